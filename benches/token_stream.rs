@@ -0,0 +1,58 @@
+//! Throughput of the token stream over generated documents, with `system.dic` in the project
+//! root directory: `cargo bench --bench token_stream`.
+//!
+//! `words` streams the words of Vibrato as is, `morphemes` goes through the analysis into
+//! morphemes like any tokenizer with filters.
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tantivy::tokenizer::Tokenizer;
+use tantivy_vibrato::filter::BaseFormFilter;
+use tantivy_vibrato::VibratoTokenizer;
+
+const SENTENCES: [&str; 6] = [
+    "すもももももももものうち。",
+    "東京大学の研究チームは新しい材料を開発したと発表しました。",
+    "明日の天気は晴れのち曇り、午後から雨が降るでしょう。",
+    "このアプリケーションはRust 1.70以降でビルドできます。",
+    "関西国際空港から羽田空港までの便は一日に十二本あります。",
+    "吾輩は猫である。名前はまだ無い。",
+];
+
+/// Documents of about 1 KB, mixing the sentences in a different order each.
+fn documents() -> Vec<String> {
+    (0..200)
+        .map(|i| {
+            (0..24)
+                .map(|j| SENTENCES[(i * 7 + j * 5) % SENTENCES.len()])
+                .collect()
+        })
+        .collect()
+}
+
+fn token_stream(c: &mut Criterion) {
+    let tokenizer = VibratoTokenizer::new("./system.dic")
+        .expect("system.dic is required in the project root directory");
+    let docs = documents();
+    let mut group = c.benchmark_group("token_stream");
+    group.throughput(Throughput::Bytes(docs.iter().map(|d| d.len() as u64).sum()));
+    for (name, tokenizer) in [
+        ("words", tokenizer.clone()),
+        ("morphemes", tokenizer.filter(BaseFormFilter::new())),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut count = 0;
+                for doc in &docs {
+                    let mut stream = tokenizer.token_stream(doc);
+                    while stream.advance() {
+                        count += stream.token().text.len();
+                    }
+                }
+                count
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, token_stream);
+criterion_main!(benches);
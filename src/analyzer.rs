@@ -0,0 +1,64 @@
+//! The recommended Japanese analyzer, used by [`VibratoTokenizer::recommended`] and
+//! [`register_japanese_analyzer`].
+use tantivy::Index;
+
+use crate::char_filter::NormalizeCharFilter;
+use crate::filter::base_form::BaseFormFilter;
+use crate::filter::lower_case::LowerCaseFilter;
+use crate::filter::pos::PosFilter;
+use crate::filter::stop_word::{StopWordFilter, KUROMOJI_STOP_WORDS};
+use crate::VibratoTokenizer;
+
+pub(crate) fn configure(tokenizer: VibratoTokenizer) -> VibratoTokenizer {
+    let kind = tokenizer.dictionary_kind();
+    tokenizer
+        .char_filter(NormalizeCharFilter::new())
+        .filter(BaseFormFilter::new())
+        .filter(PosFilter::for_dictionary(kind))
+        .filter(StopWordFilter::new(KUROMOJI_STOP_WORDS.iter().copied()))
+        .filter(LowerCaseFilter)
+}
+
+/// Register `tokenizer` with the recommended Japanese analysis, see
+/// [`VibratoTokenizer::recommended`], in the tokenizers of `index` under `name`.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::schema::{Schema, TextFieldIndexing, TextOptions};
+/// use tantivy::Index;
+/// use tantivy_vibrato::{register_japanese_analyzer, VibratoTokenizer};
+///
+/// let mut schema = Schema::builder();
+/// let indexing = TextFieldIndexing::default().set_tokenizer("lang_ja");
+/// schema.add_text_field("body", TextOptions::default().set_indexing_options(indexing));
+/// let index = Index::create_in_ram(schema.build());
+/// register_japanese_analyzer(&index, "lang_ja", VibratoTokenizer::new("/path/to/dictionary")?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn register_japanese_analyzer(index: &Index, name: &str, tokenizer: VibratoTokenizer) {
+    index.tokenizers().register(name, tokenizer.recommended());
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::Schema;
+
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn register() {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let tokenizer = test_tokenizer();
+        register_japanese_analyzer(&index, "lang_ja", tokenizer);
+
+        let analyzer = index.tokenizers().get("lang_ja").unwrap();
+        let mut stream = analyzer.token_stream("ＡＢＣの東京に行きました");
+        let mut texts = vec![];
+        while let Some(token) = stream.next() {
+            texts.push(token.text.clone());
+        }
+        assert_eq!(texts, vec!["abc", "東京", "行く"]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Command line tools for tantivy-vibrato.
+//!
+//! Built with the `cli` feature.
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+use std::time::{Duration, Instant};
+
+use tantivy::tokenizer::{TextAnalyzer, Tokenizer};
+use tantivy_vibrato::mecab::{self, OutputFormat};
+use tantivy_vibrato::{diff, VibratoTokenizer};
+
+const USAGE: &str = "\
+Usage:
+  tantivy-vibrato diff <OLD_DICT> <NEW_DICT> [CORPUS]
+  tantivy-vibrato lattice <DICT> <TEXT>
+  tantivy-vibrato mecab [-Owakati] <DICT>
+  tantivy-vibrato bench <DICT> <CORPUS> [--iterations <N>] [--lindera <LINDERA_DICT>]
+
+Commands:
+  diff      Tokenize a corpus (one document per line, stdin if omitted) with two
+            dictionaries and report the term-level differences.
+  lattice   Print the lattice of a text in the GraphViz DOT language.
+  mecab     Analyze stdin line by line and print the result in the MeCab format.
+  bench     Measure the tokenization throughput over a corpus (one document per line).
+            --lindera compares with Lindera (requires the `lindera` feature).";
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("diff") => run_diff(&args[1..]),
+        Some("lattice") => run_lattice(&args[1..]),
+        Some("mecab") => run_mecab(&args[1..]),
+        Some("bench") => run_bench(&args[1..]),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
+
+fn run_diff(args: &[String]) -> Result<()> {
+    let (old_dict, new_dict, corpus) = match args {
+        [old, new] => (old, new, None),
+        [old, new, corpus] => (old, new, Some(corpus)),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let old = TextAnalyzer::from(VibratoTokenizer::new(old_dict)?);
+    let new = TextAnalyzer::from(VibratoTokenizer::new(new_dict)?);
+    let corpus = read_corpus(corpus.map(String::as_str))?;
+
+    let report = diff::diff_corpus(&old, &new, corpus.lines());
+    print!("{report}");
+    Ok(())
+}
+
+fn run_lattice(args: &[String]) -> Result<()> {
+    let [dict, text] = args else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+
+    let tokenizer = VibratoTokenizer::new(dict)?;
+    print!("{}", tokenizer.lattice(text).to_dot());
+    Ok(())
+}
+
+fn run_mecab(args: &[String]) -> Result<()> {
+    let (output_format, dict) = match args {
+        [dict] => (OutputFormat::Mecab, dict),
+        [opt, dict] if opt == "-Owakati" => (OutputFormat::Wakati, dict),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let tokenizer = VibratoTokenizer::new(dict)?;
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let morphemes = tokenizer.morphemes(&line?);
+        stdout.write_all(mecab::format(&morphemes, output_format).as_bytes())?;
+    }
+    Ok(())
+}
+
+struct BenchResult {
+    name: &'static str,
+    elapsed: Duration,
+    tokens: usize,
+}
+
+fn run_bench(args: &[String]) -> Result<()> {
+    let mut positional = vec![];
+    let mut iterations = 3;
+    let mut lindera_dict = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => iterations = args.next().ok_or(USAGE)?.parse()?,
+            "--lindera" => lindera_dict = Some(args.next().ok_or(USAGE)?.clone()),
+            _ => positional.push(arg),
+        }
+    }
+    let [dict, corpus] = positional[..] else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+
+    let corpus = read_corpus(Some(corpus))?;
+    let docs = corpus.lines().collect::<Vec<_>>();
+
+    let tokenizer = VibratoTokenizer::new(dict)?;
+    let mut results = vec![measure("vibrato", iterations, &docs, |doc| {
+        let mut stream = tokenizer.token_stream(doc);
+        let mut tokens = 0;
+        while stream.advance() {
+            tokens += 1;
+        }
+        tokens
+    })];
+
+    if let Some(lindera_dict) = lindera_dict {
+        results.push(bench_lindera(&lindera_dict, iterations, &docs)?);
+    }
+
+    let bytes = docs.iter().map(|d| d.len()).sum::<usize>() * iterations;
+    println!(
+        "{} documents, {} bytes, {} iterations",
+        docs.len(),
+        bytes / iterations.max(1),
+        iterations
+    );
+    println!("name\telapsed\tdocs/s\tMB/s\ttokens");
+    for result in &results {
+        let secs = result.elapsed.as_secs_f64();
+        println!(
+            "{}\t{:.3}s\t{:.0}\t{:.2}\t{}",
+            result.name,
+            secs,
+            (docs.len() * iterations) as f64 / secs,
+            bytes as f64 / secs / 1_000_000.0,
+            result.tokens
+        );
+    }
+    Ok(())
+}
+
+fn measure<F>(name: &'static str, iterations: usize, docs: &[&str], mut tokenize: F) -> BenchResult
+where
+    F: FnMut(&str) -> usize,
+{
+    let mut tokens = 0;
+    let started = Instant::now();
+    for _ in 0..iterations {
+        tokens = docs.iter().map(|doc| tokenize(doc)).sum();
+    }
+    BenchResult {
+        name,
+        elapsed: started.elapsed(),
+        tokens,
+    }
+}
+
+#[cfg(feature = "lindera")]
+fn bench_lindera(dict: &str, iterations: usize, docs: &[&str]) -> Result<BenchResult> {
+    use lindera::dictionary::load_dictionary;
+    use lindera::mode::Mode;
+    use lindera::segmenter::Segmenter;
+    use lindera::tokenizer::Tokenizer;
+
+    let dictionary = load_dictionary(dict)?;
+    let tokenizer = Tokenizer::new(Segmenter::new(Mode::Normal, dictionary, None));
+    let mut result = Ok(());
+    let bench = measure("lindera", iterations, docs, |doc| match tokenizer.tokenize(doc) {
+        Ok(tokens) => tokens.len(),
+        Err(e) => {
+            result = Err(e);
+            0
+        }
+    });
+    result?;
+    Ok(bench)
+}
+
+#[cfg(not(feature = "lindera"))]
+fn bench_lindera(_dict: &str, _iterations: usize, _docs: &[&str]) -> Result<BenchResult> {
+    Err("built without the `lindera` feature".into())
+}
+
+fn read_corpus(path: Option<&str>) -> Result<String> {
+    match path {
+        Some(path) => Ok(fs::read_to_string(path)?),
+        None => {
+            let mut corpus = String::new();
+            io::stdin().read_to_string(&mut corpus)?;
+            Ok(corpus)
+        }
+    }
+}
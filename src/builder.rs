@@ -0,0 +1,92 @@
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::path;
+
+use crate::tokenizer::{Result, TokenizerOptions};
+use crate::VibratoTokenizer;
+
+/// Builds a [`VibratoTokenizer`] with options of Vibrato's tokenizer, which change the
+/// segmentation of spaces and of unknown words.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy_vibrato::VibratoTokenizer;
+///
+/// let tokenizer = VibratoTokenizer::builder()
+///     .ignore_space(true)
+///     .max_grouping_len(24)
+///     .build("/path/to/dictionary")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VibratoTokenizerBuilder {
+    options: TokenizerOptions,
+}
+
+impl VibratoTokenizerBuilder {
+    /// Create a builder with Vibrato's defaults.
+    pub fn new() -> VibratoTokenizerBuilder {
+        VibratoTokenizerBuilder::default()
+    }
+
+    /// Whether to leave spaces out of the tokens, like MeCab, instead of emitting them as
+    /// words. Disabled by default. Building fails if the dictionary has no `SPACE` character
+    /// category.
+    pub fn ignore_space(mut self, ignore_space: bool) -> VibratoTokenizerBuilder {
+        self.options.ignore_space = ignore_space;
+        self
+    }
+
+    /// Maximum length in characters of the unknown words grouped from characters of the same
+    /// category, e.g. long katakana words. The default, 0, means no limit; MeCab uses 24.
+    pub fn max_grouping_len(mut self, max_grouping_len: usize) -> VibratoTokenizerBuilder {
+        self.options.max_grouping_len = max_grouping_len;
+        self
+    }
+
+    /// Build a tokenizer with the Vibrato dictionary file at `dict_path`. Requires the `fs`
+    /// feature.
+    #[cfg(feature = "fs")]
+    pub fn build<P: AsRef<path::Path>>(&self, dict_path: P) -> Result<VibratoTokenizer> {
+        let file = fs::File::open(dict_path)?;
+        self.build_from_reader(file)
+    }
+
+    /// Build a tokenizer with a reader of the Vibrato dictionary file.
+    pub fn build_from_reader<R: Read>(&self, reader: R) -> Result<VibratoTokenizer> {
+        VibratoTokenizer::from_reader_with_options(reader, self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn options() {
+        let surfaces = |builder: VibratoTokenizerBuilder| {
+            let tokenizer = builder.build("./system.dic").unwrap();
+            let surfaces = tokenizer
+                .morphemes("東京 アイウエオカキクケコ")
+                .into_iter()
+                .map(|t| t.surface)
+                .collect::<Vec<_>>();
+            (surfaces, tokenizer.fingerprint())
+        };
+        let (default, fingerprint) = surfaces(VibratoTokenizer::builder());
+        assert_eq!(default, vec!["東京", " ", "アイウエオカキクケコ"]);
+        assert_eq!(fingerprint, test_tokenizer().fingerprint());
+        let (configured, fingerprint) = surfaces(
+            VibratoTokenizer::builder()
+                .ignore_space(true)
+                .max_grouping_len(5),
+        );
+        assert_eq!(configured.concat(), "東京アイウエオカキクケコ");
+        assert!(configured.len() > 2);
+        assert_ne!(fingerprint, test_tokenizer().fingerprint());
+    }
+}
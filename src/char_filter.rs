@@ -0,0 +1,335 @@
+//! Filters rewriting the text before it is given to Vibrato.
+//!
+//! [`OffsetMap`] can also be used on its own by applications which preprocess the text
+//! themselves, e.g. to strip the markup of a document, to map the offsets of the tokens back to
+//! the raw document for highlighting:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy::tokenizer::Tokenizer;
+//! use tantivy_vibrato::char_filter::OffsetMapBuilder;
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! // "<b>東京</b>" stripped into "東京".
+//! let mut builder = OffsetMapBuilder::new();
+//! builder.replace("<b>".len(), 0);
+//! builder.copy("東京".len());
+//! builder.replace("</b>".len(), 0);
+//! let offsets = builder.build();
+//!
+//! let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+//! let mut stream = tokenizer.token_stream("東京");
+//! while let Some(token) = stream.next() {
+//!     let mut token = token.clone();
+//!     offsets.remap_token(&mut token);
+//!     assert_eq!(token.offset_from, 3);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::any::type_name;
+use std::ops::Range;
+
+use tantivy::tokenizer::Token;
+
+mod enclosed;
+mod markup;
+mod normalize;
+
+pub(crate) use markup::find_end_tag;
+
+pub use enclosed::EnclosedCharFilter;
+pub use markup::MarkupCharFilter;
+pub use normalize::NormalizeCharFilter;
+
+/// A filter rewriting the text before morphological analysis.
+///
+/// The returned [`OffsetMap`] maps offsets of the rewritten text back to the input text so
+/// that the emitted tokens keep pointing at the original text.
+pub trait CharFilter: Send + Sync + 'static {
+    /// Name of the filter, used by [`explain`](crate::VibratoTokenizer::explain).
+    fn name(&self) -> &'static str {
+        short_type_name::<Self>()
+    }
+
+    /// Rewrite `text`.
+    fn filter(&self, text: &str) -> (String, OffsetMap);
+
+    /// Parameters of the filter, included in the
+    /// [`fingerprint`](crate::VibratoTokenizer::fingerprint) of the tokenizer.
+    fn config(&self) -> String {
+        String::new()
+    }
+}
+
+pub(crate) fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    filtered: Range<usize>,
+    original: Range<usize>,
+    replaced: bool,
+}
+
+/// Mapping of byte offsets in a rewritten text to byte offsets in its original text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap {
+    segments: Vec<Segment>,
+    filtered_len: usize,
+    original_len: usize,
+}
+
+impl OffsetMap {
+    /// Create a map for a text of `len` bytes which is not rewritten at all.
+    pub fn identity(len: usize) -> OffsetMap {
+        let mut builder = OffsetMapBuilder::new();
+        builder.copy(len);
+        builder.build()
+    }
+
+    /// Map the start offset of a span in the rewritten text to the original text.
+    ///
+    /// A span starting inside a replacement starts at the beginning of the replaced text.
+    pub fn original_start(&self, offset: usize) -> usize {
+        if offset >= self.filtered_len {
+            return self.original_len;
+        }
+        let i = self
+            .segments
+            .partition_point(|s| s.filtered.end <= offset);
+        match self.segments.get(i) {
+            Some(s) if s.replaced => s.original.start,
+            Some(s) => s.original.start + (offset - s.filtered.start),
+            None => self.original_len,
+        }
+    }
+
+    /// Map the end offset of a span in the rewritten text to the original text.
+    ///
+    /// A span ending inside a replacement ends at the end of the replaced text.
+    pub fn original_end(&self, offset: usize) -> usize {
+        if offset == 0 {
+            return 0;
+        }
+        let i = self.segments.partition_point(|s| s.filtered.end < offset);
+        match self.segments.get(i) {
+            Some(s) if s.replaced => s.original.end,
+            Some(s) => s.original.start + (offset - s.filtered.start),
+            None => self.original_len,
+        }
+    }
+
+    /// Map a span of the rewritten text to the original text.
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_start(range.start)..self.original_end(range.end)
+    }
+
+    /// Map the offsets of `token`, produced from the rewritten text, to the original text.
+    pub fn remap_token(&self, token: &mut Token) {
+        let range = self.original_range(token.offset_from..token.offset_to);
+        token.offset_from = range.start;
+        token.offset_to = range.end.max(range.start);
+    }
+
+    /// Map the start offset of a span in the original text to the rewritten text.
+    ///
+    /// A span starting inside a replaced text starts at the beginning of its replacement.
+    pub fn filtered_start(&self, offset: usize) -> usize {
+        if offset >= self.original_len {
+            return self.filtered_len;
+        }
+        let i = self
+            .segments
+            .partition_point(|s| s.original.end <= offset);
+        match self.segments.get(i) {
+            Some(s) if s.replaced => s.filtered.start,
+            Some(s) => s.filtered.start + (offset - s.original.start),
+            None => self.filtered_len,
+        }
+    }
+
+    /// Map the end offset of a span in the original text to the rewritten text.
+    ///
+    /// A span ending inside a replaced text ends at the end of its replacement.
+    pub fn filtered_end(&self, offset: usize) -> usize {
+        if offset == 0 {
+            return 0;
+        }
+        let i = self.segments.partition_point(|s| s.original.end < offset);
+        match self.segments.get(i) {
+            Some(s) if s.replaced => s.filtered.end,
+            Some(s) => s.filtered.start + (offset - s.original.start),
+            None => self.filtered_len,
+        }
+    }
+
+    /// Map a span of the original text to the rewritten text, e.g. to find the tokens
+    /// covering a selection of the raw document.
+    pub fn filtered_range(&self, range: Range<usize>) -> Range<usize> {
+        self.filtered_start(range.start)..self.filtered_end(range.end)
+    }
+
+    /// Length of the rewritten text in bytes.
+    pub fn filtered_len(&self) -> usize {
+        self.filtered_len
+    }
+
+    /// Length of the original text in bytes.
+    pub fn original_len(&self) -> usize {
+        self.original_len
+    }
+
+    /// Returns `true` if the rewritten text is identical to the original text.
+    pub fn is_identity(&self) -> bool {
+        self.segments.iter().all(|s| !s.replaced)
+    }
+
+    /// Returns the rewritten spans as `(rewritten range, original range)` pairs.
+    pub fn replacements(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+        self.segments
+            .iter()
+            .filter(|s| s.replaced)
+            .map(|s| (s.filtered.clone(), s.original.clone()))
+    }
+}
+
+/// Builder of an [`OffsetMap`], fed while the rewritten text is produced from left to right.
+#[derive(Debug, Default)]
+pub struct OffsetMapBuilder {
+    map: OffsetMap,
+}
+
+impl OffsetMapBuilder {
+    /// Create an empty builder.
+    pub fn new() -> OffsetMapBuilder {
+        OffsetMapBuilder::default()
+    }
+
+    /// Record that the next `len` bytes are copied as-is.
+    pub fn copy(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let map = &mut self.map;
+        match map.segments.last_mut() {
+            Some(last) if !last.replaced => {
+                last.filtered.end += len;
+                last.original.end += len;
+            }
+            _ => map.segments.push(Segment {
+                filtered: map.filtered_len..map.filtered_len + len,
+                original: map.original_len..map.original_len + len,
+                replaced: false,
+            }),
+        }
+        map.filtered_len += len;
+        map.original_len += len;
+    }
+
+    /// Record that the next `original_len` bytes of the original text are replaced with
+    /// `filtered_len` bytes.
+    pub fn replace(&mut self, original_len: usize, filtered_len: usize) {
+        if original_len == 0 && filtered_len == 0 {
+            return;
+        }
+        let map = &mut self.map;
+        map.segments.push(Segment {
+            filtered: map.filtered_len..map.filtered_len + filtered_len,
+            original: map.original_len..map.original_len + original_len,
+            replaced: true,
+        });
+        map.filtered_len += filtered_len;
+        map.original_len += original_len;
+    }
+
+    /// Finish building the map.
+    pub fn build(self) -> OffsetMap {
+        self.map
+    }
+}
+
+/// Maps offsets through the maps of several char filters applied in order.
+pub(crate) fn original_range(maps: &[OffsetMap], range: Range<usize>) -> Range<usize> {
+    maps.iter().rev().fold(range, |range, map| {
+        let start = map.original_start(range.start);
+        let end = map.original_end(range.end).max(start);
+        start..end
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "a<b>c" -> "ac", "x" -> "xyz"
+    fn map() -> OffsetMap {
+        let mut builder = OffsetMapBuilder::new();
+        builder.copy(1);
+        builder.replace(3, 0);
+        builder.copy(1);
+        builder.replace(1, 3);
+        builder.build()
+    }
+
+    #[test]
+    fn identity() {
+        let map = OffsetMap::identity(5);
+        assert!(map.is_identity());
+        assert_eq!(map.original_range(1..3), 1..3);
+        assert_eq!(map.original_range(0..5), 0..5);
+    }
+
+    #[test]
+    fn deletion_and_expansion() {
+        let map = map();
+        assert!(!map.is_identity());
+        // "a"
+        assert_eq!(map.original_range(0..1), 0..1);
+        // "c"
+        assert_eq!(map.original_range(1..2), 4..5);
+        // "ac"
+        assert_eq!(map.original_range(0..2), 0..5);
+        // "yz" is inside the expansion of "x"
+        assert_eq!(map.original_range(3..5), 5..6);
+        assert_eq!(map.original_range(2..5), 5..6);
+        assert_eq!(
+            map.replacements().collect::<Vec<_>>(),
+            vec![(1..1, 1..4), (2..5, 5..6)]
+        );
+    }
+
+    #[test]
+    fn filtered_offsets() {
+        let map = map();
+        // "a"
+        assert_eq!(map.filtered_range(0..1), 0..1);
+        // "<b>" is removed
+        assert_eq!(map.filtered_range(1..4), 1..1);
+        // "c"
+        assert_eq!(map.filtered_range(4..5), 1..2);
+        // "x" is expanded into "xyz"
+        assert_eq!(map.filtered_range(5..6), 2..5);
+
+        let mut token = Token {
+            offset_from: 1,
+            offset_to: 2,
+            ..Token::default()
+        };
+        map.remap_token(&mut token);
+        assert_eq!((token.offset_from, token.offset_to), (4, 5));
+    }
+
+    #[test]
+    fn chained_maps() {
+        let mut builder = OffsetMapBuilder::new();
+        builder.replace(2, 1);
+        builder.copy(3);
+        let second = builder.build();
+        // "xyz" of the second input starts at byte 2 and maps back to "x" at byte 5.
+        assert_eq!(original_range(&[map(), second], 1..4), 5..6);
+    }
+}
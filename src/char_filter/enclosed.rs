@@ -0,0 +1,130 @@
+use crate::char_filter::{CharFilter, OffsetMap, OffsetMapBuilder};
+
+/// Enclosed ideographs expanded to the words they abbreviate. The others are replaced with
+/// their ideograph, e.g. `㊤` with `上`.
+const ABBREVIATIONS: [(char, &str); 22] = [
+    ('㈱', "株式会社"),
+    ('㈲', "有限会社"),
+    ('㈳', "社団法人"),
+    ('㈴', "合名会社"),
+    ('㈵', "特殊法人"),
+    ('㈶', "財団法人"),
+    ('㈹', "代表"),
+    ('㈻', "学校法人"),
+    ('㈼', "監査法人"),
+    ('㈽', "企業組合"),
+    ('㈾', "合資会社"),
+    ('㈿', "協同組合"),
+    ('㊑', "株式会社"),
+    ('㊒', "有限会社"),
+    ('㊓', "社団法人"),
+    ('㊖', "財団法人"),
+    ('㍿', "株式会社"),
+    ('㍻', "平成"),
+    ('㍼', "昭和"),
+    ('㍽', "大正"),
+    ('㍾', "明治"),
+    ('㋿', "令和"),
+];
+
+/// Ideographs of `㈠` (U+3220) to `㉃` (U+3243).
+const PARENTHESIZED_IDEOGRAPHS: &str =
+    "一二三四五六七八九十月火水木金土日株有社名特財祝労代呼学監企資協祭休自至";
+
+/// Ideographs of `㊀` (U+3280) to `㊰` (U+32B0).
+const CIRCLED_IDEOGRAPHS: &str =
+    "一二三四五六七八九十月火水木金土日株有社名特財祝労秘男女適優印注項休写正上中下左右医宗学監企資協夜";
+
+/// Replaces enclosed alphanumerics and ideographs with the characters they enclose, e.g. `①`
+/// with `1`, `Ⓐ` with `A` and `㊤` with `上`, and the enclosed abbreviations common in
+/// business documents with the words they stand for, e.g. `㈱` with `株式会社`.
+///
+/// These characters are analyzed as symbols and cannot be matched by the text they stand for
+/// otherwise. The offsets of the rewritten characters are kept, so that `株式会社` is
+/// highlighted as `㈱`.
+#[derive(Debug, Clone, Default)]
+pub struct EnclosedCharFilter;
+
+impl EnclosedCharFilter {
+    /// Create a new `EnclosedCharFilter`.
+    pub fn new() -> EnclosedCharFilter {
+        EnclosedCharFilter
+    }
+}
+
+fn offset(c: char, first: char) -> u32 {
+    c as u32 - first as u32
+}
+
+fn letter(base: char, i: u32) -> String {
+    char::from_u32(base as u32 + i).unwrap().to_string()
+}
+
+fn nth(chars: &str, i: u32) -> String {
+    chars.chars().nth(i as usize).unwrap().to_string()
+}
+
+/// Returns the replacement of `c`, or `None` if it is not an enclosed character.
+fn expand(c: char) -> Option<String> {
+    if let Some((_, word)) = ABBREVIATIONS.iter().find(|(a, _)| *a == c) {
+        return Some(word.to_string());
+    }
+    let expanded = match c {
+        '①'..='⑳' => (offset(c, '①') + 1).to_string(),
+        '⑴'..='⒇' => (offset(c, '⑴') + 1).to_string(),
+        '⒈'..='⒛' => (offset(c, '⒈') + 1).to_string(),
+        '⒜'..='⒵' => letter('a', offset(c, '⒜')),
+        'Ⓐ'..='Ⓩ' => letter('A', offset(c, 'Ⓐ')),
+        'ⓐ'..='ⓩ' => letter('a', offset(c, 'ⓐ')),
+        '⓪' | '⓿' | '🄀' => "0".to_string(),
+        '⓫'..='⓴' => (offset(c, '⓫') + 11).to_string(),
+        '⓵'..='⓾' => (offset(c, '⓵') + 1).to_string(),
+        '❶'..='❿' => (offset(c, '❶') + 1).to_string(),
+        '➀'..='➉' => (offset(c, '➀') + 1).to_string(),
+        '➊'..='➓' => (offset(c, '➊') + 1).to_string(),
+        '㉑'..='㉟' => (offset(c, '㉑') + 21).to_string(),
+        '㊱'..='㊿' => (offset(c, '㊱') + 36).to_string(),
+        '㈠'..='㉃' => nth(PARENTHESIZED_IDEOGRAPHS, offset(c, '㈠')),
+        '㊀'..='㊰' => nth(CIRCLED_IDEOGRAPHS, offset(c, '㊀')),
+        _ => return None,
+    };
+    Some(expanded)
+}
+
+impl CharFilter for EnclosedCharFilter {
+    fn filter(&self, text: &str) -> (String, OffsetMap) {
+        let mut filtered = String::with_capacity(text.len());
+        let mut offsets = OffsetMapBuilder::new();
+        for c in text.chars() {
+            match expand(c) {
+                Some(expanded) => {
+                    offsets.replace(c.len_utf8(), expanded.len());
+                    filtered.push_str(&expanded);
+                }
+                None => {
+                    offsets.copy(c.len_utf8());
+                    filtered.push(c);
+                }
+            }
+        }
+        (filtered, offsets.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand() {
+        let (filtered, offsets) = EnclosedCharFilter::new().filter("①⑳㉑㊿Ⓐⓩ㊤㈱すもも");
+        assert_eq!(filtered, "1202150Az上株式会社すもも");
+        let start = filtered.find("株式会社").unwrap();
+        assert_eq!(
+            offsets.original_range(start..start + "株式会社".len()),
+            "①⑳㉑㊿Ⓐⓩ㊤".len().."①⑳㉑㊿Ⓐⓩ㊤㈱".len()
+        );
+        assert_eq!(super::expand('㊰').as_deref(), Some("夜"));
+        assert_eq!(super::expand('㉃').as_deref(), Some("至"));
+    }
+}
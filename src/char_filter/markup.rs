@@ -0,0 +1,387 @@
+use crate::char_filter::{CharFilter, OffsetMap, OffsetMapBuilder};
+
+/// Tags replaced with a line break, so that the words of adjacent blocks are not joined.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Tags whose content is removed along with them.
+const HIDDEN_TAGS: &[&str] = &["script", "style", "rt", "rp"];
+
+/// Tags of ruby annotations, kept by [`MarkupCharFilter::keep_ruby`].
+const RUBY_TAGS: &[&str] = &["ruby", "rb", "rt", "rp"];
+
+const ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", " "),
+    ("copy", "©"),
+    ("reg", "®"),
+    ("hellip", "…"),
+    ("mdash", "—"),
+    ("ndash", "–"),
+    ("laquo", "«"),
+    ("raquo", "»"),
+    ("yen", "¥"),
+];
+
+/// Strips HTML tags and decodes character references, and optionally removes the Markdown
+/// syntax, so that web pages and README-like documents can be indexed as they are.
+///
+/// Tags and comments are removed, except block-level tags such as `<p>` or `<br>` which are
+/// replaced with a line break, and the content of `<script>` and `<style>` is removed too, as
+/// well as ruby annotations unless [`keep_ruby`](Self::keep_ruby) is enabled.
+/// Named references of common characters (`&amp;`, `&nbsp;`, ...) and numeric references
+/// (`&#12354;`, `&#x3042;`) are decoded. Offsets of the remaining text point at the raw
+/// document, so that highlights are placed correctly.
+///
+/// With [`markdown`](Self::markdown), heading, quote and list markers, code fences, emphasis
+/// markers, inline code quotes and the URLs of links and images are removed as well, keeping
+/// the texts of links and the alternative texts of images.
+#[derive(Debug, Clone, Default)]
+pub struct MarkupCharFilter {
+    markdown: bool,
+    keep_ruby: bool,
+}
+
+impl MarkupCharFilter {
+    /// Create a filter stripping HTML.
+    pub fn new() -> MarkupCharFilter {
+        MarkupCharFilter {
+            markdown: false,
+            keep_ruby: false,
+        }
+    }
+
+    /// Whether to also remove the Markdown syntax. Disabled by default.
+    pub fn markdown(mut self, markdown: bool) -> MarkupCharFilter {
+        self.markdown = markdown;
+        self
+    }
+
+    /// Whether to keep the `<ruby>` elements as they are, for a
+    /// [`RubyRecognizer`](crate::recognizer::RubyRecognizer) to use their annotations as
+    /// readings. Disabled by default.
+    pub fn keep_ruby(mut self, keep_ruby: bool) -> MarkupCharFilter {
+        self.keep_ruby = keep_ruby;
+        self
+    }
+}
+
+/// Output of the filter, with its offset map.
+struct Output {
+    text: String,
+    offsets: OffsetMapBuilder,
+}
+
+impl Output {
+    fn copy(&mut self, s: &str) {
+        self.text.push_str(s);
+        self.offsets.copy(s.len());
+    }
+
+    fn replace(&mut self, original_len: usize, replacement: &str) {
+        self.text.push_str(replacement);
+        self.offsets.replace(original_len, replacement.len());
+    }
+}
+
+/// Returns the name of the tag starting `tag`, e.g. `p` for `</p>`, lowercased.
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/'])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Returns the offset of the first end tag of `text` named one of `names`, in lowercase, and
+/// that name. Names are matched case-insensitively without copying `text`, and must be followed
+/// by `>` or a space, so that `</rt` does not match `</rtc>`.
+pub(crate) fn find_end_tag<'n>(text: &str, names: &[&'n str]) -> Option<(usize, &'n str)> {
+    let bytes = text.as_bytes();
+    text.match_indices("</").find_map(|(i, _)| {
+        let rest = &bytes[i + 2..];
+        names
+            .iter()
+            .find(|name| {
+                rest.len() > name.len()
+                    && rest[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+                    && (rest[name.len()] == b'>' || rest[name.len()].is_ascii_whitespace())
+            })
+            .map(|name| (i, *name))
+    })
+}
+
+/// Returns the length of the tag or comment at the start of `text`, if there is one.
+fn tag_len(text: &str) -> Option<usize> {
+    if let Some(comment) = text.strip_prefix("<!--") {
+        return Some(comment.find("-->").map_or(text.len(), |i| 4 + i + 3));
+    }
+    let next = text[1..].chars().next()?;
+    if !(next.is_ascii_alphabetic() || next == '/' || next == '!' || next == '?') {
+        return None;
+    }
+    text.find('>').map(|i| i + 1)
+}
+
+/// Returns the decoded character reference at the start of `text` and its length.
+fn entity(text: &str) -> Option<(String, usize)> {
+    let end = text[1..].find(';').filter(|&i| i <= 10)? + 1;
+    let name = &text[1..end];
+    let decoded = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+        char::from_u32(u32::from_str_radix(hex, 16).ok()?)?.to_string()
+    } else if let Some(dec) = name.strip_prefix('#') {
+        char::from_u32(dec.parse().ok()?)?.to_string()
+    } else {
+        ENTITIES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, c)| c.to_string())?
+    };
+    Some((decoded, end + 1))
+}
+
+/// Returns the length of the Markdown block marker at the start of `line`: heading, quote or
+/// list marker with the spaces following it.
+fn block_marker_len(line: &str) -> usize {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let rest = &line[indent..];
+    let marker = if rest.starts_with('#') {
+        rest.len() - rest.trim_start_matches('#').len()
+    } else if rest.starts_with('>')
+        || rest.starts_with(['-', '*', '+']) && rest[1..].starts_with(' ')
+    {
+        1
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0
+            && rest[digits..].starts_with(['.', ')'])
+            && rest[digits + 1..].starts_with(' ')
+        {
+            digits + 1
+        } else {
+            return 0;
+        }
+    };
+    let spaces = rest[marker..].len() - rest[marker..].trim_start_matches(' ').len();
+    if marker > 0 && (spaces > 0 || rest[marker..].is_empty() || rest.starts_with('>')) {
+        indent + marker + spaces
+    } else {
+        0
+    }
+}
+
+/// Returns the length of `](url)` at the start of `text` if it closes a link.
+fn link_target_len(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix("](")?;
+    let close = rest.find([')', '\n'])?;
+    rest[close..].starts_with(')').then_some(2 + close + 1)
+}
+
+impl MarkupCharFilter {
+    fn strip(&self, text: &str) -> Output {
+        let mut out = Output {
+            text: String::with_capacity(text.len()),
+            offsets: OffsetMapBuilder::new(),
+        };
+        // Start offsets of the `](url)` closing the links opened so far.
+        let mut link_ends: Vec<usize> = vec![];
+        let mut in_fence = false;
+        let mut i = 0;
+        while i < text.len() {
+            let rest = &text[i..];
+            let line_start = i == 0 || text.as_bytes()[i - 1] == b'\n';
+            if self.markdown && line_start {
+                let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+                    in_fence = !in_fence;
+                    out.replace(line.len(), "");
+                    i += line.len();
+                    continue;
+                }
+                let marker = if in_fence { 0 } else { block_marker_len(line) };
+                if marker > 0 {
+                    out.replace(marker, "");
+                    i += marker;
+                    continue;
+                }
+            }
+
+            let c = rest.chars().next().unwrap();
+            if c == '<' {
+                if let Some(len) = tag_len(rest) {
+                    let name = tag_name(&rest[..len]);
+                    if self.keep_ruby && RUBY_TAGS.contains(&name.as_str()) {
+                        out.copy(&rest[..len]);
+                        i += len;
+                        continue;
+                    }
+                    let mut len = len;
+                    if HIDDEN_TAGS.contains(&name.as_str()) && !rest.starts_with("</") {
+                        // The end tag of annotations may be omitted, ending them at `</ruby>`.
+                        let names = if RUBY_TAGS.contains(&name.as_str()) {
+                            &[name.as_str(), "ruby"][..]
+                        } else {
+                            &[name.as_str()][..]
+                        };
+                        len = match find_end_tag(rest, names) {
+                            Some((j, "ruby")) => j,
+                            Some((j, _)) => rest[j..].find('>').map_or(rest.len(), |k| j + k + 1),
+                            None => rest.len(),
+                        };
+                    }
+                    let replacement = if BLOCK_TAGS.contains(&name.as_str()) {
+                        "\n"
+                    } else {
+                        ""
+                    };
+                    out.replace(len, replacement);
+                    i += len;
+                    continue;
+                }
+            }
+            if c == '&' {
+                if let Some((decoded, len)) = entity(rest) {
+                    out.replace(len, &decoded);
+                    i += len;
+                    continue;
+                }
+            }
+            if self.markdown && !in_fence {
+                if let Some(len) = self.inline_marker_len(text, i, &mut link_ends) {
+                    out.replace(len, "");
+                    i += len;
+                    continue;
+                }
+            }
+            out.copy(&rest[..c.len_utf8()]);
+            i += c.len_utf8();
+        }
+        out
+    }
+
+    /// Returns the length of the Markdown inline marker at `i`, if there is one.
+    fn inline_marker_len(&self, text: &str, i: usize, link_ends: &mut Vec<usize>) -> Option<usize> {
+        let rest = &text[i..];
+        if link_ends.last() == Some(&i) {
+            link_ends.pop();
+            return link_target_len(rest);
+        }
+        let opening = if rest.starts_with("![") {
+            Some(2)
+        } else if rest.starts_with('[') {
+            Some(1)
+        } else {
+            None
+        };
+        if let Some(opening) = opening {
+            // The text of the link ends at the first `](` of the line.
+            let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+            let close = line.find("](")?;
+            link_target_len(&line[close..])?;
+            link_ends.push(i + close);
+            return Some(opening);
+        }
+        if rest.starts_with("**") || rest.starts_with("__") || rest.starts_with("~~") {
+            return Some(2);
+        }
+        let c = rest.chars().next()?;
+        let previous = text[..i].chars().next_back();
+        let next = rest[c.len_utf8()..].chars().next();
+        let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+        match c {
+            '`' => Some(1),
+            // `_` inside words is kept, e.g. `snake_case`.
+            '*' | '_' if !(is_word(previous) && is_word(next)) => Some(1),
+            _ => None,
+        }
+    }
+}
+
+impl CharFilter for MarkupCharFilter {
+    fn filter(&self, text: &str) -> (String, OffsetMap) {
+        let out = self.strip(text);
+        (out.text, out.offsets.build())
+    }
+
+    fn config(&self) -> String {
+        format!("markdown={} keep_ruby={}", self.markdown, self.keep_ruby)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(filter: &MarkupCharFilter, text: &str) -> String {
+        filter.filter(text).0
+    }
+
+    #[test]
+    fn html() {
+        let filter = MarkupCharFilter::new();
+        let text = "<p>東京&amp;<b>大阪</b></p><script>x < 1</script>&#x3042;<!-- c -->&lt;";
+        let (filtered, offsets) = filter.filter(text);
+        assert_eq!(filtered, "\n東京&大阪\nあ<");
+        let start = filtered.find("大阪").unwrap();
+        let original = offsets.original_range(start..start + "大阪".len());
+        assert_eq!(&text[original], "大阪");
+        let ruby = "<ruby>東京<rt>とうきょう</rt></ruby>";
+        assert_eq!(strip(&filter, ruby), "東京");
+        assert_eq!(strip(&filter.clone().keep_ruby(true), ruby), ruby);
+        assert_eq!(
+            strip(&filter, "<ruby>東<RT>とう</RTC>う</rt>京<rt>きょう</RUBY>"),
+            "東京"
+        );
+        assert_eq!(
+            strip(&filter, "a < b &unknown; <br/>c"),
+            "a < b &unknown; \nc"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let filter = MarkupCharFilter::new().markdown(true);
+        let text = "# 見出し\n- **東京**の[大学](https://example.com)\n```\nlet snake_case = 1;\n```\n> `code` ![画像](a.png)";
+        assert_eq!(
+            strip(&filter, text),
+            "見出し\n東京の大学\n\nlet snake_case = 1;\n\ncode 画像"
+        );
+        assert_eq!(strip(&MarkupCharFilter::new(), "# a"), "# a");
+    }
+}
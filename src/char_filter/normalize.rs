@@ -0,0 +1,109 @@
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::char_filter::{CharFilter, OffsetMap, OffsetMapBuilder};
+use crate::filter::width::fold_width;
+
+/// Normalizes the text with Unicode NFKC before the analysis, so that full-width ASCII,
+/// half-width katakana and composed or decomposed forms are segmented and indexed alike, e.g.
+/// `ＡＢＣ` as `ABC`, `ｶﾞｲﾄﾞ` as `ガイド` and `か` + U+3099 as `が`.
+///
+/// The text is normalized by runs of characters which normalize on their own, and the offsets
+/// of each rewritten run are kept, so that highlights cover the original characters.
+///
+/// NFKC also rewrites compatibility characters such as `①` or `㍿`; with
+/// [`width_only`](Self::width_only), only the width of ASCII and katakana is folded.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeCharFilter {
+    width_only: bool,
+}
+
+impl NormalizeCharFilter {
+    /// Create a filter normalizing with NFKC.
+    pub fn new() -> NormalizeCharFilter {
+        NormalizeCharFilter::default()
+    }
+
+    /// Whether to only fold full-width ASCII to half-width and half-width katakana to
+    /// full-width, like Lucene's `CJKWidthFilter`, instead of normalizing with NFKC.
+    pub fn width_only(mut self, width_only: bool) -> NormalizeCharFilter {
+        self.width_only = width_only;
+        self
+    }
+
+    fn normalize(&self, run: &str) -> String {
+        if self.width_only {
+            fold_width(run)
+        } else {
+            run.nfkc().collect()
+        }
+    }
+}
+
+/// Returns `true` if `c` may compose with the character before it: combining marks, the
+/// half-width voiced sound marks and Hangul vowel and final jamo.
+fn joins_previous(c: char) -> bool {
+    matches!(c, '\u{FF9E}' | '\u{FF9F}' | '\u{1160}'..='\u{11FF}')
+        || c.nfkd()
+            .next()
+            .is_some_and(|d| canonical_combining_class(d) != 0)
+}
+
+impl CharFilter for NormalizeCharFilter {
+    fn filter(&self, text: &str) -> (String, OffsetMap) {
+        let mut filtered = String::with_capacity(text.len());
+        let mut offsets = OffsetMapBuilder::new();
+        let mut start = 0;
+        let bounds = text
+            .char_indices()
+            .skip(1)
+            .filter(|(_, c)| !joins_previous(*c))
+            .map(|(i, _)| i)
+            .chain([text.len()]);
+        for end in bounds {
+            let run = &text[start..end];
+            let normalized = self.normalize(run);
+            if normalized == run {
+                offsets.copy(run.len());
+            } else {
+                offsets.replace(run.len(), normalized.len());
+            }
+            filtered.push_str(&normalized);
+            start = end;
+        }
+        (filtered, offsets.build())
+    }
+
+    fn config(&self) -> String {
+        if self.width_only {
+            "width_only".to_string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let text = "ＡＢＣのｶﾞｲﾄﾞか\u{3099}①";
+        let (filtered, offsets) = NormalizeCharFilter::new().filter(text);
+        assert_eq!(filtered, "ABCのガイドが1");
+        let start = filtered.find("ガイド").unwrap();
+        assert_eq!(
+            &text[offsets.original_range(start..start + "ガイド".len())],
+            "ｶﾞｲﾄﾞ"
+        );
+        let start = filtered.find("が").unwrap();
+        assert_eq!(
+            &text[offsets.original_range(start..start + "が".len())],
+            "か\u{3099}"
+        );
+
+        let (filtered, _) = NormalizeCharFilter::new().width_only(true).filter(text);
+        assert_eq!(filtered, "ABCのガイドか\u{3099}①");
+    }
+}
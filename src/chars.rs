@@ -0,0 +1,9 @@
+//! Character classes shared by the filters and recognizers.
+
+/// Whether `c` is a kanji, including the supplementary ideographs like `𠮷` and the marks
+/// written in runs of kanji like `々` and `ヶ`.
+pub(crate) fn is_kanji(c: char) -> bool {
+    matches!(c,
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3FFFF}' | '々' | '〆' | '〇' | 'ヶ')
+}
@@ -0,0 +1,205 @@
+//! Implementations of the `Tokenizer` trait of tantivy 0.20 and later, enabled by the
+//! `tantivy-0-NN` features.
+//!
+//! Since tantivy 0.20, the trait lives in the `tantivy-tokenizer-api` crate, takes `&mut self`
+//! and returns an associated token stream type. The tokenizers of this crate implement the
+//! trait of each enabled version besides the one of tantivy 0.19, so they can be registered
+//! in the `TokenizerManager` of a recent tantivy index. The version of the feature must match
+//! the version of tantivy used by the application, e.g. `tantivy-0-22` for tantivy 0.22.
+//!
+//! The fallback of a [`LanguageGate`](crate::LanguageGate) is then a tokenizer of that
+//! version, e.g. its `SimpleTokenizer`.
+
+/// Defines the module implementing the `Tokenizer` trait of the tokenizer API crate `$api`.
+macro_rules! tokenizer_api {
+    ($feature:literal, $module:ident, $api:ident) => {
+        #[cfg(feature = $feature)]
+        #[doc = concat!("The `Tokenizer` trait of the `", $feature, "` feature.")]
+        pub mod $module {
+            use tantivy::tokenizer::{BoxTokenStream, Tokenizer as TTokenizer};
+
+            use crate::{
+                LanguageGate, MultiDictTokenizer, ReloadableVibratoTokenizer, ScriptRouter,
+                VibratoTokenizer,
+            };
+
+            /// Token stream of the tokenizers, converting the tokens of tantivy 0.19.
+            pub struct TokenStream<'a> {
+                tail: BoxTokenStream<'a>,
+                token: $api::Token,
+            }
+
+            impl<'a> TokenStream<'a> {
+                fn new(tail: BoxTokenStream<'a>) -> TokenStream<'a> {
+                    TokenStream {
+                        tail,
+                        token: $api::Token::default(),
+                    }
+                }
+            }
+
+            impl $api::TokenStream for TokenStream<'_> {
+                fn advance(&mut self) -> bool {
+                    if !self.tail.advance() {
+                        return false;
+                    }
+                    let token = self.tail.token();
+                    self.token.offset_from = token.offset_from;
+                    self.token.offset_to = token.offset_to;
+                    self.token.position = token.position;
+                    self.token.position_length = token.position_length;
+                    self.token.text.clone_from(&token.text);
+                    true
+                }
+
+                fn token(&self) -> &$api::Token {
+                    &self.token
+                }
+
+                fn token_mut(&mut self) -> &mut $api::Token {
+                    &mut self.token
+                }
+            }
+
+            tokenizer_api!(@impl $api, VibratoTokenizer);
+            tokenizer_api!(@impl $api, ReloadableVibratoTokenizer);
+            tokenizer_api!(@impl $api, MultiDictTokenizer);
+            tokenizer_api!(@impl $api, ScriptRouter);
+
+            /// Token stream of [`LanguageGate`], from Vibrato or from the fallback tokenizer.
+            pub enum GateTokenStream<'a, S> {
+                Japanese(TokenStream<'a>),
+                Fallback(S),
+            }
+
+            impl<S: $api::TokenStream> $api::TokenStream for GateTokenStream<'_, S> {
+                fn advance(&mut self) -> bool {
+                    match self {
+                        GateTokenStream::Japanese(stream) => stream.advance(),
+                        GateTokenStream::Fallback(stream) => stream.advance(),
+                    }
+                }
+
+                fn token(&self) -> &$api::Token {
+                    match self {
+                        GateTokenStream::Japanese(stream) => stream.token(),
+                        GateTokenStream::Fallback(stream) => stream.token(),
+                    }
+                }
+
+                fn token_mut(&mut self) -> &mut $api::Token {
+                    match self {
+                        GateTokenStream::Japanese(stream) => stream.token_mut(),
+                        GateTokenStream::Fallback(stream) => stream.token_mut(),
+                    }
+                }
+            }
+
+            /// The fallback is a tokenizer of the same tantivy version, e.g. its
+            /// `SimpleTokenizer`.
+            impl<T: $api::Tokenizer> $api::Tokenizer for LanguageGate<T> {
+                type TokenStream<'a> = GateTokenStream<'a, T::TokenStream<'a>>;
+
+                fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+                    if self.is_japanese(text) {
+                        let stream = TTokenizer::token_stream(&self.japanese, text);
+                        GateTokenStream::Japanese(TokenStream::new(stream))
+                    } else {
+                        GateTokenStream::Fallback(self.fallback.token_stream(text))
+                    }
+                }
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use $api::{Token, TokenStream, Tokenizer};
+
+                use crate::{test_tokenizer, LanguageGate};
+
+                /// A tokenizer of this version only, emitting the whole text as one token.
+                #[derive(Clone)]
+                struct Whole;
+
+                struct WholeStream(Option<Token>, Token);
+
+                impl TokenStream for WholeStream {
+                    fn advance(&mut self) -> bool {
+                        match self.0.take() {
+                            Some(token) => {
+                                self.1 = token;
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+
+                    fn token(&self) -> &Token {
+                        &self.1
+                    }
+
+                    fn token_mut(&mut self) -> &mut Token {
+                        &mut self.1
+                    }
+                }
+
+                impl Tokenizer for Whole {
+                    type TokenStream<'a> = WholeStream;
+
+                    fn token_stream<'a>(&'a mut self, text: &'a str) -> WholeStream {
+                        let token = Token {
+                            offset_to: text.len(),
+                            position: 0,
+                            text: text.to_string(),
+                            ..Token::default()
+                        };
+                        WholeStream(Some(token), Token::default())
+                    }
+                }
+
+                fn tokens<T: Tokenizer>(
+                    tokenizer: &mut T,
+                    text: &str,
+                ) -> Vec<(String, usize, usize)> {
+                    let mut stream = tokenizer.token_stream(text);
+                    let mut tokens = vec![];
+                    while let Some(token) = stream.next() {
+                        tokens.push((token.text.clone(), token.offset_from, token.position));
+                    }
+                    tokens
+                }
+
+                #[test]
+                fn tokenizer_api() {
+                    let mut tokenizer = test_tokenizer();
+                    assert_eq!(
+                        tokens(&mut tokenizer, "東京大学"),
+                        vec![("東京".to_string(), 0, 0), ("大学".to_string(), 6, 1)]
+                    );
+
+                    let mut gate = LanguageGate::new(tokenizer, Whole);
+                    assert_eq!(tokens(&mut gate, "東京大学").len(), 2);
+                    assert_eq!(
+                        tokens(&mut gate, "Hello, world"),
+                        vec![("Hello, world".to_string(), 0, 0)]
+                    );
+                }
+            }
+        }
+    };
+    (@impl $api:ident, $tokenizer:ty) => {
+        impl $api::Tokenizer for $tokenizer {
+            type TokenStream<'a> = TokenStream<'a>;
+
+            fn token_stream<'a>(&'a mut self, text: &'a str) -> TokenStream<'a> {
+                TokenStream::new(TTokenizer::token_stream(self, text))
+            }
+        }
+    };
+}
+
+tokenizer_api!("tantivy-0-20", tantivy_0_20, tantivy_tokenizer_api_0_1);
+tokenizer_api!("tantivy-0-21", tantivy_0_21, tantivy_tokenizer_api_0_2);
+tokenizer_api!("tantivy-0-22", tantivy_0_22, tantivy_tokenizer_api_0_3);
+tokenizer_api!("tantivy-0-24", tantivy_0_24, tantivy_tokenizer_api_0_5);
+tokenizer_api!("tantivy-0-25", tantivy_0_25, tantivy_tokenizer_api_0_6);
+tokenizer_api!("tantivy-0-26", tantivy_0_26, tantivy_tokenizer_api_0_7);
@@ -0,0 +1,384 @@
+//! Analyzer configurations read from configuration files, with the `serde` feature.
+//!
+//! A [`VibratoAnalyzerConfig`] describes the dictionary, the options and the filters of a
+//! [`VibratoTokenizer`], so that the indexer and the searcher build identical analyzers from
+//! the same file. It can be deserialized from any format supported by serde, e.g. TOML:
+//!
+//! ```toml
+//! dictionary = "/path/to/system.dic"
+//! user_lexicon = "/path/to/user.csv"
+//! mode = "search"
+//!
+//! [[char_filters]]
+//! type = "normalize"
+//!
+//! [[filters]]
+//! type = "base_form"
+//!
+//! [[filters]]
+//! type = "pos"
+//!
+//! [[filters]]
+//! type = "stop_words"
+//! words = ["の", "に", "は"]
+//! ```
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tantivy::Index;
+
+use crate::char_filter::{EnclosedCharFilter, MarkupCharFilter, NormalizeCharFilter};
+use crate::filter::katakana_stem::KatakanaStemFilter;
+use crate::filter::lower_case::LowerCaseFilter;
+use crate::filter::stop_word::{StopWordFilter, KUROMOJI_STOP_WORDS};
+use crate::filter::width::WidthFilter;
+use crate::filter::{
+    AuxiliaryChainFilter, BaseFormFilter, CompoundVerbFilter, EraDateFilter, JapaneseNumberFilter,
+    KatakanaVariantFilter, LongUnitFilter, MiddleDotFilter, MiddleDotMode, NounPhraseFilter,
+    OkuriganaFilter, OrthographicVariantFilter, PersonNameFilter, PhoneticKeyFilter, PosFilter,
+    ReadingFormFilter, SentenceFinalParticleFilter, ShingleFilter, StopReadingFilter,
+    UnknownWordNgramFilter, VocabularyFilter, VocabularyMode,
+};
+use crate::tokenizer::Result;
+use crate::{PositionMode, SearchMode, VibratoTokenizer};
+
+/// Configuration of a [`VibratoTokenizer`] and its filters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VibratoAnalyzerConfig {
+    /// Path to the Vibrato dictionary file.
+    pub dictionary: PathBuf,
+    /// Path to a user lexicon in MeCab CSV format, see
+    /// [`VibratoTokenizer::user_lexicon`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_lexicon: Option<PathBuf>,
+    /// Segmentation mode.
+    #[serde(default)]
+    pub mode: AnalysisMode,
+    /// Whether the search mode also emits the compounds it splits.
+    #[serde(default)]
+    pub keep_compounds: bool,
+    /// See [`VibratoTokenizer::position_mode`].
+    #[serde(default)]
+    pub position_mode: PositionMode,
+    /// See [`VibratoTokenizerBuilder::ignore_space`](crate::VibratoTokenizerBuilder::ignore_space).
+    #[serde(default)]
+    pub ignore_space: bool,
+    /// See
+    /// [`VibratoTokenizerBuilder::max_grouping_len`](crate::VibratoTokenizerBuilder::max_grouping_len).
+    #[serde(default)]
+    pub max_grouping_len: usize,
+    /// Char filters, applied in order.
+    #[serde(default)]
+    pub char_filters: Vec<CharFilterConfig>,
+    /// Filters, applied in order.
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+}
+
+/// Segmentation mode of a [`VibratoAnalyzerConfig`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisMode {
+    /// The segmentation of Vibrato.
+    #[default]
+    Normal,
+    /// Long compound nouns are split, see [`VibratoTokenizer::search_mode`].
+    Search,
+}
+
+/// A char filter of a [`VibratoAnalyzerConfig`], tagged by its `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CharFilterConfig {
+    /// [`NormalizeCharFilter`].
+    Normalize {
+        #[serde(default)]
+        width_only: bool,
+    },
+    /// [`EnclosedCharFilter`].
+    Enclosed,
+    /// [`MarkupCharFilter`].
+    Markup {
+        #[serde(default)]
+        markdown: bool,
+        #[serde(default)]
+        keep_ruby: bool,
+    },
+}
+
+/// A filter of a [`VibratoAnalyzerConfig`], tagged by its `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FilterConfig {
+    /// [`BaseFormFilter`].
+    BaseForm {
+        #[serde(default)]
+        lemma: bool,
+    },
+    /// [`PosFilter`], with the stop tags of the dictionary by default.
+    Pos {
+        #[serde(default)]
+        tags: Option<Vec<String>>,
+    },
+    /// Removes stop words, Lucene's Japanese stop words by default.
+    StopWords {
+        #[serde(default)]
+        words: Option<Vec<String>>,
+    },
+    /// [`StopReadingFilter`].
+    StopReadings {
+        words: Vec<String>,
+        #[serde(default)]
+        pos_tags: Vec<String>,
+    },
+    /// [`VocabularyFilter`].
+    Vocabulary {
+        words: Vec<String>,
+        mode: VocabularyMode,
+    },
+    /// Lowercases the tokens.
+    LowerCase,
+    /// Folds full-width ASCII and half-width katakana.
+    Width,
+    /// Removes the trailing long sound mark of katakana words of `min_len` characters or more.
+    KatakanaStem {
+        #[serde(default = "default_katakana_stem_min_len")]
+        min_len: usize,
+    },
+    /// [`ReadingFormFilter`].
+    ReadingForm {
+        #[serde(default)]
+        pronunciation: bool,
+        #[serde(default)]
+        romaji: bool,
+    },
+    /// [`PhoneticKeyFilter`].
+    PhoneticKey {
+        #[serde(default)]
+        keep_original: bool,
+    },
+    /// [`ShingleFilter`].
+    Shingle {
+        #[serde(default)]
+        min_size: Option<usize>,
+        #[serde(default)]
+        max_size: Option<usize>,
+        #[serde(default)]
+        separator: Option<String>,
+        #[serde(default)]
+        output_unigrams: Option<bool>,
+    },
+    /// [`MiddleDotFilter`].
+    MiddleDot { mode: MiddleDotMode },
+    /// [`OrthographicVariantFilter`].
+    OrthographicVariant {
+        #[serde(default)]
+        lemma: bool,
+    },
+    /// [`SentenceFinalParticleFilter`].
+    SentenceFinalParticle {
+        #[serde(default)]
+        interjections: bool,
+    },
+    /// [`LongUnitFilter`].
+    LongUnit,
+    /// [`KatakanaVariantFilter`].
+    KatakanaVariant,
+    /// [`OkuriganaFilter`].
+    Okurigana,
+    /// [`CompoundVerbFilter`].
+    CompoundVerb,
+    /// [`AuxiliaryChainFilter`].
+    AuxiliaryChain,
+    /// [`EraDateFilter`].
+    EraDate,
+    /// [`PersonNameFilter`].
+    PersonName,
+    /// [`NounPhraseFilter`].
+    NounPhrase,
+    /// [`JapaneseNumberFilter`].
+    Number,
+    /// [`UnknownWordNgramFilter`].
+    UnknownWordNgram {
+        #[serde(default)]
+        size: Option<usize>,
+    },
+}
+
+fn default_katakana_stem_min_len() -> usize {
+    4
+}
+
+impl VibratoAnalyzerConfig {
+    /// Build the tokenizer described by the configuration.
+    pub fn build(&self) -> Result<VibratoTokenizer> {
+        let mut tokenizer = VibratoTokenizer::builder()
+            .ignore_space(self.ignore_space)
+            .max_grouping_len(self.max_grouping_len)
+            .build(&self.dictionary)?;
+        if let Some(path) = &self.user_lexicon {
+            tokenizer = tokenizer.user_lexicon(fs::File::open(path)?)?;
+        }
+        tokenizer = tokenizer.position_mode(self.position_mode);
+        if self.mode == AnalysisMode::Search {
+            tokenizer = tokenizer.search_mode(SearchMode::new().keep_compound(self.keep_compounds));
+        }
+        for filter in &self.char_filters {
+            tokenizer = filter.apply(tokenizer);
+        }
+        for filter in &self.filters {
+            tokenizer = filter.apply(tokenizer);
+        }
+        Ok(tokenizer)
+    }
+
+    /// Build the tokenizer and register it in the tokenizers of `index` under `name`.
+    pub fn register(&self, index: &Index, name: &str) -> Result<()> {
+        index.tokenizers().register(name, self.build()?);
+        Ok(())
+    }
+}
+
+impl CharFilterConfig {
+    fn apply(&self, tokenizer: VibratoTokenizer) -> VibratoTokenizer {
+        match self {
+            CharFilterConfig::Normalize { width_only } => {
+                tokenizer.char_filter(NormalizeCharFilter::new().width_only(*width_only))
+            }
+            CharFilterConfig::Enclosed => tokenizer.char_filter(EnclosedCharFilter::new()),
+            CharFilterConfig::Markup {
+                markdown,
+                keep_ruby,
+            } => tokenizer.char_filter(
+                MarkupCharFilter::new()
+                    .markdown(*markdown)
+                    .keep_ruby(*keep_ruby),
+            ),
+        }
+    }
+}
+
+impl FilterConfig {
+    fn apply(&self, tokenizer: VibratoTokenizer) -> VibratoTokenizer {
+        match self {
+            FilterConfig::BaseForm { lemma } => {
+                tokenizer.filter(BaseFormFilter::new().lemma(*lemma))
+            }
+            FilterConfig::Pos { tags: Some(tags) } => tokenizer.filter(PosFilter::new(tags)),
+            FilterConfig::Pos { tags: None } => {
+                let kind = tokenizer.dictionary_kind();
+                tokenizer.filter(PosFilter::for_dictionary(kind))
+            }
+            FilterConfig::StopWords { words: Some(words) } => {
+                tokenizer.filter(StopWordFilter::new(words))
+            }
+            FilterConfig::StopWords { words: None } => {
+                tokenizer.filter(StopWordFilter::new(KUROMOJI_STOP_WORDS.iter().copied()))
+            }
+            FilterConfig::StopReadings { words, pos_tags } => {
+                tokenizer.filter(StopReadingFilter::new(words).pos_tags(pos_tags))
+            }
+            FilterConfig::Vocabulary { words, mode } => {
+                tokenizer.filter(VocabularyFilter::new(words, *mode))
+            }
+            FilterConfig::LowerCase => tokenizer.filter(LowerCaseFilter),
+            FilterConfig::Width => tokenizer.filter(WidthFilter),
+            FilterConfig::KatakanaStem { min_len } => {
+                tokenizer.filter(KatakanaStemFilter::new(*min_len))
+            }
+            FilterConfig::ReadingForm {
+                pronunciation,
+                romaji,
+            } => tokenizer.filter(
+                ReadingFormFilter::new()
+                    .pronunciation(*pronunciation)
+                    .romaji(*romaji),
+            ),
+            FilterConfig::PhoneticKey { keep_original } => {
+                tokenizer.filter(PhoneticKeyFilter::new().keep_original(*keep_original))
+            }
+            FilterConfig::Shingle {
+                min_size,
+                max_size,
+                separator,
+                output_unigrams,
+            } => {
+                let mut filter = ShingleFilter::new();
+                if let Some(min_size) = min_size {
+                    filter = filter.min_size(*min_size);
+                }
+                if let Some(max_size) = max_size {
+                    filter = filter.max_size(*max_size);
+                }
+                if let Some(separator) = separator {
+                    filter = filter.separator(separator.as_str());
+                }
+                if let Some(output_unigrams) = output_unigrams {
+                    filter = filter.output_unigrams(*output_unigrams);
+                }
+                tokenizer.filter(filter)
+            }
+            FilterConfig::MiddleDot { mode } => tokenizer.filter(MiddleDotFilter::new(*mode)),
+            FilterConfig::OrthographicVariant { lemma } => {
+                tokenizer.filter(OrthographicVariantFilter::new().lemma(*lemma))
+            }
+            FilterConfig::SentenceFinalParticle { interjections } => {
+                tokenizer.filter(SentenceFinalParticleFilter::new().interjections(*interjections))
+            }
+            FilterConfig::LongUnit => tokenizer.filter(LongUnitFilter::new()),
+            FilterConfig::KatakanaVariant => tokenizer.filter(KatakanaVariantFilter::new()),
+            FilterConfig::Okurigana => tokenizer.filter(OkuriganaFilter::new()),
+            FilterConfig::CompoundVerb => tokenizer.filter(CompoundVerbFilter::new()),
+            FilterConfig::AuxiliaryChain => tokenizer.filter(AuxiliaryChainFilter::new()),
+            FilterConfig::EraDate => tokenizer.filter(EraDateFilter::new()),
+            FilterConfig::PersonName => tokenizer.filter(PersonNameFilter::new()),
+            FilterConfig::NounPhrase => tokenizer.filter(NounPhraseFilter::new()),
+            FilterConfig::Number => tokenizer.filter(JapaneseNumberFilter::new()),
+            FilterConfig::UnknownWordNgram { size } => {
+                let mut filter = UnknownWordNgramFilter::new();
+                if let Some(size) = size {
+                    filter = filter.size(*size);
+                }
+                tokenizer.filter(filter)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn build() {
+        let config: VibratoAnalyzerConfig = serde_json::from_str(
+            r#"{
+                "dictionary": "./system.dic",
+                "char_filters": [{"type": "normalize"}],
+                "filters": [
+                    {"type": "base_form"},
+                    {"type": "pos"},
+                    {"type": "stop_words"},
+                    {"type": "lower_case"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tokenizer = config.build().unwrap();
+        let recommended = test_tokenizer().recommended();
+        assert_eq!(tokenizer.fingerprint(), recommended.fingerprint());
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<VibratoAnalyzerConfig>(&json).unwrap(),
+            config
+        );
+        assert!(serde_json::from_str::<VibratoAnalyzerConfig>(
+            r#"{"dictionary": "./system.dic", "filters": [{"type": "unknown"}]}"#
+        )
+        .is_err());
+    }
+}
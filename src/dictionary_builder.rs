@@ -0,0 +1,171 @@
+use std::fs;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use vibrato::dictionary::SystemDictionaryBuilder;
+use vibrato::Dictionary;
+
+use crate::tokenizer::Result;
+use crate::VibratoTokenizer;
+
+/// File name of the compiled dictionary cached next to the sources.
+const CACHE_FILE_NAME: &str = "system.dic";
+
+/// Compiles a Vibrato dictionary from MeCab source files, e.g. a checkout of UniDic, instead of
+/// running Vibrato's `compile` tool beforehand.
+///
+/// The sources are the lexicon `lex.csv`, the connection costs `matrix.def`, the character
+/// categories `char.def` and the unknown word definitions `unk.def`, in the source directory.
+/// Compiling takes a while for large dictionaries, so the compiled dictionary can be cached in
+/// the directory; it is reused as long as it is newer than all the sources.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy_vibrato::DictionaryBuilder;
+///
+/// let tokenizer = DictionaryBuilder::new("/path/to/unidic")
+///     .cache(true)
+///     .tokenizer()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DictionaryBuilder {
+    dir: PathBuf,
+    lexicon: PathBuf,
+    cache_path: Option<PathBuf>,
+}
+
+impl DictionaryBuilder {
+    /// Create a builder compiling the sources in `source_dir`, without caching.
+    pub fn new<P: AsRef<Path>>(source_dir: P) -> DictionaryBuilder {
+        let dir = source_dir.as_ref().to_path_buf();
+        DictionaryBuilder {
+            lexicon: dir.join("lex.csv"),
+            dir,
+            cache_path: None,
+        }
+    }
+
+    /// Use the lexicon file at `path` instead of `lex.csv` in the source directory.
+    pub fn lexicon<P: AsRef<Path>>(mut self, path: P) -> DictionaryBuilder {
+        self.lexicon = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// Whether to cache the compiled dictionary as `system.dic` in the source directory.
+    /// Disabled by default.
+    pub fn cache(mut self, cache: bool) -> DictionaryBuilder {
+        self.cache_path = cache.then(|| self.dir.join(CACHE_FILE_NAME));
+        self
+    }
+
+    /// Cache the compiled dictionary at `path`.
+    pub fn cache_path<P: AsRef<Path>>(mut self, path: P) -> DictionaryBuilder {
+        self.cache_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    fn sources(&self) -> [PathBuf; 4] {
+        [
+            self.lexicon.clone(),
+            self.dir.join("matrix.def"),
+            self.dir.join("char.def"),
+            self.dir.join("unk.def"),
+        ]
+    }
+
+    /// Returns the cache file if it exists and is newer than all the sources.
+    fn fresh_cache(&self) -> Result<Option<&Path>> {
+        let Some(cache_path) = self.cache_path.as_deref() else {
+            return Ok(None);
+        };
+        let Ok(cached) = fs::metadata(cache_path).and_then(|m| m.modified()) else {
+            return Ok(None);
+        };
+        let mut newest = SystemTime::UNIX_EPOCH;
+        for source in self.sources() {
+            newest = newest.max(fs::metadata(source)?.modified()?);
+        }
+        Ok((cached >= newest).then_some(cache_path))
+    }
+
+    /// Compile the dictionary, or read it from the cache if it is up to date.
+    pub fn build(&self) -> Result<Dictionary> {
+        if let Some(cache_path) = self.fresh_cache()? {
+            return Ok(Dictionary::read(BufReader::new(fs::File::open(
+                cache_path,
+            )?))?);
+        }
+        let [lexicon, matrix, char_def, unk_def] = self.sources();
+        let dict = SystemDictionaryBuilder::from_readers(
+            BufReader::new(fs::File::open(lexicon)?),
+            BufReader::new(fs::File::open(matrix)?),
+            BufReader::new(fs::File::open(char_def)?),
+            BufReader::new(fs::File::open(unk_def)?),
+        )?;
+        if let Some(cache_path) = &self.cache_path {
+            // Write to a temporary file first, so that a failure never leaves a truncated
+            // dictionary to be read next time.
+            let tmp_path = cache_path.with_extension("dic.tmp");
+            let mut writer = BufWriter::new(fs::File::create(&tmp_path)?);
+            dict.write(&mut writer)?;
+            writer.flush()?;
+            drop(writer);
+            fs::rename(&tmp_path, cache_path)?;
+        }
+        Ok(dict)
+    }
+
+    /// Build a tokenizer with the compiled dictionary, see [`build`](Self::build).
+    pub fn tokenizer(&self) -> Result<VibratoTokenizer> {
+        VibratoTokenizer::from_dictionary(self.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let dir = std::env::temp_dir().join("tantivy-vibrato-dictionary-builder");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("lex.csv"),
+            "東京,1,1,2000,名詞,固有名詞,地域,*,*,*,東京,トウキョウ,トーキョー\n\
+             大学,1,1,2000,名詞,一般,*,*,*,*,大学,ダイガク,ダイガク\n",
+        )
+        .unwrap();
+        fs::write(dir.join("matrix.def"), "2 2\n0 0 0\n0 1 0\n1 0 0\n1 1 0\n").unwrap();
+        fs::write(dir.join("char.def"), "DEFAULT 0 1 0\n").unwrap();
+        fs::write(
+            dir.join("unk.def"),
+            "DEFAULT,1,1,20000,記号,一般,*,*,*,*,*\n",
+        )
+        .unwrap();
+        let cache_path = dir.join(CACHE_FILE_NAME);
+        let _ = fs::remove_file(&cache_path);
+
+        let builder = DictionaryBuilder::new(&dir).cache(true);
+        let tokenizer = builder.tokenizer().unwrap();
+        let texts = |tokenizer: &VibratoTokenizer| {
+            tokenizer
+                .morphemes("東京大学")
+                .into_iter()
+                .map(|t| t.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&tokenizer), vec!["東京", "大学"]);
+        assert!(builder.fresh_cache().unwrap().is_some());
+
+        let cached = builder.tokenizer().unwrap();
+        assert_eq!(cached.fingerprint(), tokenizer.fingerprint());
+        assert_eq!(
+            VibratoTokenizer::new(&cache_path).unwrap().fingerprint(),
+            tokenizer.fingerprint()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
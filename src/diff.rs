@@ -0,0 +1,259 @@
+//! Compare the output of two analyzers over the same corpus.
+//!
+//! This is meant to assess the reindexing impact of a dictionary upgrade (or of any other
+//! analyzer configuration change) before rolling it out.
+use std::collections::HashMap;
+use std::fmt;
+
+use tantivy::tokenizer::{TextAnalyzer, Token};
+
+/// A span of text which the two analyzers segment differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentationChange {
+    /// Terms produced by the old analyzer.
+    pub old: Vec<String>,
+    /// Terms produced by the new analyzer.
+    pub new: Vec<String>,
+    /// Number of times this change was seen in the corpus.
+    pub count: usize,
+}
+
+/// Term-level differences between two analyzers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// Number of documents compared.
+    pub documents: usize,
+    /// Number of documents whose tokens differ.
+    pub changed_documents: usize,
+    /// Number of tokens emitted by the old analyzer.
+    pub old_tokens: usize,
+    /// Number of tokens emitted by the new analyzer.
+    pub new_tokens: usize,
+    /// Terms only emitted by the new analyzer with their frequency, most frequent first.
+    pub added_terms: Vec<(String, usize)>,
+    /// Terms only emitted by the old analyzer with their frequency, most frequent first.
+    pub removed_terms: Vec<(String, usize)>,
+    /// Changed segmentations, most frequent first.
+    pub changes: Vec<SegmentationChange>,
+}
+
+impl DiffReport {
+    /// Returns `true` if both analyzers produced identical tokens for every document.
+    pub fn is_empty(&self) -> bool {
+        self.changed_documents == 0
+    }
+}
+
+/// Tokenize every document of `corpus` with both analyzers and report the differences.
+pub fn diff_corpus<I, S>(old: &TextAnalyzer, new: &TextAnalyzer, corpus: I) -> DiffReport
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut report = DiffReport::default();
+    let mut old_terms = HashMap::new();
+    let mut new_terms = HashMap::new();
+    let mut changes: HashMap<(Vec<String>, Vec<String>), usize> = HashMap::new();
+
+    for doc in corpus {
+        let doc = doc.as_ref();
+        let old_tokens = collect_tokens(old, doc);
+        let new_tokens = collect_tokens(new, doc);
+
+        report.documents += 1;
+        report.old_tokens += old_tokens.len();
+        report.new_tokens += new_tokens.len();
+        for token in &old_tokens {
+            *old_terms.entry(token.text.clone()).or_insert(0) += 1;
+        }
+        for token in &new_tokens {
+            *new_terms.entry(token.text.clone()).or_insert(0) += 1;
+        }
+
+        let doc_changes = align(&old_tokens, &new_tokens);
+        if !doc_changes.is_empty() {
+            report.changed_documents += 1;
+        }
+        for change in doc_changes {
+            *changes.entry(change).or_insert(0) += 1;
+        }
+    }
+
+    report.added_terms = only_in(&new_terms, &old_terms);
+    report.removed_terms = only_in(&old_terms, &new_terms);
+    let mut changes = changes
+        .into_iter()
+        .map(|((old, new), count)| SegmentationChange { old, new, count })
+        .collect::<Vec<_>>();
+    changes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.old.cmp(&b.old)));
+    report.changes = changes;
+
+    report
+}
+
+fn collect_tokens(analyzer: &TextAnalyzer, text: &str) -> Vec<Token> {
+    let mut stream = analyzer.token_stream(text);
+    let mut tokens = vec![];
+    while let Some(token) = stream.next() {
+        tokens.push(token.clone());
+    }
+    tokens
+}
+
+fn only_in(a: &HashMap<String, usize>, b: &HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut terms = a
+        .iter()
+        .filter(|(term, _)| !b.contains_key(*term))
+        .map(|(term, count)| (term.clone(), *count))
+        .collect::<Vec<_>>();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    terms
+}
+
+/// Walk both token sequences and group the tokens into regions whose boundaries agree.
+/// Regions that differ are returned as `(old terms, new terms)`.
+fn align(old: &[Token], new: &[Token]) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut changes = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && same_token(&old[i], &new[j]) {
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let mut old_region = vec![];
+        let mut new_region = vec![];
+        let mut old_end = 0;
+        let mut new_end = 0;
+        if i < old.len() {
+            old_end = old[i].offset_to;
+            old_region.push(old[i].text.clone());
+            i += 1;
+        }
+        if j < new.len() {
+            new_end = new[j].offset_to;
+            new_region.push(new[j].text.clone());
+            j += 1;
+        }
+        while old_end != new_end {
+            if (old_end < new_end && i < old.len()) || j >= new.len() {
+                if i >= old.len() {
+                    break;
+                }
+                old_end = old[i].offset_to;
+                old_region.push(old[i].text.clone());
+                i += 1;
+            } else {
+                new_end = new[j].offset_to;
+                new_region.push(new[j].text.clone());
+                j += 1;
+            }
+        }
+
+        if old_region != new_region {
+            changes.push((old_region, new_region));
+        }
+    }
+
+    changes
+}
+
+fn same_token(a: &Token, b: &Token) -> bool {
+    a.offset_from == b.offset_from && a.offset_to == b.offset_to && a.text == b.text
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "documents: {} ({} changed)",
+            self.documents, self.changed_documents
+        )?;
+        writeln!(f, "tokens: {} -> {}", self.old_tokens, self.new_tokens)?;
+        writeln!(f, "added terms: {}", self.added_terms.len())?;
+        for (term, count) in &self.added_terms {
+            writeln!(f, "  + {term}\t{count}")?;
+        }
+        writeln!(f, "removed terms: {}", self.removed_terms.len())?;
+        for (term, count) in &self.removed_terms {
+            writeln!(f, "  - {term}\t{count}")?;
+        }
+        writeln!(f, "changed segmentations: {}", self.changes.len())?;
+        for change in &self.changes {
+            writeln!(
+                f,
+                "  {} => {}\t{}",
+                change.old.join(" "),
+                change.new.join(" "),
+                change.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use tantivy::tokenizer::WhitespaceTokenizer;
+
+    fn token(text: &str, offset_from: usize) -> Token {
+        Token {
+            offset_from,
+            offset_to: offset_from + text.len(),
+            text: text.to_string(),
+            ..Token::default()
+        }
+    }
+
+    #[test]
+    fn align_groups_changed_regions() {
+        let old = [token("a", 0), token("bc", 1), token("d", 3)];
+        let new = [token("a", 0), token("b", 1), token("c", 2), token("d", 3)];
+
+        assert_eq!(
+            align(&old, &new),
+            vec![(vec!["bc".to_string()], vec!["b".to_string(), "c".to_string()])]
+        );
+    }
+
+    #[test]
+    fn identical_analyzers() {
+        let analyzer = TextAnalyzer::from(test_tokenizer());
+        let report = diff_corpus(&analyzer, &analyzer, ["すもももももももものうち", ""]);
+
+        assert!(report.is_empty());
+        assert_eq!(report.documents, 2);
+        assert_eq!(report.old_tokens, 7);
+        assert_eq!(report.new_tokens, 7);
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn different_analyzers() {
+        let old = TextAnalyzer::from(WhitespaceTokenizer);
+        let new = TextAnalyzer::from(test_tokenizer());
+        let report = diff_corpus(&old, &new, ["すもももももももものうち"]);
+
+        assert_eq!(report.changed_documents, 1);
+        assert_eq!(
+            report.removed_terms,
+            vec![("すもももももももものうち".to_string(), 1)]
+        );
+        assert_eq!(
+            report.added_terms,
+            vec![
+                ("も".to_string(), 2),
+                ("もも".to_string(), 2),
+                ("うち".to_string(), 1),
+                ("すもも".to_string(), 1),
+                ("の".to_string(), 1),
+            ]
+        );
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].new.len(), 7);
+    }
+}
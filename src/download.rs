@@ -0,0 +1,280 @@
+//! Downloading prebuilt Vibrato dictionaries into a local cache, with the `download` feature.
+//!
+//! A [`Preset`] is the URL of a dictionary file, or of a tar archive containing it, with the
+//! SHA-256 checksum of the download, e.g. one of the dictionaries released by Vibrato such as
+//! [`Preset::ipadic_mecab_2_7_0`]. [`DictionaryDownloader::fetch`] downloads it once, verifies
+//! the checksum and keeps the dictionary under the cache directory, by default
+//! `$XDG_CACHE_HOME/tantivy-vibrato` or `~/.cache/tantivy-vibrato`:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy_vibrato::download::Preset;
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! let tokenizer = VibratoTokenizer::from_preset(&Preset::ipadic_mecab_2_7_0())?;
+//!
+//! // Another source.
+//! let preset = Preset::new(
+//!     "https://example.com/ipadic-mecab-2_7_0.tar.gz",
+//!     "<sha256 of the archive>",
+//! )
+//! .archive_entry("ipadic-mecab-2_7_0/system.dic");
+//! let tokenizer = VibratoTokenizer::from_preset(&preset)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::tokenizer::Result;
+
+/// Magic number of gzip files.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic number of xz files.
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0];
+
+/// Release of Vibrato whose prebuilt dictionaries are downloaded by the named presets, of the
+/// same version as the `vibrato` dependency so that the dictionary format matches.
+const RELEASE_URL: &str = "https://github.com/daac-tools/vibrato/releases/download/v0.3.3";
+
+/// SHA-256 checksums of the archives of the release, by dictionary name.
+// TODO: pin the checksums of the release assets. They could not be downloaded when the presets
+// were added, so the presets fail with a checksum mismatch showing the actual checksum.
+const RELEASE_CHECKSUMS: [(&str, &str); 4] = [
+    ("ipadic-mecab-2_7_0", ""),
+    ("unidic-cwj-3_1_1", ""),
+    ("unidic-csj-3_1_1", ""),
+    ("unidic-mecab-2_1_2", ""),
+];
+
+/// A prebuilt dictionary to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    url: String,
+    sha256: String,
+    archive_entry: Option<String>,
+}
+
+impl Preset {
+    /// Create a preset downloading the dictionary file at `url`, optionally compressed with
+    /// zstd (see [`VibratoTokenizer::new`](crate::VibratoTokenizer::new)), whose SHA-256 checksum is the hexadecimal
+    /// `sha256`.
+    pub fn new<U: Into<String>, S: Into<String>>(url: U, sha256: S) -> Preset {
+        Preset {
+            url: url.into(),
+            sha256: sha256.into().to_ascii_lowercase(),
+            archive_entry: None,
+        }
+    }
+
+    /// IPADIC 2.7.0, released by Vibrato.
+    pub fn ipadic_mecab_2_7_0() -> Preset {
+        Preset::released("ipadic-mecab-2_7_0")
+    }
+
+    /// UniDic-cwj 3.1.1, the UniDic of contemporary written Japanese, released by Vibrato.
+    pub fn unidic_cwj_3_1_1() -> Preset {
+        Preset::released("unidic-cwj-3_1_1")
+    }
+
+    /// UniDic-csj 3.1.1, the UniDic of spoken Japanese, released by Vibrato.
+    pub fn unidic_csj_3_1_1() -> Preset {
+        Preset::released("unidic-csj-3_1_1")
+    }
+
+    /// UniDic-mecab 2.1.2, the UniDic distributed for MeCab, released by Vibrato.
+    pub fn unidic_mecab_2_1_2() -> Preset {
+        Preset::released("unidic-mecab-2_1_2")
+    }
+
+    /// The archive `name` of the Vibrato release, containing `name/system.dic`.
+    fn released(name: &str) -> Preset {
+        let (_, sha256) = RELEASE_CHECKSUMS
+            .iter()
+            .find(|(released, _)| *released == name)
+            .unwrap();
+        Preset::new(format!("{RELEASE_URL}/{name}.tar.gz"), *sha256)
+            .archive_entry(format!("{name}/system.dic"))
+    }
+
+    /// Extract the dictionary file at `path` of the downloaded tar archive, optionally
+    /// compressed with gzip or xz.
+    pub fn archive_entry<P: Into<String>>(mut self, path: P) -> Preset {
+        self.archive_entry = Some(path.into());
+        self
+    }
+
+    /// Name of the cached dictionary file.
+    fn file_name(&self) -> String {
+        let name = self
+            .archive_entry
+            .as_deref()
+            .unwrap_or(&self.url)
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("system.dic");
+        format!("{}-{name}", &self.sha256)
+    }
+}
+
+/// Downloads [`Preset`]s into a cache directory.
+#[derive(Debug, Clone)]
+pub struct DictionaryDownloader {
+    cache_dir: PathBuf,
+}
+
+impl DictionaryDownloader {
+    /// Create a downloader caching the dictionaries under `$XDG_CACHE_HOME/tantivy-vibrato`,
+    /// or `~/.cache/tantivy-vibrato` if `XDG_CACHE_HOME` is not set.
+    pub fn new() -> DictionaryDownloader {
+        let cache_home = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(env::temp_dir);
+        DictionaryDownloader {
+            cache_dir: cache_home.join("tantivy-vibrato"),
+        }
+    }
+
+    /// Cache the dictionaries under `cache_dir` instead.
+    pub fn cache_dir<P: AsRef<Path>>(mut self, cache_dir: P) -> DictionaryDownloader {
+        self.cache_dir = cache_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Returns the path of the cached dictionary of `preset`, downloading it first if it is
+    /// not cached yet.
+    pub fn fetch(&self, preset: &Preset) -> Result<PathBuf> {
+        let path = self.cache_dir.join(preset.file_name());
+        if path.exists() {
+            return Ok(path);
+        }
+        log::info!("downloading the dictionary {}", preset.url);
+        let response = ureq::get(&preset.url).call().map_err(io::Error::other)?;
+        let mut bytes = vec![];
+        response.into_reader().read_to_end(&mut bytes)?;
+        self.install(preset, &bytes)
+    }
+
+    /// Verifies the downloaded `bytes` and writes the dictionary into the cache.
+    fn install(&self, preset: &Preset, bytes: &[u8]) -> Result<PathBuf> {
+        let checksum = Sha256::digest(bytes)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        if checksum != preset.sha256 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "checksum mismatch of {}: expected {}, got {checksum}",
+                    preset.url, preset.sha256
+                ),
+            )
+            .into());
+        }
+        let dictionary = match &preset.archive_entry {
+            Some(entry) => extract(bytes, entry)?,
+            None => bytes.to_vec(),
+        };
+
+        fs::create_dir_all(&self.cache_dir)?;
+        let path = self.cache_dir.join(preset.file_name());
+        // Write to a temporary file first, so that concurrent processes never read a partial
+        // dictionary.
+        let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::File::create(&tmp_path)?.write_all(&dictionary)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(path)
+    }
+}
+
+impl Default for DictionaryDownloader {
+    fn default() -> DictionaryDownloader {
+        DictionaryDownloader::new()
+    }
+}
+
+/// Returns the contents of the file at `entry` of the tar archive `bytes`.
+fn extract(bytes: &[u8], entry: &str) -> Result<Vec<u8>> {
+    let reader: Box<dyn Read + '_> = if bytes.starts_with(&GZIP_MAGIC) {
+        Box::new(flate2::read::GzDecoder::new(bytes))
+    } else if bytes.starts_with(&XZ_MAGIC) {
+        Box::new(xz2::read::XzDecoder::new(bytes))
+    } else {
+        Box::new(bytes)
+    };
+    let mut archive = tar::Archive::new(reader);
+    for file in archive.entries()? {
+        let mut file = file?;
+        if file.path()?.as_ref() == Path::new(entry) {
+            let mut contents = vec![];
+            file.read_to_end(&mut contents)?;
+            return Ok(contents);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{entry} not found in the archive"),
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::VibratoTokenizer;
+
+    #[test]
+    fn install() {
+        let dictionary = fs::read("./system.dic").unwrap();
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+            vec![],
+            flate2::Compression::fast(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(dictionary.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, "dict/system.dic", dictionary.as_slice())
+            .unwrap();
+        let bytes = archive.into_inner().unwrap().finish().unwrap();
+        let checksum = Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+
+        let cache_dir = env::temp_dir().join("tantivy-vibrato-download");
+        let downloader = DictionaryDownloader::new().cache_dir(&cache_dir);
+        let preset = Preset::new("https://example.com/dict.tar.gz", &checksum)
+            .archive_entry("dict/system.dic");
+        let path = downloader.install(&preset, &bytes).unwrap();
+        assert_eq!(downloader.fetch(&preset).unwrap(), path);
+        assert_eq!(
+            VibratoTokenizer::new(&path).unwrap().fingerprint(),
+            test_tokenizer().fingerprint()
+        );
+
+        let released = Preset::unidic_cwj_3_1_1();
+        assert_eq!(
+            released.url,
+            "https://github.com/daac-tools/vibrato/releases/download/v0.3.3/unidic-cwj-3_1_1.tar.gz"
+        );
+        assert_eq!(
+            released.archive_entry.as_deref(),
+            Some("unidic-cwj-3_1_1/system.dic")
+        );
+
+        let corrupted = Preset::new("https://example.com/dict.dic", "00");
+        assert!(downloader.install(&corrupted, &dictionary).is_err());
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+use tantivy::tokenizer::{PreTokenizedString, Token as TToken};
+
+use crate::token::MorphToken;
+
+/// Tokens of a text for the main field and for a field of its proper nouns, returned by
+/// [`VibratoTokenizer::entity_tokens`](crate::VibratoTokenizer::entity_tokens).
+///
+/// Indexing the proper nouns in a dedicated field and boosting it at query time, e.g. with a
+/// `QueryParser` searching both fields and `set_field_boost`, makes a document matching an
+/// entity of the query rank above one matching a common word spelled the same way:
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::schema::{Schema, Value, TEXT};
+/// use tantivy::Document;
+/// use tantivy_vibrato::VibratoTokenizer;
+///
+/// let mut schema = Schema::builder();
+/// let body = schema.add_text_field("body", TEXT);
+/// let entities = schema.add_text_field("entities", TEXT);
+///
+/// let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+/// let tokens = tokenizer.entity_tokens("東京大学に行く");
+/// let mut doc = Document::new();
+/// doc.add_field_value(body, Value::PreTokStr(tokens.text));
+/// doc.add_field_value(entities, Value::PreTokStr(tokens.entities));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityTokens {
+    /// Every token, as emitted by the token stream.
+    pub text: PreTokenizedString,
+    /// The tokens of the proper nouns, with the same offsets and positions.
+    pub entities: PreTokenizedString,
+}
+
+impl EntityTokens {
+    pub(crate) fn new(text: &str, tagged: Vec<(TToken, bool)>) -> EntityTokens {
+        let entities = tagged
+            .iter()
+            .filter(|(_, entity)| *entity)
+            .map(|(token, _)| token.clone())
+            .collect();
+        EntityTokens {
+            text: PreTokenizedString {
+                text: text.to_string(),
+                tokens: tagged.into_iter().map(|(token, _)| token).collect(),
+            },
+            entities: PreTokenizedString {
+                text: text.to_string(),
+                tokens: entities,
+            },
+        }
+    }
+}
+
+/// Returns `true` if `token` is tagged `名詞,固有名詞`.
+pub(crate) fn is_proper_noun(token: &MorphToken) -> bool {
+    token.features().pos().starts_with(&["名詞", "固有名詞"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::{Gazetteer, PositionMode};
+
+    #[test]
+    fn entities() {
+        let gazetteer = Gazetteer::new().entity("ChatGPT");
+        let tokenizer = test_tokenizer()
+            .gazetteer(&gazetteer)
+            .unwrap()
+            .position_mode(PositionMode::CharOffset);
+        let tokens = tokenizer.entity_tokens("東京大学でChatGPT");
+        let texts = |s: &PreTokenizedString| {
+            s.tokens
+                .iter()
+                .map(|t| (t.text.clone(), t.position))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokens.text.tokens.len(), 4);
+        assert_eq!(
+            texts(&tokens.entities),
+            vec![("東京".to_string(), 0), ("ChatGPT".to_string(), 5)]
+        );
+        assert_eq!(tokens.entities.text, "東京大学でChatGPT");
+    }
+}
@@ -0,0 +1,74 @@
+use tantivy::tokenizer::Token as TToken;
+
+/// What the tokenizer emits when the analysis of a text fails, set with
+/// [`VibratoTokenizer::error_policy`](crate::VibratoTokenizer::error_policy).
+///
+/// Vibrato does not report errors while tokenizing, so the failures are the panics raised
+/// during the analysis, by Vibrato or by a char filter, filter or recognizer.
+/// Panics cannot be caught where they abort, e.g. on `wasm32-unknown-unknown`, so the policies
+/// have no effect there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Propagate the panic, failing the indexing of the document. The default.
+    #[default]
+    Strict,
+    /// Log the error and index the character bigrams of the text instead, so that its content
+    /// stays searchable.
+    FallbackNgram,
+    /// Log the error and emit no tokens, dropping the content of the text from the index.
+    Skip,
+}
+
+/// Returns the character bigrams of the runs of alphanumeric characters of `text`, or the
+/// character alone for runs of one character.
+pub(crate) fn bigrams(text: &str, sequential: bool) -> Vec<TToken> {
+    let chars = text.char_indices().enumerate().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    for run in chars.split(|(_, (_, c))| !c.is_alphanumeric()) {
+        let grams = if run.len() == 1 {
+            run.windows(1)
+        } else {
+            run.windows(2)
+        };
+        for gram in grams {
+            let (char_from, (from, _)) = gram[0];
+            let (_, (last, c)) = gram[gram.len() - 1];
+            let to = last + c.len_utf8();
+            tokens.push(TToken {
+                offset_from: from,
+                offset_to: to,
+                position: if sequential { tokens.len() } else { char_from },
+                position_length: if sequential { 1 } else { gram.len() },
+                text: text[from..to].to_string(),
+            });
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_bigrams() {
+        let tokens = bigrams("東京都 に AB", false)
+            .into_iter()
+            .map(|t| (t.text, t.offset_from, t.position, t.position_length))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                ("東京".to_string(), 0, 0, 2),
+                ("京都".to_string(), 3, 1, 2),
+                ("に".to_string(), 10, 4, 1),
+                ("AB".to_string(), 14, 6, 2),
+            ]
+        );
+        let positions = bigrams("東京都 に AB", true)
+            .into_iter()
+            .map(|t| t.position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![0, 1, 2, 3]);
+    }
+}
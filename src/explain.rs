@@ -0,0 +1,113 @@
+use tantivy::tokenizer::Token as TToken;
+
+use crate::char_filter::OffsetMap;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Structured trace of the analysis of a text, returned by
+/// [`VibratoTokenizer::explain`](crate::VibratoTokenizer::explain).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// The input text.
+    pub text: String,
+    /// Char filters in the order they were applied.
+    pub char_filters: Vec<CharFilterStep>,
+    /// Morphemes produced by Vibrato, before any filter is applied.
+    pub morphemes: Vec<MorphToken>,
+    /// Filters in the order they were applied.
+    pub filters: Vec<FilterStep>,
+    /// Tokens finally emitted to tantivy.
+    pub tokens: Vec<TToken>,
+}
+
+/// Result of a char filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharFilterStep {
+    /// Name of the filter.
+    pub name: &'static str,
+    /// Text after the filter is applied.
+    pub text: String,
+    /// Mapping of offsets in `text` to offsets in the text before the filter.
+    pub offsets: OffsetMap,
+}
+
+/// Result of a morpheme filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterStep {
+    /// Name of the filter.
+    pub name: &'static str,
+    /// What happened to each token.
+    pub effects: Vec<TokenEffect>,
+}
+
+/// What a filter did to a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenEffect {
+    /// The token passed through unchanged.
+    Kept(MorphToken),
+    /// The token was removed.
+    Dropped {
+        /// The removed token.
+        token: MorphToken,
+        /// Why it was removed, if the filter can tell.
+        reason: Option<String>,
+    },
+    /// The token was rewritten.
+    Rewritten {
+        /// The token before the filter.
+        from: MorphToken,
+        /// The token after the filter.
+        to: MorphToken,
+        /// Why it was rewritten, if the filter can tell.
+        reason: Option<String>,
+    },
+    /// The token was added by the filter.
+    Added(MorphToken),
+}
+
+impl FilterStep {
+    /// Compare the tokens before and after `filter` was applied.
+    ///
+    /// Tokens are matched by their character span, in order.
+    pub(crate) fn new(
+        filter: &dyn MorphFilter,
+        before: &[MorphToken],
+        after: &[MorphToken],
+    ) -> FilterStep {
+        let mut matched = vec![false; before.len()];
+        let mut effects = vec![];
+
+        for token in after {
+            let found = before.iter().enumerate().position(|(i, b)| {
+                !matched[i] && b.char_from == token.char_from && b.char_to == token.char_to
+            });
+            match found {
+                Some(i) => {
+                    matched[i] = true;
+                    let from = &before[i];
+                    if from == token {
+                        effects.push(TokenEffect::Kept(token.clone()));
+                    } else {
+                        effects.push(TokenEffect::Rewritten {
+                            from: from.clone(),
+                            to: token.clone(),
+                            reason: filter.reason(from),
+                        });
+                    }
+                }
+                None => effects.push(TokenEffect::Added(token.clone())),
+            }
+        }
+        for (token, _) in before.iter().zip(matched).filter(|(_, m)| !m) {
+            effects.push(TokenEffect::Dropped {
+                token: token.clone(),
+                reason: filter.reason(token),
+            });
+        }
+
+        FilterStep {
+            name: filter.name(),
+            effects,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Access to the fields of dictionary feature strings.
+//!
+//! The layout of the feature string depends on the dictionary the Vibrato dictionary was
+//! compiled from. IPADIC and UniDic are supported.
+
+/// Layout of the feature strings of a dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DictionaryKind {
+    /// IPADIC (and compatible dictionaries such as NEologd).
+    ///
+    /// `品詞,品詞細分類1,品詞細分類2,品詞細分類3,活用型,活用形,原形,読み,発音`
+    Ipadic,
+    /// UniDic.
+    ///
+    /// `pos1,pos2,pos3,pos4,cType,cForm,lForm,lemma,orth,pron,orthBase,pronBase,...`
+    Unidic,
+}
+
+impl DictionaryKind {
+    /// Guess the layout from the feature string of a system dictionary entry.
+    pub fn detect(feature: &str) -> DictionaryKind {
+        if feature.split(',').count() >= 13 {
+            DictionaryKind::Unidic
+        } else {
+            DictionaryKind::Ipadic
+        }
+    }
+}
+
+/// Parsed feature string of a dictionary entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Features<'a> {
+    kind: DictionaryKind,
+    fields: Vec<&'a str>,
+}
+
+impl<'a> Features<'a> {
+    /// Parse `feature` with the layout of `kind`.
+    pub fn parse(feature: &'a str, kind: DictionaryKind) -> Features<'a> {
+        Features {
+            kind,
+            fields: feature.split(',').collect(),
+        }
+    }
+
+    /// Layout of the feature string.
+    pub fn kind(&self) -> DictionaryKind {
+        self.kind
+    }
+
+    /// Returns the `i`-th field, or `None` if it is missing or `*`.
+    pub fn field(&self, i: usize) -> Option<&'a str> {
+        self.fields.get(i).copied().filter(|f| !f.is_empty() && *f != "*")
+    }
+
+    /// Part-of-speech hierarchy, e.g. `["名詞", "固有名詞", "地名"]`.
+    pub fn pos(&self) -> Vec<&'a str> {
+        (0..4).map_while(|i| self.field(i)).collect()
+    }
+
+    /// Part-of-speech hierarchy joined with `-`, e.g. `名詞-固有名詞-地名`.
+    pub fn pos_tag(&self) -> String {
+        self.pos().join("-")
+    }
+
+    /// Conjugation type (活用型).
+    pub fn conjugation_type(&self) -> Option<&'a str> {
+        self.field(4)
+    }
+
+    /// Conjugation form (活用形).
+    pub fn conjugation_form(&self) -> Option<&'a str> {
+        self.field(5)
+    }
+
+    /// Base (dictionary) form in the orthography of the surface, e.g. `食べる` for `食べ`.
+    pub fn base_form(&self) -> Option<&'a str> {
+        match self.kind {
+            DictionaryKind::Ipadic => self.field(6),
+            DictionaryKind::Unidic => self.field(10),
+        }
+    }
+
+    /// Lemma. For UniDic this also normalizes the orthography, e.g. `引っ越す` for `引越す`.
+    pub fn lemma(&self) -> Option<&'a str> {
+        match self.kind {
+            DictionaryKind::Ipadic => self.field(6),
+            DictionaryKind::Unidic => self.field(7),
+        }
+    }
+
+    /// Reading in katakana.
+    ///
+    /// This is the reading of the entry selected on the analyzed path, so that homographs are
+    /// read according to their context, e.g. `人` as `ヒト` alone and `ジン` in `東京人`. For
+    /// UniDic, it is the reading of the inflected surface (`kana`), not of the lemma.
+    pub fn reading(&self) -> Option<&'a str> {
+        match self.kind {
+            DictionaryKind::Ipadic => self.field(7),
+            DictionaryKind::Unidic => self.field(20).or_else(|| self.field(9)),
+        }
+    }
+
+    /// Pronunciation in katakana.
+    pub fn pronunciation(&self) -> Option<&'a str> {
+        match self.kind {
+            DictionaryKind::Ipadic => self.field(8),
+            DictionaryKind::Unidic => self.field(9),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPADIC: &str = "動詞,自立,*,*,一段,連用形,食べる,タベ,タベ";
+    const UNIDIC: &str =
+        "動詞,一般,*,*,下一段-バ行,連用形-一般,タベル,食べる,食べ,タベ,食べる,タベル,和,*,*,*,*";
+
+    #[test]
+    fn detect() {
+        assert_eq!(DictionaryKind::detect(IPADIC), DictionaryKind::Ipadic);
+        assert_eq!(DictionaryKind::detect(UNIDIC), DictionaryKind::Unidic);
+    }
+
+    #[test]
+    fn ipadic() {
+        let features = Features::parse(IPADIC, DictionaryKind::Ipadic);
+        assert_eq!(features.pos(), vec!["動詞", "自立"]);
+        assert_eq!(features.pos_tag(), "動詞-自立");
+        assert_eq!(features.conjugation_type(), Some("一段"));
+        assert_eq!(features.conjugation_form(), Some("連用形"));
+        assert_eq!(features.base_form(), Some("食べる"));
+        assert_eq!(features.lemma(), Some("食べる"));
+        assert_eq!(features.reading(), Some("タベ"));
+        assert_eq!(features.pronunciation(), Some("タベ"));
+    }
+
+    #[test]
+    fn unidic() {
+        let features = Features::parse(UNIDIC, DictionaryKind::Unidic);
+        assert_eq!(features.pos_tag(), "動詞-一般");
+        assert_eq!(features.conjugation_type(), Some("下一段-バ行"));
+        assert_eq!(features.base_form(), Some("食べる"));
+        assert_eq!(features.lemma(), Some("食べる"));
+        assert_eq!(features.reading(), Some("タベ"));
+    }
+
+    #[test]
+    fn unknown_word() {
+        let features = Features::parse("名詞,一般,*,*,*,*,*", DictionaryKind::Ipadic);
+        assert_eq!(features.pos_tag(), "名詞-一般");
+        assert_eq!(features.base_form(), None);
+        assert_eq!(features.reading(), None);
+    }
+}
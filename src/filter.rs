@@ -0,0 +1,82 @@
+//! Filters applied to the morphemes produced by Vibrato.
+//!
+//! tantivy's `Token` only keeps the term text, so filters that need dictionary features
+//! (part of speech, base form, reading, ...) cannot be written as tantivy `TokenFilter`s.
+//! They implement [`MorphFilter`] instead and are attached to the tokenizer with
+//! [`VibratoTokenizer::filter`](crate::VibratoTokenizer::filter).
+mod auxiliary_chain;
+pub(crate) mod base_form;
+mod compound_verb;
+mod conversational;
+mod era;
+pub(crate) mod katakana_stem;
+mod katakana_variant;
+pub(crate) mod lower_case;
+pub(crate) mod long_unit;
+mod middle_dot;
+mod noun_phrase;
+mod number;
+mod okurigana;
+mod orthography;
+mod person_name;
+mod phonetic;
+pub(crate) mod pos;
+pub(crate) mod predicate;
+mod reading_form;
+mod romaji;
+mod shingle;
+mod stop_reading;
+pub(crate) mod stop_word;
+mod synonym;
+mod unknown_ngram;
+mod vocabulary;
+pub(crate) mod width;
+
+pub use auxiliary_chain::AuxiliaryChainFilter;
+pub use base_form::BaseFormFilter;
+pub use compound_verb::CompoundVerbFilter;
+pub use conversational::SentenceFinalParticleFilter;
+pub use era::EraDateFilter;
+pub use katakana_stem::KatakanaStemFilter;
+pub use katakana_variant::KatakanaVariantFilter;
+pub use long_unit::LongUnitFilter;
+pub use middle_dot::{MiddleDotFilter, MiddleDotMode};
+pub use noun_phrase::NounPhraseFilter;
+pub use number::JapaneseNumberFilter;
+pub use okurigana::OkuriganaFilter;
+pub use orthography::OrthographicVariantFilter;
+pub use person_name::PersonNameFilter;
+pub use phonetic::PhoneticKeyFilter;
+pub use pos::PosFilter;
+pub use reading_form::ReadingFormFilter;
+pub use shingle::ShingleFilter;
+pub use stop_word::JapaneseStopWordFilter;
+pub use stop_reading::StopReadingFilter;
+pub use synonym::SynonymFilter;
+pub use unknown_ngram::UnknownWordNgramFilter;
+pub use vocabulary::{VocabularyFilter, VocabularyMode};
+
+use crate::char_filter::short_type_name;
+use crate::token::MorphToken;
+
+/// A filter transforming the morphemes of a text before they are emitted as tantivy tokens.
+pub trait MorphFilter: Send + Sync + 'static {
+    /// Name of the filter, used by [`explain`](crate::VibratoTokenizer::explain).
+    fn name(&self) -> &'static str {
+        short_type_name::<Self>()
+    }
+
+    /// Transform `tokens` in place. Tokens can be rewritten, removed or added.
+    fn filter(&self, tokens: &mut Vec<MorphToken>);
+
+    /// Parameters of the filter, included in the
+    /// [`fingerprint`](crate::VibratoTokenizer::fingerprint) of the tokenizer.
+    fn config(&self) -> String {
+        String::new()
+    }
+
+    /// Explain why `token` was dropped or rewritten by this filter, if possible.
+    fn reason(&self, _token: &MorphToken) -> Option<String> {
+        None
+    }
+}
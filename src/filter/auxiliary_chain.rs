@@ -0,0 +1,86 @@
+use crate::filter::predicate::{chain_end, is_adjective, is_main_verb};
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Collapses a verb or an adjective and the auxiliaries and inflections following it into one
+/// token spanning the whole chain, e.g. `行きました` instead of `行き`, `まし` and `た`.
+///
+/// Unlike lemmatization, the chain keeps its text, so that `行きました` and `行きません` stay
+/// different terms: use it for fields where phrase-level units match the intent of the users
+/// better than words. The chain is made of the auxiliary words (`助動詞`), the auxiliary verbs
+/// and adjectives, and the `て`/`で`/`ば` particles followed by an auxiliary verb. The collapsed
+/// token keeps the feature string of its first word and spans the positions of all its words.
+#[derive(Debug, Clone, Default)]
+pub struct AuxiliaryChainFilter;
+
+impl AuxiliaryChainFilter {
+    /// Create a new `AuxiliaryChainFilter`.
+    pub fn new() -> AuxiliaryChainFilter {
+        AuxiliaryChainFilter
+    }
+}
+
+impl MorphFilter for AuxiliaryChainFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            if token.overlay || !(is_main_verb(token) || is_adjective(token)) {
+                filtered.push(token.clone());
+                i += 1;
+                continue;
+            }
+            let end = chain_end(tokens, i);
+            let chain = &tokens[i..end];
+            let last = &chain[chain.len() - 1];
+            filtered.push(MorphToken {
+                text: chain.iter().map(|t| t.text.as_str()).collect(),
+                surface: chain.iter().map(|t| t.surface.as_str()).collect(),
+                offset_to: last.offset_to,
+                char_to: last.char_to,
+                total_cost: last.total_cost,
+                ..token.clone()
+            });
+            i = end;
+        }
+        *tokens = filtered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn collapse() {
+        let tokenizer = test_tokenizer();
+        let collapse = |text: &str| {
+            let mut tokens = tokenizer.morphemes(text);
+            AuxiliaryChainFilter::new().filter(&mut tokens);
+            tokens
+                .into_iter()
+                .map(|t| (t.text, t.char_from, t.char_to))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            collapse("東京に行きました。"),
+            vec![
+                ("東京".to_string(), 0, 2),
+                ("に".to_string(), 2, 3),
+                ("行きました".to_string(), 3, 8),
+                ("。".to_string(), 8, 9),
+            ]
+        );
+        assert_eq!(
+            collapse("読んでしまった"),
+            vec![("読んでしまった".to_string(), 0, 7)]
+        );
+        assert_eq!(
+            collapse("美味しかった"),
+            vec![("美味しかった".to_string(), 0, 6)]
+        );
+    }
+}
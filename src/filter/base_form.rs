@@ -0,0 +1,69 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Replaces the text of inflected words with their base form, e.g. `食べる` for `食べ` in
+/// `食べた`, so that every inflection of a verb or an adjective matches the others.
+///
+/// Words without a base form, such as unknown words, keep their surface.
+#[derive(Debug, Clone, Default)]
+pub struct BaseFormFilter {
+    lemma: bool,
+}
+
+impl BaseFormFilter {
+    /// Create a filter replacing the text with the base form.
+    pub fn new() -> BaseFormFilter {
+        BaseFormFilter::default()
+    }
+
+    /// Whether to use the lemma rather than the base form. For UniDic, this also normalizes
+    /// the orthography, e.g. `引っ越す` for `引越し`; IPADIC has no lemma distinct from the base
+    /// form. Disabled by default.
+    pub fn lemma(mut self, lemma: bool) -> BaseFormFilter {
+        self.lemma = lemma;
+        self
+    }
+}
+
+impl MorphFilter for BaseFormFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        for token in tokens {
+            let features = token.features();
+            let form = if self.lemma {
+                features.lemma()
+            } else {
+                features.base_form()
+            };
+            if let Some(form) = form {
+                if form != token.text {
+                    token.text = form.to_string();
+                }
+            }
+        }
+    }
+
+    fn config(&self) -> String {
+        if self.lemma {
+            "lemma".to_string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn base_forms() {
+        let tokenizer = test_tokenizer().filter(BaseFormFilter::new());
+        let texts = tokenizer
+            .analyze("食べました。ChatGPT", None)
+            .into_iter()
+            .map(|t| t.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["食べる", "ます", "た", "。", "ChatGPT"]);
+    }
+}
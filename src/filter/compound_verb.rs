@@ -0,0 +1,114 @@
+use crate::filter::predicate::{is_auxiliary, is_auxiliary_verb, is_main_verb, is_te};
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Merges a verb followed by te-form auxiliary verbs into one token whose text is the lemma of
+/// the main verb, e.g. `読んでしまった` into `読む` and `食べている` into `食べる`.
+///
+/// Conversational text is full of aspect forms (`〜ている`, `〜てしまう`, `〜てみる`, ...)
+/// which add little to the meaning for search but index the same auxiliaries over and over.
+/// A chain is a main verb, one or more `て`/`で` + auxiliary verb pairs, and the auxiliaries
+/// (`た`, `ます`, ...) following them. The merged token spans the whole chain and keeps the
+/// feature string of the main verb. Verbs without a te-form auxiliary are left alone.
+#[derive(Debug, Clone, Default)]
+pub struct CompoundVerbFilter;
+
+impl CompoundVerbFilter {
+    /// Create a new `CompoundVerbFilter`.
+    pub fn new() -> CompoundVerbFilter {
+        CompoundVerbFilter
+    }
+}
+
+/// Returns the end of the chain starting with the main verb at `start`, if there is one.
+fn chain_end(tokens: &[MorphToken], start: usize) -> Option<usize> {
+    let adjacent = |i: usize| {
+        tokens
+            .get(i)
+            .filter(|t| !t.overlay && t.char_from == tokens[i - 1].char_to)
+    };
+    let mut end = start + 1;
+    let mut merged = false;
+    loop {
+        match (adjacent(end), adjacent(end + 1)) {
+            (Some(te), Some(verb)) if is_te(te) && is_auxiliary_verb(verb) => {
+                end += 2;
+                merged = true;
+            }
+            _ => break,
+        }
+        while adjacent(end).is_some_and(is_auxiliary) {
+            end += 1;
+        }
+    }
+    merged.then_some(end)
+}
+
+impl MorphFilter for CompoundVerbFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let end = (!token.overlay && is_main_verb(token))
+                .then(|| chain_end(tokens, i))
+                .flatten();
+            let Some(end) = end else {
+                filtered.push(token.clone());
+                i += 1;
+                continue;
+            };
+            let chain = &tokens[i..end];
+            let last = &chain[chain.len() - 1];
+            filtered.push(MorphToken {
+                text: token.features().lemma().unwrap_or(&token.text).to_string(),
+                surface: chain.iter().map(|t| t.surface.as_str()).collect(),
+                offset_to: last.offset_to,
+                char_to: last.char_to,
+                total_cost: last.total_cost,
+                ..token.clone()
+            });
+            i = end;
+        }
+        *tokens = filtered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn merge() {
+        let tokenizer = test_tokenizer();
+        let merge = |text: &str| {
+            let mut tokens = tokenizer.morphemes(text);
+            CompoundVerbFilter::new().filter(&mut tokens);
+            tokens
+                .into_iter()
+                .map(|t| (t.text, t.surface, t.char_from, t.char_to))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            merge("読んでしまった"),
+            vec![("読む".to_string(), "読んでしまった".to_string(), 0, 7)]
+        );
+        assert_eq!(
+            merge("私は食べている"),
+            vec![
+                ("私".to_string(), "私".to_string(), 0, 1),
+                ("は".to_string(), "は".to_string(), 1, 2),
+                ("食べる".to_string(), "食べている".to_string(), 2, 7),
+            ]
+        );
+        assert_eq!(
+            merge("行きました")
+                .into_iter()
+                .map(|t| t.0)
+                .collect::<Vec<_>>(),
+            vec!["行き", "まし", "た"]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Removes sentence-final particles (`ね`, `よ`, `か`, `わ`, `ぞ`, ...) and fillers (`えっと`,
+/// `あのー`, ...), which dominate the term statistics of chat and voice transcripts.
+///
+/// The particles are the ones tagged `助詞-終助詞`. `か` is tagged `助詞-副助詞／並立助詞／終助詞`
+/// by IPADIC since it also lists alternatives (`行くか行かないか`), so such particles are only
+/// removed at the end of a sentence: before a symbol, another removed particle or the end of
+/// the text. Interjections (`感動詞`) are removed as well unless disabled.
+#[derive(Debug, Clone)]
+pub struct SentenceFinalParticleFilter {
+    interjections: bool,
+}
+
+impl SentenceFinalParticleFilter {
+    /// Create a new `SentenceFinalParticleFilter`.
+    pub fn new() -> SentenceFinalParticleFilter {
+        SentenceFinalParticleFilter {
+            interjections: true,
+        }
+    }
+
+    /// Whether to remove the interjections besides the fillers. Enabled by default.
+    pub fn interjections(mut self, interjections: bool) -> SentenceFinalParticleFilter {
+        self.interjections = interjections;
+        self
+    }
+
+    fn kind(&self, token: &MorphToken) -> Option<Kind> {
+        let features = token.features();
+        match features.pos().as_slice() {
+            ["助詞", "終助詞", ..] => Some(Kind::Particle),
+            ["助詞", "副助詞／並立助詞／終助詞", ..] => Some(Kind::AmbiguousParticle),
+            ["フィラー", ..] | ["感動詞", "フィラー", ..] => Some(Kind::Filler),
+            ["感動詞", ..] if self.interjections => Some(Kind::Interjection),
+            _ => None,
+        }
+    }
+}
+
+impl Default for SentenceFinalParticleFilter {
+    fn default() -> SentenceFinalParticleFilter {
+        SentenceFinalParticleFilter::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Particle,
+    AmbiguousParticle,
+    Filler,
+    Interjection,
+}
+
+fn is_symbol(token: &MorphToken) -> bool {
+    matches!(
+        token.features().pos().first().copied(),
+        Some("記号" | "補助記号")
+    )
+}
+
+impl MorphFilter for SentenceFinalParticleFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let kinds = tokens.iter().map(|t| self.kind(t)).collect::<Vec<_>>();
+        let mut removed = vec![false; tokens.len()];
+        // From the end, so that `か` followed by a removed particle is removed too.
+        for i in (0..tokens.len()).rev() {
+            removed[i] = match kinds[i] {
+                Some(Kind::AmbiguousParticle) => {
+                    let next = (i + 1..tokens.len()).find(|&j| !tokens[j].overlay);
+                    next.is_none_or(|j| removed[j] || is_symbol(&tokens[j]))
+                }
+                Some(_) => true,
+                None => false,
+            };
+        }
+        let mut removed = removed.into_iter();
+        tokens.retain(|_| !removed.next().unwrap_or(false));
+    }
+
+    fn config(&self) -> String {
+        format!("interjections={}", self.interjections)
+    }
+
+    fn reason(&self, token: &MorphToken) -> Option<String> {
+        self.kind(token).map(|kind| match kind {
+            Kind::Particle | Kind::AmbiguousParticle => {
+                format!("sentence-final particle {}", token.surface)
+            }
+            Kind::Filler => format!("filler {}", token.surface),
+            Kind::Interjection => format!("interjection {}", token.surface),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn strip() {
+        let tokenizer = test_tokenizer();
+        let strip = |filter: &SentenceFinalParticleFilter, text: &str| {
+            let mut tokens = tokenizer.morphemes(text);
+            filter.filter(&mut tokens);
+            tokens.into_iter().map(|t| t.surface).collect::<Vec<_>>()
+        };
+        let filter = SentenceFinalParticleFilter::new();
+
+        assert_eq!(
+            strip(&filter, "えっと東京に行きましたよね"),
+            vec!["東京", "に", "行き", "まし", "た"]
+        );
+        assert_eq!(
+            strip(&filter, "行きましたか。"),
+            vec!["行き", "まし", "た", "。"]
+        );
+        assert_eq!(
+            strip(&filter, "うーん東京か大学か"),
+            vec!["東京", "か", "大学"]
+        );
+        assert_eq!(
+            strip(&filter.clone().interjections(false), "うーん東京"),
+            vec!["うーん", "東京"]
+        );
+    }
+}
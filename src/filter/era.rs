@@ -0,0 +1,176 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Japanese eras: name, Gregorian year of the first year and number of years, `None` for the
+/// current era, whose years are accepted up to 99.
+const ERAS: [(&str, u32, Option<u32>); 5] = [
+    ("令和", 2019, None),
+    ("平成", 1989, Some(31)),
+    ("昭和", 1926, Some(64)),
+    ("大正", 1912, Some(15)),
+    ("明治", 1868, Some(45)),
+];
+
+const KANJI_DIGITS: &str = "〇一二三四五六七八九";
+
+/// Maximum number of bytes of an era date, `令和` included.
+const MAX_DATE_LEN: usize = 32;
+
+/// Adds the Gregorian year of era dates, e.g. `2018` for `平成30年`, at the position of the
+/// year number, so that date queries match documents regardless of the calendar they use.
+///
+/// The era name and `年` are kept, so that with consecutive positions `平成30年4月` also
+/// matches the phrase `2018年4月`. The year may be written with ASCII, fullwidth or kanji
+/// digits (`平成三十年`), and `元年` is the first year of an era. Years beyond the end of an
+/// era are left alone.
+#[derive(Debug, Clone, Default)]
+pub struct EraDateFilter;
+
+impl EraDateFilter {
+    /// Create a new `EraDateFilter`.
+    pub fn new() -> EraDateFilter {
+        EraDateFilter
+    }
+}
+
+fn kanji_digit(c: char) -> Option<u32> {
+    KANJI_DIGITS.chars().position(|k| k == c).map(|d| d as u32)
+}
+
+/// Parses the year number at the start of `text`, returning its value and its length in
+/// bytes.
+fn parse_year(text: &str) -> Option<(u32, usize)> {
+    if text.starts_with('元') {
+        return Some((1, '元'.len_utf8()));
+    }
+    let len = text
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_digit() && !('０'..='９').contains(c))
+        .map_or(text.len(), |(i, _)| i);
+    if len > 0 {
+        let digits = text[..len].chars().map(|c| match c {
+            '０'..='９' => c as u32 - '０' as u32,
+            _ => c as u32 - '0' as u32,
+        });
+        return Some((
+            digits.fold(0u32, |n, d| n.saturating_mul(10).saturating_add(d)),
+            len,
+        ));
+    }
+
+    // Kanji numerals: `三`, `十`, `三十`, `三十一`, `二〇`.
+    let len = text
+        .char_indices()
+        .find(|(_, c)| kanji_digit(*c).is_none() && *c != '十')
+        .map_or(text.len(), |(i, _)| i);
+    let numeral = &text[..len];
+    let value = match numeral.split_once('十') {
+        Some((tens, ones)) if tens.chars().count() <= 1 && ones.chars().count() <= 1 => {
+            let tens = tens.chars().next().map_or(Some(1), kanji_digit)?;
+            let ones = ones.chars().next().map_or(Some(0), kanji_digit)?;
+            tens * 10 + ones
+        }
+        Some(_) => return None,
+        None if !numeral.is_empty() => numeral
+            .chars()
+            .filter_map(kanji_digit)
+            .fold(0, |n, d| n * 10 + d),
+        None => return None,
+    };
+    Some((value, len))
+}
+
+/// Returns the Gregorian year of the era date at the start of `text` and the byte range of
+/// its year number.
+fn gregorian_year(text: &str) -> Option<(u32, std::ops::Range<usize>)> {
+    let (era, first_year, length) = ERAS.iter().find(|(era, ..)| text.starts_with(era))?;
+    let (year, len) = parse_year(&text[era.len()..])?;
+    let number = era.len()..era.len() + len;
+    let valid = (1..=length.unwrap_or(99)).contains(&year);
+    (valid && text[number.end..].starts_with('年')).then(|| (first_year + year - 1, number))
+}
+
+impl MorphFilter for EraDateFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let primary = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+        let mut overlays = vec![];
+        for (i, first) in primary.iter().enumerate() {
+            if !ERAS.iter().any(|(era, ..)| first.surface.starts_with(era)) {
+                continue;
+            }
+            // The era name, the number and `年` may be split into any words.
+            let mut text = first.surface.clone();
+            for (previous, token) in primary[i..].iter().zip(&primary[i + 1..]) {
+                if previous.offset_to != token.offset_from || text.len() > MAX_DATE_LEN {
+                    break;
+                }
+                text.push_str(&token.surface);
+            }
+            let Some((year, number)) = gregorian_year(&text) else {
+                continue;
+            };
+            let offset_from = first.offset_from + number.start;
+            let char_from = first.char_from + text[..number.start].chars().count();
+            let template = primary[i..]
+                .iter()
+                .find(|t| t.offset_to > offset_from)
+                .unwrap_or(first);
+            overlays.push(MorphToken {
+                text: year.to_string(),
+                surface: text[number.clone()].to_string(),
+                offset_from,
+                offset_to: first.offset_from + number.end,
+                char_from,
+                char_to: char_from + text[number].chars().count(),
+                overlay: true,
+                ..(*template).clone()
+            });
+        }
+        if !overlays.is_empty() {
+            tokens.extend(overlays);
+            tokens.sort_by_key(|t| (t.char_from, t.overlay));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn years() {
+        assert_eq!(gregorian_year("令和5年").map(|y| y.0), Some(2023));
+        assert_eq!(gregorian_year("平成３０年").map(|y| y.0), Some(2018));
+        assert_eq!(gregorian_year("平成三十一年").map(|y| y.0), Some(2019));
+        assert_eq!(gregorian_year("昭和元年").map(|y| y.0), Some(1926));
+        assert_eq!(gregorian_year("明治十年").map(|y| y.0), Some(1877));
+        assert_eq!(gregorian_year("平成32年"), None);
+        assert_eq!(gregorian_year("令和5"), None);
+        assert_eq!(gregorian_year("令和99999999999年"), None);
+        assert_eq!(gregorian_year("令和年"), None);
+    }
+
+    #[test]
+    fn overlays() {
+        let tokenizer = test_tokenizer();
+        let mut tokens = tokenizer.morphemes("平成30年4月");
+        EraDateFilter::new().filter(&mut tokens);
+
+        let texts = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay, t.offset_from, t.offset_to))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("平成", false, 0, 6),
+                ("30", false, 6, 8),
+                ("2018", true, 6, 8),
+                ("年", false, 8, 11),
+                ("4", false, 11, 12),
+                ("月", false, 12, 15),
+            ]
+        );
+    }
+}
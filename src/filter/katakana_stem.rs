@@ -0,0 +1,139 @@
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenFilter, TokenStream};
+
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+const PROLONGED_SOUND_MARK: char = 'ー';
+
+/// Default minimum length of the stemmed words, the one of Lucene's
+/// `JapaneseKatakanaStemFilter`.
+const DEFAULT_MIN_LEN: usize = 4;
+
+/// Removes the trailing prolonged sound mark of katakana words of at least `min_len`
+/// characters, e.g. `サーバー` to `サーバ` and `コンピューター` to `コンピュータ`, so that both
+/// spellings match. Words with other characters than katakana are left alone.
+///
+/// It is both a [`MorphFilter`] and a tantivy `TokenFilter` matching the term text only,
+/// which can follow any filter of a `TextAnalyzer`:
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::tokenizer::TextAnalyzer;
+/// use tantivy_vibrato::filter::KatakanaStemFilter;
+/// use tantivy_vibrato::VibratoTokenizer;
+///
+/// let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+/// let analyzer = TextAnalyzer::from(tokenizer).filter(KatakanaStemFilter::default());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KatakanaStemFilter {
+    min_len: usize,
+}
+
+impl KatakanaStemFilter {
+    /// Create a filter stemming the katakana words of at least `min_len` characters. The
+    /// default is 4, like Lucene.
+    pub fn new(min_len: usize) -> KatakanaStemFilter {
+        KatakanaStemFilter { min_len }
+    }
+
+    fn stem<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let len = text.chars().count();
+        if len >= self.min_len
+            && text.ends_with(PROLONGED_SOUND_MARK)
+            && text.chars().all(is_katakana)
+        {
+            Some(&text[..text.len() - PROLONGED_SOUND_MARK.len_utf8()])
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for KatakanaStemFilter {
+    fn default() -> KatakanaStemFilter {
+        KatakanaStemFilter::new(DEFAULT_MIN_LEN)
+    }
+}
+
+impl MorphFilter for KatakanaStemFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        for token in tokens {
+            if let Some(stem) = self.stem(&token.text) {
+                token.text = stem.to_string();
+            }
+        }
+    }
+
+    fn config(&self) -> String {
+        self.min_len.to_string()
+    }
+}
+
+impl TokenFilter for KatakanaStemFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(KatakanaStemTokenStream {
+            filter: self.clone(),
+            tail: token_stream,
+        })
+    }
+}
+
+struct KatakanaStemTokenStream<'a> {
+    filter: KatakanaStemFilter,
+    tail: BoxTokenStream<'a>,
+}
+
+impl TokenStream for KatakanaStemTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if let Some(len) = self.filter.stem(&token.text).map(str::len) {
+            token.text.truncate(len);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+pub(crate) fn is_katakana(c: char) -> bool {
+    ('\u{30A0}'..='\u{30FF}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TextAnalyzer;
+
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn stem() {
+        let filter = KatakanaStemFilter::default();
+        assert_eq!(filter.stem("サーバー"), Some("サーバ"));
+        assert_eq!(filter.stem("コピー"), None);
+        assert_eq!(filter.stem("バー"), None);
+        assert_eq!(filter.stem("サーバー用"), None);
+        assert_eq!(KatakanaStemFilter::new(3).stem("コピー"), Some("コピ"));
+
+        let tokenizer = test_tokenizer();
+        let analyzer = TextAnalyzer::from(tokenizer).filter(KatakanaStemFilter::default());
+        let mut stream = analyzer.token_stream("サーバーのうち");
+        let mut texts = vec![];
+        while let Some(token) = stream.next() {
+            texts.push(token.text.clone());
+        }
+        assert_eq!(texts, vec!["サーバ", "の", "うち"]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::filter::katakana_stem::is_katakana;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Spelling variants of loanwords, rewritten from left to right. Longer patterns come first.
+const RULES: &[(&str, &str)] = &[
+    ("ヴァ", "バ"),
+    ("ヴィ", "ビ"),
+    ("ヴェ", "ベ"),
+    ("ヴォ", "ボ"),
+    ("ヴ", "ブ"),
+    ("ヲ", "ウォ"),
+    ("ティ", "チ"),
+];
+
+/// Adds a normalized spelling of katakana words at their position, so that the inconsistent
+/// spellings of loanwords match each other: `ヴァイオリン` and `バイオリン`, `ティケット` and
+/// `チケット`, `ヲッカ` and `ウォッカ`, `コンピューター` and `コンピュータ`.
+///
+/// The normalized spelling replaces `ヴ` with `ブ` (`ヴァ` with `バ`, ...), `ヲ` with `ウォ`,
+/// `ティ` with `チ` and removes the trailing prolonged sound mark. It is added as an overlay
+/// token when it differs from the word.
+#[derive(Debug, Clone, Default)]
+pub struct KatakanaVariantFilter;
+
+impl KatakanaVariantFilter {
+    /// Create a new `KatakanaVariantFilter`.
+    pub fn new() -> KatakanaVariantFilter {
+        KatakanaVariantFilter
+    }
+}
+
+fn normalize(text: &str) -> Option<String> {
+    if text.is_empty() || !text.chars().all(is_katakana) {
+        return None;
+    }
+    let mut normalized = String::with_capacity(text.len());
+    let mut rest = text;
+    'outer: while let Some(c) = rest.chars().next() {
+        for (from, to) in RULES {
+            if let Some(r) = rest.strip_prefix(from) {
+                normalized.push_str(to);
+                rest = r;
+                continue 'outer;
+            }
+        }
+        normalized.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    while normalized.chars().count() > 1 && normalized.ends_with('ー') {
+        normalized.pop();
+    }
+    (normalized != text).then_some(normalized)
+}
+
+impl MorphFilter for KatakanaVariantFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for token in tokens.drain(..) {
+            let variant = normalize(&token.text).map(|text| MorphToken {
+                text,
+                overlay: true,
+                ..token.clone()
+            });
+            filtered.push(token);
+            filtered.extend(variant);
+        }
+        *tokens = filtered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn variants() {
+        assert_eq!(normalize("ヴァイオリン").as_deref(), Some("バイオリン"));
+        assert_eq!(normalize("バイオリン"), None);
+        assert_eq!(normalize("ヴェネツィア").as_deref(), Some("ベネツィア"));
+        assert_eq!(normalize("ティケット").as_deref(), Some("チケット"));
+        assert_eq!(normalize("ヲッカ").as_deref(), Some("ウォッカ"));
+        assert_eq!(normalize("コンピューター").as_deref(), Some("コンピュータ"));
+        assert_eq!(normalize("ー"), None);
+        assert_eq!(normalize("すもも"), None);
+    }
+
+    #[test]
+    fn overlays() {
+        let tokenizer = test_tokenizer();
+        let mut tokens = tokenizer.morphemes("コンピューターの");
+        KatakanaVariantFilter::new().filter(&mut tokens);
+
+        let texts = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay, t.char_from))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("コンピューター", false, 0),
+                ("コンピュータ", true, 0),
+                ("の", false, 7),
+            ]
+        );
+    }
+}
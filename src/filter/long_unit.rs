@@ -0,0 +1,188 @@
+use crate::features::DictionaryKind;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Adds long-unit words recombined from runs of short-unit nouns, e.g. `東京大学` on top of
+/// `東京` and `大学`.
+///
+/// UniDic segments text into short-unit words (SUW), which splits the compounds of news and
+/// legal texts into words too common to search precisely. A run of nouns, optionally preceded
+/// by noun prefixes and followed by noun suffixes, is emitted as one more token overlaid on the
+/// short-unit words it covers, so that both the compound and its parts match.
+///
+/// The feature string of a long-unit word is the one of its last word, with the base form,
+/// lemma, reading and pronunciation fields replaced by the concatenation of its words.
+/// IPADIC, whose words are already longer, is supported in the same way.
+#[derive(Debug, Clone, Default)]
+pub struct LongUnitFilter;
+
+impl LongUnitFilter {
+    /// Create a new `LongUnitFilter`.
+    pub fn new() -> LongUnitFilter {
+        LongUnitFilter
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Prefix,
+    Body,
+    Suffix,
+    Other,
+}
+
+fn role(token: &MorphToken) -> Role {
+    let features = token.features();
+    let pos = features.pos();
+    let pos = (pos.first().copied(), pos.get(1).copied());
+    match token.kind {
+        DictionaryKind::Ipadic => match pos {
+            (Some("名詞"), Some("非自立" | "代名詞")) => Role::Other,
+            (Some("名詞"), Some("接尾")) => Role::Suffix,
+            (Some("名詞"), _) => Role::Body,
+            (Some("接頭詞"), Some("名詞接続")) => Role::Prefix,
+            _ => Role::Other,
+        },
+        DictionaryKind::Unidic => match pos {
+            (Some("名詞"), Some("普通名詞" | "固有名詞" | "数詞")) => Role::Body,
+            (Some("接頭辞"), _) => Role::Prefix,
+            (Some("接尾辞"), Some("名詞的")) => Role::Suffix,
+            _ => Role::Other,
+        },
+    }
+}
+
+/// Fields concatenated over the words of a long-unit word.
+pub(crate) fn concatenated_fields(kind: DictionaryKind) -> &'static [usize] {
+    match kind {
+        DictionaryKind::Ipadic => &[6, 7, 8],
+        DictionaryKind::Unidic => &[6, 7, 8, 9, 10, 11, 20],
+    }
+}
+
+/// Returns the overlay spanning `words`, with the feature string of the last word whose base
+/// form, lemma, reading and pronunciation fields are concatenated over the words.
+pub(crate) fn combine(words: &[&MorphToken]) -> MorphToken {
+    let first = words[0];
+    let last = words[words.len() - 1];
+    let mut fields = last
+        .feature_fields()
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for &i in concatenated_fields(last.kind) {
+        if i >= fields.len() {
+            continue;
+        }
+        let parts = words
+            .iter()
+            .map(|w| w.features().field(i))
+            .collect::<Option<Vec<_>>>();
+        fields[i] = parts.map_or_else(|| "*".to_string(), |p| p.concat());
+    }
+
+    MorphToken {
+        text: words.iter().map(|w| w.text.as_str()).collect(),
+        surface: words.iter().map(|w| w.surface.as_str()).collect(),
+        offset_from: first.offset_from,
+        offset_to: last.offset_to,
+        char_from: first.char_from,
+        char_to: last.char_to,
+        feature: fields.join(","),
+        kind: last.kind,
+        lex_type: last.lex_type,
+        word_cost: words
+            .iter()
+            .map(|w| w.word_cost)
+            .fold(0i16, i16::saturating_add),
+        total_cost: last.total_cost,
+        overlay: true,
+        negated: false,
+    }
+}
+
+/// Returns the end of the run of nouns of `tokens` starting at `start`, optionally preceded by
+/// noun prefixes and followed by noun suffixes, if there is one.
+pub(crate) fn noun_run_end(tokens: &[&MorphToken], start: usize) -> Option<usize> {
+    let adjacent = |i: usize| i == start || tokens[i - 1].char_to == tokens[i].char_from;
+    let is = |i: usize, roles: &[Role]| {
+        i < tokens.len() && adjacent(i) && roles.contains(&role(tokens[i]))
+    };
+    let mut end = start;
+    while is(end, &[Role::Prefix]) {
+        end += 1;
+    }
+    if !is(end, &[Role::Body]) {
+        return None;
+    }
+    while is(end, &[Role::Body, Role::Suffix]) {
+        end += 1;
+    }
+    Some(end)
+}
+
+/// Returns the runs of nouns of `tokens`, including the runs of a single noun. Overlays are
+/// ignored.
+pub(crate) fn noun_runs(tokens: &[MorphToken]) -> Vec<Vec<&MorphToken>> {
+    let tokens = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+    let mut runs = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        match noun_run_end(&tokens, i) {
+            Some(end) => {
+                runs.push(tokens[i..end].to_vec());
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    runs
+}
+
+impl MorphFilter for LongUnitFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let long_units = noun_runs(tokens)
+            .into_iter()
+            .filter(|run| run.len() >= 2)
+            .map(|run| combine(&run))
+            .collect::<Vec<_>>();
+
+        if !long_units.is_empty() {
+            tokens.extend(long_units);
+            tokens.sort_by_key(|t| (t.char_from, t.overlay));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn long_units() {
+        let tokenizer = test_tokenizer();
+        let mut tokens = tokenizer.morphemes("新東京大学に行き");
+        LongUnitFilter::new().filter(&mut tokens);
+
+        let texts = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("新", false),
+                ("新東京大学", true),
+                ("東京", false),
+                ("大学", false),
+                ("に", false),
+                ("行き", false),
+            ]
+        );
+        let long_unit = tokens[1].features();
+        assert_eq!(long_unit.pos_tag(), "名詞-一般");
+        assert_eq!(long_unit.base_form(), Some("新東京大学"));
+        assert_eq!(long_unit.reading(), Some("シントウキョウダイガク"));
+    }
+}
@@ -0,0 +1,16 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Lowercases the text of tokens.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LowerCaseFilter;
+
+impl MorphFilter for LowerCaseFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        for token in tokens {
+            if token.text.chars().any(char::is_uppercase) {
+                token.text = token.text.to_lowercase();
+            }
+        }
+    }
+}
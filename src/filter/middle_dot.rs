@@ -0,0 +1,224 @@
+use crate::filter::katakana_stem::is_katakana;
+use crate::filter::long_unit::{combine, concatenated_fields};
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// How katakana compounds joined by middle dots are emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MiddleDotMode {
+    /// Emit the parts only, e.g. `スミス` and `ジョーンズ`.
+    Split,
+    /// Emit the whole compound only, e.g. `スミス・ジョーンズ`.
+    Keep,
+    /// Emit the parts and the whole compound overlaid on them, spanning their positions.
+    Both,
+}
+
+/// Makes katakana compounds joined by middle dots (`・` or `･`), such as `スミス・ジョーンズ` or
+/// `プロジェクト・マネージャー`, consistently split into their parts, kept whole, or both,
+/// whether or not the dictionary has the compound.
+///
+/// The compounds of the dictionary are split into their parts first, with the fields of the
+/// entry split on the dots too when they have as many parts. The middle dots of the compounds
+/// are never emitted. The whole compound keeps its dots and gets the feature string of its
+/// last part, see [`LongUnitFilter`](crate::filter::LongUnitFilter).
+#[derive(Debug, Clone)]
+pub struct MiddleDotFilter {
+    mode: MiddleDotMode,
+}
+
+impl MiddleDotFilter {
+    /// Create a filter emitting the compounds in `mode`.
+    pub fn new(mode: MiddleDotMode) -> MiddleDotFilter {
+        MiddleDotFilter { mode }
+    }
+}
+
+fn is_dot(text: &str) -> bool {
+    text == "・" || text == "･"
+}
+
+fn is_part(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| (is_katakana(c) || ('\u{FF66}'..='\u{FF9F}').contains(&c)) && c != '・')
+}
+
+/// Splits a dictionary entry joined by middle dots into its parts and dots.
+fn split(token: MorphToken) -> Vec<MorphToken> {
+    let pieces = token
+        .surface
+        .split_inclusive(['・', '･'])
+        .collect::<Vec<_>>();
+    let parts = pieces
+        .iter()
+        .map(|p| p.trim_end_matches(['・', '･']))
+        .collect::<Vec<_>>();
+    if token.overlay
+        || token.text != token.surface
+        || parts.len() < 2
+        || !parts.iter().all(|p| is_part(p))
+    {
+        return vec![token];
+    }
+
+    let fields = token.feature_fields();
+    let mut split = vec![];
+    let (mut offset, mut chars) = (token.offset_from, token.char_from);
+    for (i, part) in parts.iter().enumerate() {
+        let mut part_fields = fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        for &f in concatenated_fields(token.kind) {
+            if let Some(field) = part_fields.get_mut(f) {
+                let values = field.split(['・', '･']).collect::<Vec<_>>();
+                *field = if values.len() == parts.len() {
+                    values[i].to_string()
+                } else {
+                    "*".to_string()
+                };
+            }
+        }
+        let part_len = part.chars().count();
+        split.push(MorphToken {
+            text: part.to_string(),
+            surface: part.to_string(),
+            offset_from: offset,
+            offset_to: offset + part.len(),
+            char_from: chars,
+            char_to: chars + part_len,
+            feature: part_fields.join(","),
+            ..token.clone()
+        });
+        offset += part.len();
+        chars += part_len;
+        let dot = &pieces[i][part.len()..];
+        if !dot.is_empty() {
+            split.push(MorphToken {
+                text: dot.to_string(),
+                surface: dot.to_string(),
+                offset_from: offset,
+                offset_to: offset + dot.len(),
+                char_from: chars,
+                char_to: chars + 1,
+                ..token.clone()
+            });
+            offset += dot.len();
+            chars += 1;
+        }
+    }
+    split
+}
+
+/// Returns the end of the compound of `tokens` starting at `start`, if there is one.
+fn compound_end(tokens: &[MorphToken], start: usize) -> Option<usize> {
+    let adjacent = |i: usize| tokens[i - 1].char_to == tokens[i].char_from;
+    let is =
+        |i: usize, f: fn(&str) -> bool| tokens.get(i).is_some_and(|t| !t.overlay && f(&t.surface));
+    if !is(start, is_part) {
+        return None;
+    }
+    let mut end = start + 1;
+    while is(end, is_dot) && is(end + 1, is_part) && adjacent(end) && adjacent(end + 1) {
+        end += 2;
+    }
+    (end > start + 1).then_some(end)
+}
+
+impl MorphFilter for MiddleDotFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        if !tokens.iter().any(|t| t.surface.contains(['・', '･'])) {
+            return;
+        }
+        let split = tokens.drain(..).flat_map(split).collect::<Vec<_>>();
+        let mut i = 0;
+        while i < split.len() {
+            let Some(end) = compound_end(&split, i) else {
+                tokens.push(split[i].clone());
+                i += 1;
+                continue;
+            };
+            let compound = &split[i..end];
+            if self.mode != MiddleDotMode::Split {
+                let mut whole = combine(&compound.iter().collect::<Vec<_>>());
+                whole.overlay = self.mode == MiddleDotMode::Both;
+                tokens.push(whole);
+            }
+            if self.mode != MiddleDotMode::Keep {
+                tokens.extend(compound.iter().filter(|t| !is_dot(&t.surface)).cloned());
+            }
+            i = end;
+        }
+        if self.mode == MiddleDotMode::Both {
+            tokens.sort_by_key(|t| (t.char_from, t.overlay));
+        }
+    }
+
+    fn config(&self) -> String {
+        format!("{:?}", self.mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    fn filtered(mode: MiddleDotMode, text: &str) -> Vec<(String, bool)> {
+        let tokenizer = test_tokenizer();
+        let mut tokens = tokenizer.morphemes(text);
+        MiddleDotFilter::new(mode).filter(&mut tokens);
+        tokens.into_iter().map(|t| (t.text, t.overlay)).collect()
+    }
+
+    #[test]
+    fn modes() {
+        let text = "スミス・ジョーンズと東京・大阪";
+        let texts = |mode| {
+            let tokens = filtered(mode, text);
+            tokens[..tokens.len() - 4]
+                .iter()
+                .map(|(t, overlay)| format!("{t}{}", if *overlay { "+" } else { "" }))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(MiddleDotMode::Split), vec!["スミス", "ジョーンズ"]);
+        assert_eq!(texts(MiddleDotMode::Keep), vec!["スミス・ジョーンズ"]);
+        assert_eq!(
+            texts(MiddleDotMode::Both),
+            vec!["スミス", "スミス・ジョーンズ+", "ジョーンズ"]
+        );
+        // Other words joined by dots are left alone.
+        let tail = filtered(MiddleDotMode::Split, text);
+        let tail = tail[tail.len() - 4..].iter().map(|(t, _)| t.as_str());
+        assert_eq!(tail.collect::<Vec<_>>(), vec!["と", "東京", "・", "大阪"]);
+    }
+
+    #[test]
+    fn split_entries() {
+        let token = MorphToken {
+            text: "スミス・ジョーンズ".to_string(),
+            surface: "スミス・ジョーンズ".to_string(),
+            offset_from: 0,
+            offset_to: 27,
+            char_from: 0,
+            char_to: 9,
+            feature: "名詞,固有名詞,人名,一般,*,*,スミス・ジョーンズ,スミス・ジョーンズ,スミス・ジョーンズ"
+                .to_string(),
+            kind: crate::DictionaryKind::Ipadic,
+            lex_type: crate::LexType::System,
+            word_cost: 0,
+            total_cost: 0,
+            overlay: false,
+            negated: false,
+        };
+        let mut tokens = vec![token];
+        MiddleDotFilter::new(MiddleDotMode::Split).filter(&mut tokens);
+        let parts = tokens
+            .iter()
+            .map(|t| (t.surface.as_str(), t.offset_from, t.char_from))
+            .collect::<Vec<_>>();
+        assert_eq!(parts, vec![("スミス", 0, 0), ("ジョーンズ", 12, 4)]);
+        assert_eq!(tokens[1].features().reading(), Some("ジョーンズ"));
+    }
+}
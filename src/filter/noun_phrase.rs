@@ -0,0 +1,141 @@
+use crate::features::DictionaryKind;
+use crate::filter::long_unit::noun_run_end;
+use crate::filter::predicate::is_adjective;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Replaces the morphemes with the noun phrases of the text, e.g. `この美味しいすもも`,
+/// `東京の大学` or `静かな街`, for fields like topics where phrase-level units match better than
+/// words.
+///
+/// A noun phrase is a run of nouns preceded by any number of adnominal modifiers (連体修飾):
+/// adnominals (`この`), adjectives in their attributive form (`美味しい`), adjectival nouns
+/// followed by `な` (`静かな`) and runs of nouns followed by the particle `の` (`東京の`). Each
+/// noun phrase is emitted as one token spanning all its words, and the other words are
+/// removed.
+#[derive(Debug, Clone, Default)]
+pub struct NounPhraseFilter;
+
+impl NounPhraseFilter {
+    /// Create a new `NounPhraseFilter`.
+    pub fn new() -> NounPhraseFilter {
+        NounPhraseFilter
+    }
+}
+
+fn is_adnominal(token: &MorphToken) -> bool {
+    token.features().pos().first() == Some(&"連体詞")
+}
+
+fn is_attributive_adjective(token: &MorphToken) -> bool {
+    let features = token.features();
+    is_adjective(token)
+        && match token.kind {
+            DictionaryKind::Ipadic => features.conjugation_form() == Some("基本形"),
+            DictionaryKind::Unidic => features
+                .conjugation_form()
+                .is_some_and(|f| f.starts_with("連体形") || f.starts_with("終止形")),
+        }
+}
+
+fn is_adjectival_noun(token: &MorphToken) -> bool {
+    let features = token.features();
+    match token.kind {
+        DictionaryKind::Ipadic => features.pos().starts_with(&["名詞", "形容動詞語幹"]),
+        DictionaryKind::Unidic => features.pos().first() == Some(&"形状詞"),
+    }
+}
+
+fn is_na(token: &MorphToken) -> bool {
+    token.surface == "な" && token.features().pos().first() == Some(&"助動詞")
+}
+
+fn is_no(token: &MorphToken) -> bool {
+    token.surface == "の" && token.features().pos().first() == Some(&"助詞")
+}
+
+/// Returns the end of the longest noun phrase starting at `start`, if there is one.
+fn phrase_end(tokens: &[&MorphToken], start: usize) -> Option<usize> {
+    let adjacent = |i: usize| i < tokens.len() && tokens[i - 1].char_to == tokens[i].char_from;
+    let mut end = start;
+    let mut longest = None;
+    while end < tokens.len() && (end == start || adjacent(end)) {
+        if let Some(run_end) = noun_run_end(tokens, end) {
+            longest = Some(run_end);
+            let linked = adjacent(run_end)
+                && (is_no(tokens[run_end])
+                    || is_na(tokens[run_end]) && is_adjectival_noun(tokens[run_end - 1]));
+            if !linked {
+                break;
+            }
+            end = run_end + 1;
+        } else if is_adjectival_noun(tokens[end]) && adjacent(end + 1) && is_na(tokens[end + 1]) {
+            end += 2;
+        } else if is_adnominal(tokens[end]) || is_attributive_adjective(tokens[end]) {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    longest
+}
+
+impl MorphFilter for NounPhraseFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let primary = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+        let mut phrases = vec![];
+        let mut i = 0;
+        while i < primary.len() {
+            let Some(end) = phrase_end(&primary, i) else {
+                i += 1;
+                continue;
+            };
+            let words = &primary[i..end];
+            let (first, last) = (words[0], words[words.len() - 1]);
+            phrases.push(MorphToken {
+                text: words.iter().map(|t| t.text.as_str()).collect(),
+                surface: words.iter().map(|t| t.surface.as_str()).collect(),
+                offset_to: last.offset_to,
+                char_to: last.char_to,
+                feature: last.feature.clone(),
+                total_cost: last.total_cost,
+                ..first.clone()
+            });
+            i = end;
+        }
+        *tokens = phrases;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn chunks() {
+        let tokenizer = test_tokenizer();
+        let chunks = |text: &str| {
+            let mut tokens = tokenizer.morphemes(text);
+            NounPhraseFilter::new().filter(&mut tokens);
+            tokens
+                .into_iter()
+                .map(|t| (t.text, t.char_from, t.char_to))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            chunks("この美味しいすもも"),
+            vec![("この美味しいすもも".to_string(), 0, 9)]
+        );
+        assert_eq!(
+            chunks("私は東京の大学に行きました"),
+            vec![("東京の大学".to_string(), 2, 7)]
+        );
+        assert_eq!(
+            chunks("静かな街の新東京大学"),
+            vec![("静かな街の新東京大学".to_string(), 0, 10)]
+        );
+        assert!(chunks("行きました").is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+const KANJI_DIGITS: &str = "〇一二三四五六七八九";
+
+/// Kanji multipliers below 10,000 and their exponents.
+const SMALL_UNITS: [(char, u32); 3] = [('十', 1), ('百', 2), ('千', 3)];
+
+/// Kanji multipliers of groups of four digits and their exponents.
+const LARGE_UNITS: [(char, u32); 3] = [('万', 4), ('億', 8), ('兆', 12)];
+
+/// Normalizes numbers written with kanji numerals or fullwidth digits to ASCII digits, e.g.
+/// `一千二百` to `1200`, `２０２４` to `2024` and `3万` to `30000`, like Lucene's
+/// `JapaneseNumberFilter`.
+///
+/// Adjacent words made of numerals are merged first, since Vibrato often splits numbers into
+/// several words. Thousands separators are removed and decimal numbers, e.g. `１．５`, are
+/// kept as they are with ASCII digits. Numbers too large for 128 bits are left alone.
+#[derive(Debug, Clone, Default)]
+pub struct JapaneseNumberFilter;
+
+impl JapaneseNumberFilter {
+    /// Create a new `JapaneseNumberFilter`.
+    pub fn new() -> JapaneseNumberFilter {
+        JapaneseNumberFilter
+    }
+}
+
+fn digit(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => Some(c as u32 - '0' as u32),
+        '０'..='９' => Some(c as u32 - '０' as u32),
+        _ => KANJI_DIGITS.chars().position(|k| k == c).map(|d| d as u32),
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ',' | '，' | '.' | '．')
+}
+
+fn is_numeral(c: char) -> bool {
+    digit(c).is_some()
+        || SMALL_UNITS.iter().any(|(u, _)| *u == c)
+        || LARGE_UNITS.iter().any(|(u, _)| *u == c)
+}
+
+fn is_number(token: &MorphToken) -> bool {
+    !token.overlay && token.surface.chars().all(is_numeral)
+}
+
+fn is_separator_token(token: &MorphToken) -> bool {
+    !token.overlay && token.surface.chars().count() == 1 && token.surface.chars().all(is_separator)
+}
+
+/// Returns the ASCII digits of the number `text`, or `None` if it is not a number.
+fn normalize(text: &str) -> Option<String> {
+    let text = text.replace([',', '，'], "");
+    if let Some((integer, fraction)) = text.split_once(['.', '．']) {
+        let digits = |s: &str| {
+            (!s.is_empty())
+                .then(|| {
+                    s.chars()
+                        .map(|c| digit(c).and_then(|d| char::from_digit(d, 10)))
+                        .collect::<Option<String>>()
+                })
+                .flatten()
+        };
+        let integer = digits(integer)?.trim_start_matches('0').to_string();
+        let integer = if integer.is_empty() { "0" } else { &integer };
+        return Some(format!("{integer}.{}", digits(fraction)?));
+    }
+
+    let mut total: u128 = 0;
+    let mut section: u128 = 0;
+    let mut current: Option<u128> = None;
+    for c in text.chars() {
+        if let Some(d) = digit(c) {
+            current = Some(
+                current
+                    .unwrap_or(0)
+                    .checked_mul(10)?
+                    .checked_add(u128::from(d))?,
+            );
+        } else if let Some((_, exp)) = SMALL_UNITS.iter().find(|(u, _)| *u == c) {
+            let value = current.take().unwrap_or(1).checked_mul(10u128.pow(*exp))?;
+            section = section.checked_add(value)?;
+        } else if let Some((_, exp)) = LARGE_UNITS.iter().find(|(u, _)| *u == c) {
+            let group = section.checked_add(current.take().unwrap_or(0))?;
+            let group = if group == 0 { 1 } else { group };
+            total = total.checked_add(group.checked_mul(10u128.pow(*exp))?)?;
+            section = 0;
+        } else {
+            return None;
+        }
+    }
+    let value = total
+        .checked_add(section)?
+        .checked_add(current.unwrap_or(0))?;
+    Some(value.to_string())
+}
+
+impl MorphFilter for JapaneseNumberFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            if !is_number(&tokens[i]) {
+                filtered.push(tokens[i].clone());
+                i += 1;
+                continue;
+            }
+            // Merge the adjacent numbers, with single separators between them.
+            let mut end = i + 1;
+            while end < tokens.len() && tokens[end - 1].offset_to == tokens[end].offset_from {
+                if is_number(&tokens[end]) {
+                    end += 1;
+                } else if is_separator_token(&tokens[end])
+                    && tokens.get(end + 1).is_some_and(|next| {
+                        is_number(next) && tokens[end].offset_to == next.offset_from
+                    })
+                {
+                    end += 2;
+                } else {
+                    break;
+                }
+            }
+            let run = &tokens[i..end];
+            let surface = run.iter().map(|t| t.surface.as_str()).collect::<String>();
+            match normalize(&surface) {
+                Some(number) => {
+                    let last = &run[run.len() - 1];
+                    filtered.push(MorphToken {
+                        text: number,
+                        surface,
+                        offset_to: last.offset_to,
+                        char_to: last.char_to,
+                        total_cost: last.total_cost,
+                        ..run[0].clone()
+                    });
+                }
+                None => filtered.extend(run.iter().cloned()),
+            }
+            i = end;
+        }
+        *tokens = filtered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(normalize("一千二百").as_deref(), Some("1200"));
+        assert_eq!(normalize("千二百").as_deref(), Some("1200"));
+        assert_eq!(normalize("２０２４").as_deref(), Some("2024"));
+        assert_eq!(normalize("二〇二四").as_deref(), Some("2024"));
+        assert_eq!(normalize("3万").as_deref(), Some("30000"));
+        assert_eq!(normalize("一億二千万").as_deref(), Some("120000000"));
+        assert_eq!(normalize("1,234").as_deref(), Some("1234"));
+        assert_eq!(normalize("１．５").as_deref(), Some("1.5"));
+        assert_eq!(normalize(&"9".repeat(40)), None);
+
+        let tokenizer = test_tokenizer();
+        let texts = |text: &str| {
+            let mut tokens = tokenizer.morphemes(text);
+            JapaneseNumberFilter::new().filter(&mut tokens);
+            tokens
+                .into_iter()
+                .map(|t| (t.text, t.offset_from, t.offset_to))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("東京に一千二百の")[2], ("1200".to_string(), 9, 21));
+        assert_eq!(texts("東京に1,234.5の")[2], ("1234.5".to_string(), 9, 16));
+    }
+}
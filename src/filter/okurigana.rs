@@ -0,0 +1,143 @@
+use crate::chars::is_kanji;
+use crate::features::DictionaryKind;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Normalizes okurigana spelling variants to a canonical form, so that e.g. `引越し`,
+/// `引っ越し` and `引越` match each other.
+///
+/// With UniDic, a word is rewritten to its lemma when both are spelled with the same kanji,
+/// i.e. when they only differ by their okurigana. Inflected words are rewritten to their lemma
+/// as well, since it is the only canonical spelling in the dictionary.
+///
+/// IPADIC has no lemma field, so the okurigana (the hiragana following a kanji) are removed
+/// from nouns instead: all three words above become `引越`. Verbs and adjectives are left
+/// untouched, so that e.g. `行く` and `行う` stay distinct.
+#[derive(Debug, Clone, Default)]
+pub struct OkuriganaFilter;
+
+impl OkuriganaFilter {
+    /// Create a new `OkuriganaFilter`.
+    pub fn new() -> OkuriganaFilter {
+        OkuriganaFilter
+    }
+
+    fn normalize(&self, token: &MorphToken) -> Option<String> {
+        let skeleton = kanji(&token.text);
+        if skeleton.is_empty() {
+            return None;
+        }
+        let features = token.features();
+        let normalized = match token.kind {
+            DictionaryKind::Unidic => features
+                .lemma()
+                .filter(|lemma| kanji(lemma) == skeleton)?
+                .to_string(),
+            DictionaryKind::Ipadic if features.pos().first() == Some(&"名詞") => {
+                strip_okurigana(&token.text)
+            }
+            DictionaryKind::Ipadic => return None,
+        };
+        (normalized != token.text).then_some(normalized)
+    }
+}
+
+impl MorphFilter for OkuriganaFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        for token in tokens {
+            if let Some(normalized) = self.normalize(token) {
+                token.text = normalized;
+            }
+        }
+    }
+}
+
+fn is_hiragana(c: char) -> bool {
+    ('\u{3041}'..='\u{309F}').contains(&c)
+}
+
+fn kanji(text: &str) -> String {
+    text.chars().filter(|&c| is_kanji(c)).collect()
+}
+
+fn strip_okurigana(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut after_kanji = false;
+    for c in text.chars() {
+        if is_kanji(c) {
+            after_kanji = true;
+        } else if is_hiragana(c) && after_kanji {
+            continue;
+        } else {
+            after_kanji = false;
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn ipadic() {
+        let tokenizer = test_tokenizer();
+        for text in ["引越し", "引っ越し", "引越"] {
+            let mut tokens = tokenizer.morphemes(text);
+            OkuriganaFilter::new().filter(&mut tokens);
+            assert_eq!(tokens.len(), 1);
+            assert_eq!(tokens[0].text, "引越");
+        }
+
+        let mut tokens = tokenizer.morphemes("すもも");
+        OkuriganaFilter::new().filter(&mut tokens);
+        assert_eq!(tokens[0].text, "すもも");
+
+        // Verbs and adjectives are not okurigana variants of the nouns of the same kanji.
+        let filter = OkuriganaFilter::new();
+        let mut token = tokenizer.morphemes("引越し").remove(0);
+        for (text, feature) in [
+            (
+                "行く",
+                "動詞,自立,*,*,五段・カ行促音便,基本形,行く,イク,イク",
+            ),
+            (
+                "行う",
+                "動詞,自立,*,*,五段・ワ行促音便,基本形,行う,オコナウ,オコナウ",
+            ),
+            (
+                "高い",
+                "形容詞,自立,*,*,形容詞・アウオ段,基本形,高い,タカイ,タカイ",
+            ),
+        ] {
+            token.text = text.to_string();
+            token.feature = feature.to_string();
+            assert_eq!(filter.normalize(&token), None);
+        }
+    }
+
+    #[test]
+    fn unidic() {
+        let filter = OkuriganaFilter::new();
+        let mut token = test_tokenizer().morphemes("引越し").remove(0);
+        token.kind = DictionaryKind::Unidic;
+        token.feature =
+            "名詞,普通名詞,サ変可能,*,*,*,ヒッコシ,引っ越し,引越し,ヒッコシ,引越し,ヒッコシ,和"
+                .to_string();
+        assert_eq!(filter.normalize(&token).as_deref(), Some("引っ越し"));
+
+        // Not an okurigana variant.
+        token.feature = "名詞,普通名詞,一般,*,*,*,カラダ,体,身体,カラダ,身体,カラダ,和".to_string();
+        token.text = "身体".to_string();
+        assert_eq!(filter.normalize(&token), None);
+    }
+
+    #[test]
+    fn okurigana() {
+        assert_eq!(strip_okurigana("引っ越し"), "引越");
+        assert_eq!(strip_okurigana("お引越し"), "お引越");
+        assert_eq!(strip_okurigana("取り扱い"), "取扱");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::features::DictionaryKind;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Adds the orthographic base form (`orthBase`) and the lemma of UniDic words at the position
+/// of their surface.
+///
+/// The orthographic base form undoes the inflection in the spelling of the surface, e.g.
+/// `食べる` for `食べ`, and the lemma also normalizes the spelling, so that `りんご`, `リンゴ` and
+/// `林檎` share the term `林檎`. The surface is kept, and each distinct form is added as an
+/// overlay token. The English gloss of the lemmas of loanwords (`ページ-page`) is removed.
+///
+/// IPADIC has neither field, so this filter does nothing for IPADIC dictionaries.
+#[derive(Debug, Clone)]
+pub struct OrthographicVariantFilter {
+    lemma: bool,
+}
+
+impl OrthographicVariantFilter {
+    /// Create a new `OrthographicVariantFilter` adding both forms.
+    pub fn new() -> OrthographicVariantFilter {
+        OrthographicVariantFilter { lemma: true }
+    }
+
+    /// Whether to add the lemma besides the orthographic base form. Enabled by default.
+    pub fn lemma(mut self, lemma: bool) -> OrthographicVariantFilter {
+        self.lemma = lemma;
+        self
+    }
+
+    fn variants(&self, token: &MorphToken) -> Vec<String> {
+        let features = token.features();
+        let mut variants: Vec<String> = vec![];
+        let forms = [
+            features.base_form(),
+            features.lemma().filter(|_| self.lemma).map(strip_gloss),
+        ];
+        for form in forms.into_iter().flatten() {
+            if form != token.text && !variants.iter().any(|v| v == form) {
+                variants.push(form.to_string());
+            }
+        }
+        variants
+    }
+}
+
+impl Default for OrthographicVariantFilter {
+    fn default() -> OrthographicVariantFilter {
+        OrthographicVariantFilter::new()
+    }
+}
+
+fn strip_gloss(lemma: &str) -> &str {
+    match lemma.split_once('-') {
+        Some((stem, gloss)) if !stem.is_empty() && gloss.is_ascii() => stem,
+        _ => lemma,
+    }
+}
+
+impl MorphFilter for OrthographicVariantFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for token in tokens.drain(..) {
+            let variants = if token.kind == DictionaryKind::Unidic {
+                self.variants(&token)
+            } else {
+                vec![]
+            };
+            let overlays = variants
+                .into_iter()
+                .map(|text| MorphToken {
+                    text,
+                    overlay: true,
+                    ..token.clone()
+                })
+                .collect::<Vec<_>>();
+            filtered.push(token);
+            filtered.extend(overlays);
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        format!("lemma={}", self.lemma)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    fn unidic(surface: &str, feature: &str) -> Vec<MorphToken> {
+        let mut token = test_tokenizer().morphemes("すもも").remove(0);
+        token.text = surface.to_string();
+        token.surface = surface.to_string();
+        token.kind = DictionaryKind::Unidic;
+        token.feature = feature.to_string();
+        vec![token]
+    }
+
+    fn texts(tokens: &[MorphToken]) -> Vec<(&str, bool)> {
+        tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay))
+            .collect()
+    }
+
+    #[test]
+    fn variants() {
+        let filter = OrthographicVariantFilter::new();
+
+        let mut tokens = unidic(
+            "りんご",
+            "名詞,普通名詞,一般,*,*,*,リンゴ,林檎,りんご,リンゴ,りんご,リンゴ,和",
+        );
+        filter.filter(&mut tokens);
+        assert_eq!(texts(&tokens), vec![("りんご", false), ("林檎", true)]);
+
+        let mut tokens = unidic(
+            "食べ",
+            "動詞,一般,*,*,下一段-バ行,連用形-一般,タベル,食べる,食べ,タベ,食べる,タベル,和",
+        );
+        filter.filter(&mut tokens);
+        assert_eq!(texts(&tokens), vec![("食べ", false), ("食べる", true)]);
+
+        let mut tokens = unidic(
+            "ぺーじ",
+            "名詞,普通名詞,一般,*,*,*,ページ,ページ-page,ぺーじ,ページ,ぺーじ,ページ,外",
+        );
+        OrthographicVariantFilter::new()
+            .lemma(false)
+            .filter(&mut tokens);
+        assert_eq!(texts(&tokens), vec![("ぺーじ", false)]);
+        OrthographicVariantFilter::new().filter(&mut tokens);
+        assert_eq!(texts(&tokens), vec![("ぺーじ", false), ("ページ", true)]);
+    }
+
+    #[test]
+    fn ipadic() {
+        let mut tokens = test_tokenizer().morphemes("行きました");
+        let before = tokens.clone();
+        OrthographicVariantFilter::new().filter(&mut tokens);
+        assert_eq!(tokens, before);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::filter::long_unit::combine;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Adds full person names, e.g. `山田太郎`, on top of the surname and the given name they are
+/// segmented into, so that people are found by their full name as well as by either part.
+///
+/// A full name is a surname (`名詞,固有名詞,人名,姓`) directly followed by a given name
+/// (`人名,名`). Its feature string is the one of the given name tagged as `人名,一般`, with the
+/// readings of both parts concatenated. Names missing from the dictionary can be added with
+/// [`Gazetteer::surname`](crate::Gazetteer::surname) and
+/// [`Gazetteer::given_name`](crate::Gazetteer::given_name), whose costs are tuned so that they
+/// are segmented as such:
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy_vibrato::filter::PersonNameFilter;
+/// use tantivy_vibrato::{Gazetteer, VibratoTokenizer};
+///
+/// let names = Gazetteer::new().surname("小鳥遊").given_name("星歌");
+/// let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?
+///     .gazetteer(&names)?
+///     .filter(PersonNameFilter::new());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PersonNameFilter;
+
+impl PersonNameFilter {
+    /// Create a new `PersonNameFilter`.
+    pub fn new() -> PersonNameFilter {
+        PersonNameFilter
+    }
+}
+
+fn is_name(token: &MorphToken, part: &str) -> bool {
+    token
+        .features()
+        .pos()
+        .starts_with(&["名詞", "固有名詞", "人名", part])
+}
+
+impl MorphFilter for PersonNameFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let primary = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+        let mut names = vec![];
+        for pair in primary.windows(2) {
+            let (surname, given_name) = (pair[0], pair[1]);
+            if surname.char_to != given_name.char_from
+                || !is_name(surname, "姓")
+                || !is_name(given_name, "名")
+            {
+                continue;
+            }
+            let mut name = combine(pair);
+            let mut fields = name.feature_fields();
+            if fields.len() > 3 {
+                fields[3] = "一般";
+                name.feature = fields.join(",");
+            }
+            names.push(name);
+        }
+        if !names.is_empty() {
+            tokens.extend(names);
+            tokens.sort_by_key(|t| (t.char_from, t.overlay));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::Gazetteer;
+
+    #[test]
+    fn full_names() {
+        let tokenizer = test_tokenizer();
+        let mut tokens = tokenizer.morphemes("山田太郎と山田");
+        PersonNameFilter::new().filter(&mut tokens);
+
+        let texts = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay, t.char_from))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                ("山田", false, 0),
+                ("山田太郎", true, 0),
+                ("太郎", false, 2),
+                ("と", false, 4),
+                ("山田", false, 5),
+            ]
+        );
+        assert_eq!(
+            tokens[1].feature,
+            "名詞,固有名詞,人名,一般,*,*,山田太郎,ヤマダタロウ,ヤマダタロウ"
+        );
+
+        let names = Gazetteer::new().surname("鈴木").given_name("一郎");
+        let tokenizer = tokenizer.gazetteer(&names).unwrap();
+        let mut tokens = tokenizer.morphemes("鈴木一郎");
+        PersonNameFilter::new().filter(&mut tokens);
+        let texts = tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["鈴木", "鈴木一郎", "一郎"]);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::filter::MorphFilter;
+use crate::sort_key::to_katakana;
+use crate::token::MorphToken;
+
+/// Voiced, semi-voiced and small kana, with the kana they are folded into.
+const FOLDED: &[(&str, &str)] = &[
+    ("ガギグゲゴ", "カキクケコ"),
+    ("ザジズゼゾ", "サシスセソ"),
+    ("ダヂヅデド", "タシステト"),
+    ("バビブベボ", "ハヒフヘホ"),
+    ("パピプペポ", "ハヒフヘホ"),
+    ("ァィゥェォヵヶャュョヮヴヲ", "アイウエオカケヤユヨワウオ"),
+];
+
+/// Kana of each vowel, after folding.
+const VOWELS: &[(char, &str)] = &[
+    ('a', "アカサタナハマヤラワ"),
+    ('i', "イキシチニヒミリ"),
+    ('u', "ウクスツヌフムユル"),
+    ('e', "エケセテネヘメレ"),
+    ('o', "オコソトノホモヨロ"),
+];
+
+/// Adds a phonetic key of the reading of the words, for fuzzy matching of names and of their
+/// variant spellings and transliterations in a dedicated field.
+///
+/// The key is the reading in katakana with the voiced and semi-voiced kana folded into their
+/// unvoiced kana, the small kana into their normal kana, and the long vowels collapsed, e.g.
+/// `トキヨ` for both `トウキョウ` and `トーキョー`, or `ハクタ` for both `ハクタ` and `バグダ`.
+/// Words without a reading are kept as is, unless they are written in kana.
+#[derive(Debug, Clone)]
+pub struct PhoneticKeyFilter {
+    keep_original: bool,
+}
+
+impl PhoneticKeyFilter {
+    /// Create a filter adding the keys as overlays on the words.
+    pub fn new() -> PhoneticKeyFilter {
+        PhoneticKeyFilter {
+            keep_original: true,
+        }
+    }
+
+    /// Whether to keep the words besides their keys. Enabled by default; when disabled, the
+    /// text of the words is replaced with their keys.
+    pub fn keep_original(mut self, keep_original: bool) -> PhoneticKeyFilter {
+        self.keep_original = keep_original;
+        self
+    }
+}
+
+impl Default for PhoneticKeyFilter {
+    fn default() -> PhoneticKeyFilter {
+        PhoneticKeyFilter::new()
+    }
+}
+
+/// Returns the phonetic key of `token`, if it has a reading.
+fn key(token: &MorphToken) -> Option<String> {
+    let features = token.features();
+    let reading = match features.reading() {
+        Some(reading) if reading != "*" => reading.to_string(),
+        _ if token.surface.chars().all(is_kana) => to_katakana(&token.surface),
+        _ => return None,
+    };
+    let key = phonetic_key(&reading);
+    (!key.is_empty()).then_some(key)
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}' | 'ー')
+}
+
+fn fold(c: char) -> char {
+    FOLDED
+        .iter()
+        .find_map(|(from, to)| {
+            from.chars()
+                .position(|f| f == c)
+                .map(|i| to.chars().nth(i).unwrap())
+        })
+        .unwrap_or(c)
+}
+
+fn vowel(c: char) -> Option<char> {
+    VOWELS
+        .iter()
+        .find(|(_, kana)| kana.contains(c))
+        .map(|(vowel, _)| *vowel)
+}
+
+/// Returns the phonetic key of the katakana `reading`.
+fn phonetic_key(reading: &str) -> String {
+    let mut key = String::with_capacity(reading.len());
+    let mut last_vowel = None;
+    for c in reading.chars().filter(|&c| c != 'ー' && c != 'ッ') {
+        let c = fold(c);
+        let extends = match (last_vowel, c) {
+            (Some(v), 'ア') => v == 'a',
+            (Some(v), 'イ') => v == 'i' || v == 'e',
+            (Some(v), 'ウ') => v == 'u' || v == 'o',
+            (Some(v), 'エ') => v == 'e',
+            (Some(v), 'オ') => v == 'o',
+            _ => false,
+        };
+        if !extends {
+            key.push(c);
+            last_vowel = vowel(c);
+        }
+    }
+    key
+}
+
+impl MorphFilter for PhoneticKeyFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            let Some(key) = key(&token).filter(|k| *k != token.text) else {
+                filtered.push(token);
+                continue;
+            };
+            if self.keep_original {
+                let overlay = MorphToken {
+                    text: key,
+                    overlay: true,
+                    ..token.clone()
+                };
+                filtered.push(token);
+                filtered.push(overlay);
+            } else {
+                token.text = key;
+                filtered.push(token);
+            }
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        format!("keep_original={}", self.keep_original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn keys() {
+        assert_eq!(phonetic_key("トウキョウ"), "トキヨ");
+        assert_eq!(phonetic_key("トーキョー"), "トキヨ");
+        assert_eq!(phonetic_key("バクダ"), "ハクタ");
+        assert_eq!(phonetic_key("バグダ"), "ハクタ");
+        assert_eq!(phonetic_key("ハカタ"), "ハカタ");
+        assert_eq!(phonetic_key("ジェイソン"), "シエソン");
+        assert_eq!(phonetic_key("ヂョッキ"), "シヨキ");
+
+        let tokenizer = test_tokenizer();
+        let texts = |filter: PhoneticKeyFilter| {
+            let mut tokens = tokenizer.morphemes("山田タロー");
+            filter.filter(&mut tokens);
+            tokens
+                .into_iter()
+                .map(|t| (t.text, t.overlay))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts(PhoneticKeyFilter::new()),
+            vec![
+                ("山田".to_string(), false),
+                ("ヤマタ".to_string(), true),
+                ("タロー".to_string(), false),
+                ("タロ".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            texts(PhoneticKeyFilter::new().keep_original(false))
+                .into_iter()
+                .map(|(text, _)| text)
+                .collect::<Vec<_>>(),
+            vec!["ヤマタ", "タロ"]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use crate::features::DictionaryKind;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Part-of-speech tags removed by Lucene's `JapanesePartOfSpeechStopFilter` by default
+/// (`stoptags.txt`).
+pub(crate) const KUROMOJI_STOP_TAGS: &[&str] = &[
+    "接続詞",
+    "助詞",
+    "助詞-格助詞",
+    "助詞-格助詞-一般",
+    "助詞-格助詞-引用",
+    "助詞-格助詞-連語",
+    "助詞-接続助詞",
+    "助詞-係助詞",
+    "助詞-副助詞",
+    "助詞-間投助詞",
+    "助詞-並立助詞",
+    "助詞-終助詞",
+    "助詞-副助詞／並立助詞／終助詞",
+    "助詞-連体化",
+    "助詞-副詞化",
+    "助詞-特殊",
+    "助動詞",
+    "記号",
+    "記号-一般",
+    "記号-読点",
+    "記号-句点",
+    "記号-空白",
+    "記号-括弧開",
+    "記号-括弧閉",
+    "その他-間投",
+    "フィラー",
+    "非言語音",
+];
+
+/// Particles, auxiliary verbs, symbols and blanks of UniDic, the equivalent of
+/// [`KUROMOJI_STOP_TAGS`].
+pub(crate) const UNIDIC_STOP_TAGS: &[&str] = &[
+    "助詞",
+    "助詞-格助詞",
+    "助詞-係助詞",
+    "助詞-副助詞",
+    "助詞-接続助詞",
+    "助詞-終助詞",
+    "助詞-準体助詞",
+    "助動詞",
+    "補助記号",
+    "補助記号-一般",
+    "補助記号-句点",
+    "補助記号-読点",
+    "補助記号-括弧開",
+    "補助記号-括弧閉",
+    "補助記号-ＡＡ-一般",
+    "補助記号-ＡＡ-顔文字",
+    "記号",
+    "記号-一般",
+    "記号-文字",
+    "空白",
+];
+
+/// Removes tokens whose part-of-speech tag is one of the stop tags, like Lucene's
+/// `JapanesePartOfSpeechStopFilter`, e.g. the particles, auxiliary verbs and symbols.
+///
+/// Tags are the part-of-speech fields of the feature string joined with `-`, without the `*`
+/// fields, e.g. `助詞-格助詞-一般` for IPADIC or `助詞-格助詞` for UniDic, and must match the
+/// tag of a token exactly.
+#[derive(Debug, Clone)]
+pub struct PosFilter {
+    stop_tags: HashSet<String>,
+}
+
+impl PosFilter {
+    /// Create a filter removing the tokens tagged with one of `stop_tags`.
+    pub fn new<I, S>(stop_tags: I) -> PosFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        PosFilter {
+            stop_tags: stop_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Create a filter removing the particles, auxiliary verbs, symbols and blanks of the
+    /// dictionary `kind`: Lucene's default stop tags (`stoptags.txt`) for IPADIC, and their
+    /// equivalent for UniDic.
+    pub fn for_dictionary(kind: DictionaryKind) -> PosFilter {
+        match kind {
+            DictionaryKind::Ipadic => PosFilter::new(KUROMOJI_STOP_TAGS.iter().copied()),
+            DictionaryKind::Unidic => PosFilter::new(UNIDIC_STOP_TAGS.iter().copied()),
+        }
+    }
+
+    fn is_stop(&self, token: &MorphToken) -> bool {
+        self.stop_tags.contains(&token.features().pos_tag())
+    }
+}
+
+impl MorphFilter for PosFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        tokens.retain(|t| !self.is_stop(t));
+    }
+
+    fn config(&self) -> String {
+        let mut stop_tags = self
+            .stop_tags
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        stop_tags.sort_unstable();
+        stop_tags.join("\n")
+    }
+
+    fn reason(&self, token: &MorphToken) -> Option<String> {
+        self.is_stop(token)
+            .then(|| format!("stop tag {}", token.features().pos_tag()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn stop_tags() {
+        let tokenizer = test_tokenizer();
+        let filter = PosFilter::for_dictionary(tokenizer.dictionary_kind());
+        let texts = tokenizer
+            .filter(filter)
+            .analyze("私は東京に行きました。", None)
+            .into_iter()
+            .map(|t| t.text)
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["私", "東京", "行き"]);
+    }
+}
@@ -0,0 +1,107 @@
+//! Classification of the words of predicates, shared by the filters working on them.
+use crate::features::DictionaryKind;
+use crate::token::MorphToken;
+
+pub(crate) fn is_main_verb(token: &MorphToken) -> bool {
+    let features = token.features();
+    let pos = features.pos();
+    match token.kind {
+        DictionaryKind::Ipadic => pos.starts_with(&["動詞", "自立"]),
+        DictionaryKind::Unidic => pos.starts_with(&["動詞", "一般"]),
+    }
+}
+
+pub(crate) fn is_auxiliary_verb(token: &MorphToken) -> bool {
+    let features = token.features();
+    let pos = features.pos();
+    match token.kind {
+        DictionaryKind::Ipadic => pos.starts_with(&["動詞", "非自立"]),
+        DictionaryKind::Unidic => pos.starts_with(&["動詞", "非自立可能"]),
+    }
+}
+
+pub(crate) fn is_te(token: &MorphToken) -> bool {
+    matches!(token.surface.as_str(), "て" | "で")
+        && token.features().pos().starts_with(&["助詞", "接続助詞"])
+}
+
+pub(crate) fn is_auxiliary(token: &MorphToken) -> bool {
+    token.features().pos().first() == Some(&"助動詞")
+}
+
+pub(crate) fn is_adjective(token: &MorphToken) -> bool {
+    let features = token.features();
+    let pos = features.pos();
+    match token.kind {
+        DictionaryKind::Ipadic => pos.starts_with(&["形容詞", "自立"]),
+        DictionaryKind::Unidic => pos.starts_with(&["形容詞", "一般"]),
+    }
+}
+
+/// Verbs and adjectives used as suffixes of predicates, e.g. `れる`, `させる`, `ない`.
+pub(crate) fn is_predicate_suffix(token: &MorphToken) -> bool {
+    let features = token.features();
+    let pos = features.pos();
+    match token.kind {
+        DictionaryKind::Ipadic => {
+            pos.starts_with(&["動詞", "接尾"]) || pos.starts_with(&["形容詞", "非自立"])
+        }
+        DictionaryKind::Unidic => {
+            pos.starts_with(&["接尾辞", "動詞的"])
+                || pos.starts_with(&["接尾辞", "形容詞的"])
+                || pos.starts_with(&["形容詞", "非自立可能"])
+        }
+    }
+}
+
+/// Conjunctive particles linking a predicate to a following auxiliary verb.
+fn is_conjunctive(token: &MorphToken) -> bool {
+    matches!(token.surface.as_str(), "て" | "で" | "ば")
+        && token.features().pos().starts_with(&["助詞", "接続助詞"])
+}
+
+/// Returns the end of the chain of auxiliaries following the predicate at `start`: the
+/// auxiliary words, the auxiliary verbs and adjectives, and the `て`/`で`/`ば` particles
+/// followed by an auxiliary verb.
+pub(crate) fn chain_end(tokens: &[MorphToken], start: usize) -> usize {
+    let adjacent = |i: usize| {
+        tokens
+            .get(i)
+            .filter(|t| !t.overlay && t.char_from == tokens[i - 1].char_to)
+    };
+    let mut end = start + 1;
+    while let Some(token) = adjacent(end) {
+        if is_auxiliary(token) || is_auxiliary_verb(token) || is_predicate_suffix(token) {
+            end += 1;
+        } else if is_conjunctive(token) && adjacent(end + 1).is_some_and(is_auxiliary_verb) {
+            end += 2;
+        } else {
+            break;
+        }
+    }
+    end
+}
+
+fn is_negation(token: &MorphToken) -> bool {
+    let features = token.features();
+    let lemma = features.lemma().or(features.base_form());
+    (is_auxiliary(token) || is_predicate_suffix(token))
+        && matches!(lemma, Some("ない" | "無い" | "ぬ" | "ず" | "ん"))
+}
+
+/// Set [`MorphToken::negated`] on the verbs and adjectives followed by an odd number of
+/// negation auxiliaries.
+pub(crate) fn tag_negation(tokens: &mut [MorphToken]) {
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if token.overlay || !(is_main_verb(token) || is_adjective(token)) {
+            i += 1;
+            continue;
+        }
+        let end = chain_end(tokens, i);
+        let negations = tokens[i + 1..end].iter().filter(|t| is_negation(t)).count();
+        tokens[i].negated = negations % 2 == 1;
+        i = end;
+    }
+}
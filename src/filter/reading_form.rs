@@ -0,0 +1,116 @@
+use crate::filter::romaji::to_romaji;
+use crate::filter::MorphFilter;
+use crate::sort_key::to_katakana;
+use crate::token::MorphToken;
+
+/// Replaces the text of the words with their reading in katakana, like Lucene's
+/// `JapaneseReadingFormFilter`, e.g. to index a secondary field where `豊富` and `ホウフ` match.
+///
+/// The reading is the one of the entry selected in context. Words without a reading keep their
+/// surface, converted to katakana if it is written in hiragana.
+///
+/// In [`romaji`](Self::romaji) mode, the reading is transliterated into Hepburn romaji, so that
+/// queries typed in ASCII match Japanese words.
+#[derive(Debug, Clone, Default)]
+pub struct ReadingFormFilter {
+    pronunciation: bool,
+    romaji: bool,
+}
+
+impl ReadingFormFilter {
+    /// Create a filter replacing the text with the reading.
+    pub fn new() -> ReadingFormFilter {
+        ReadingFormFilter::default()
+    }
+
+    /// Whether to use the pronunciation rather than the reading, e.g. `トーキョー` rather than
+    /// `トウキョウ` for `東京`. Disabled by default.
+    pub fn pronunciation(mut self, pronunciation: bool) -> ReadingFormFilter {
+        self.pronunciation = pronunciation;
+        self
+    }
+
+    /// Whether to transliterate the readings into Hepburn romaji, e.g. `toukyou` for `東京`,
+    /// with the spelling with short vowels added as an overlay when it differs, e.g. `tokyo`,
+    /// so that both spellings of a query match. Disabled by default.
+    pub fn romaji(mut self, romaji: bool) -> ReadingFormFilter {
+        self.romaji = romaji;
+        self
+    }
+
+    /// Returns the reading of `token`, if it differs from its text.
+    fn reading(&self, token: &MorphToken) -> Option<String> {
+        let features = token.features();
+        let reading = if self.pronunciation {
+            features.pronunciation()
+        } else {
+            features.reading()
+        };
+        let reading = reading.map_or_else(|| to_katakana(&token.text), str::to_string);
+        (reading != token.text).then_some(reading)
+    }
+}
+
+impl MorphFilter for ReadingFormFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        if !self.romaji {
+            for token in tokens {
+                if let Some(reading) = self.reading(token) {
+                    token.text = reading;
+                }
+            }
+            return;
+        }
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            let reading = self.reading(&token).unwrap_or_else(|| token.text.clone());
+            let short = to_romaji(&reading, true);
+            token.text = to_romaji(&reading, false);
+            let overlay = (short != token.text).then(|| MorphToken {
+                text: short,
+                overlay: true,
+                ..token.clone()
+            });
+            filtered.push(token);
+            filtered.extend(overlay);
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        format!(
+            "pronunciation={} romaji={}",
+            self.pronunciation, self.romaji
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn readings() {
+        let texts = |filter: ReadingFormFilter| {
+            test_tokenizer()
+                .filter(filter)
+                .analyze("東京のすももとChatGPT", None)
+                .into_iter()
+                .map(|t| t.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts(ReadingFormFilter::new()),
+            vec!["トウキョウ", "ノ", "スモモ", "ト", "ChatGPT"]
+        );
+        assert_eq!(
+            texts(ReadingFormFilter::new().pronunciation(true))[0],
+            "トーキョー"
+        );
+        assert_eq!(
+            texts(ReadingFormFilter::new().romaji(true)),
+            vec!["toukyou", "tokyo", "no", "sumomo", "to", "ChatGPT"]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Transliteration of kana into Hepburn romaji.
+use crate::sort_key::to_katakana;
+
+/// Consonants of the kana of the `i` column combined with small `ャ`, `ュ`, `ョ` and `ェ`.
+const YOON: &[(char, &str)] = &[
+    ('キ', "ky"),
+    ('ギ', "gy"),
+    ('ニ', "ny"),
+    ('ヒ', "hy"),
+    ('ビ', "by"),
+    ('ピ', "py"),
+    ('ミ', "my"),
+    ('リ', "ry"),
+    ('シ', "sh"),
+    ('ジ', "j"),
+    ('チ', "ch"),
+    ('ヂ', "j"),
+];
+
+/// Combinations of a kana and a small vowel used for loanwords.
+const EXTENDED: &[(&str, &str)] = &[
+    ("ファ", "fa"),
+    ("フィ", "fi"),
+    ("フェ", "fe"),
+    ("フォ", "fo"),
+    ("フュ", "fyu"),
+    ("ティ", "ti"),
+    ("ディ", "di"),
+    ("トゥ", "tu"),
+    ("ドゥ", "du"),
+    ("テュ", "tyu"),
+    ("デュ", "dyu"),
+    ("ウィ", "wi"),
+    ("ウェ", "we"),
+    ("ウォ", "wo"),
+    ("ヴァ", "va"),
+    ("ヴィ", "vi"),
+    ("ヴェ", "ve"),
+    ("ヴォ", "vo"),
+    ("ツァ", "tsa"),
+    ("ツィ", "tsi"),
+    ("ツェ", "tse"),
+    ("ツォ", "tso"),
+    ("イェ", "ye"),
+    ("クァ", "kwa"),
+    ("グァ", "gwa"),
+];
+
+/// Kana of each row, in the order of the vowels `a`, `i`, `u`, `e`, `o`, and their romaji.
+const ROWS: &[(&str, [&str; 5])] = &[
+    ("アイウエオ", ["a", "i", "u", "e", "o"]),
+    ("ァィゥェォ", ["a", "i", "u", "e", "o"]),
+    ("カキクケコ", ["ka", "ki", "ku", "ke", "ko"]),
+    ("サシスセソ", ["sa", "shi", "su", "se", "so"]),
+    ("タチツテト", ["ta", "chi", "tsu", "te", "to"]),
+    ("ナニヌネノ", ["na", "ni", "nu", "ne", "no"]),
+    ("ハヒフヘホ", ["ha", "hi", "fu", "he", "ho"]),
+    ("マミムメモ", ["ma", "mi", "mu", "me", "mo"]),
+    ("ヤ\0ユ\0ヨ", ["ya", "", "yu", "", "yo"]),
+    ("ャ\0ュ\0ョ", ["ya", "", "yu", "", "yo"]),
+    ("ラリルレロ", ["ra", "ri", "ru", "re", "ro"]),
+    ("ワヰ\0ヱヲ", ["wa", "i", "", "e", "o"]),
+    ("ガギグゲゴ", ["ga", "gi", "gu", "ge", "go"]),
+    ("ザジズゼゾ", ["za", "ji", "zu", "ze", "zo"]),
+    ("ダヂヅデド", ["da", "ji", "zu", "de", "do"]),
+    ("バビブベボ", ["ba", "bi", "bu", "be", "bo"]),
+    ("パピプペポ", ["pa", "pi", "pu", "pe", "po"]),
+    ("ヮ\0ヴヶ\0", ["wa", "", "vu", "ke", ""]),
+];
+
+/// Returns the romaji of the mora at the start of `kana` and its length in characters.
+fn mora(kana: &str) -> Option<(String, usize)> {
+    let mut chars = kana.chars();
+    let first = chars.next()?;
+    let second = chars.next();
+    if let Some(second) = second {
+        for (from, to) in EXTENDED {
+            if kana.starts_with(from) {
+                return Some((to.to_string(), 2));
+            }
+        }
+        if let Some((_, consonant)) = YOON.iter().find(|(c, _)| *c == first) {
+            let vowel = match second {
+                'ャ' => Some("a"),
+                'ュ' => Some("u"),
+                'ョ' => Some("o"),
+                'ェ' => Some("e"),
+                _ => None,
+            };
+            if let Some(vowel) = vowel {
+                return Some((format!("{consonant}{vowel}"), 2));
+            }
+        }
+    }
+    if first == 'ン' {
+        return Some(("n".to_string(), 1));
+    }
+    if first == 'ヵ' {
+        return Some(("ka".to_string(), 1));
+    }
+    ROWS.iter().find_map(|(row, romaji)| {
+        row.chars()
+            .position(|c| c == first)
+            .map(|i| (romaji[i].to_string(), 1))
+    })
+}
+
+/// Returns the Hepburn romaji of `kana`, e.g. `toukyou` for `トウキョウ` or `matchi` for
+/// `マッチ`. Characters other than kana are kept.
+///
+/// With `collapse_long_vowels`, long vowels are written as short ones, e.g. `tokyo` for both
+/// `トウキョウ` and `トーキョー`, as in the usual spelling of Japanese words in English.
+pub(crate) fn to_romaji(kana: &str, collapse_long_vowels: bool) -> String {
+    let kana = to_katakana(kana);
+    let mut romaji = String::with_capacity(kana.len());
+    let mut rest = kana.as_str();
+    let mut sokuon = false;
+    while let Some(c) = rest.chars().next() {
+        if c == 'ッ' {
+            sokuon = true;
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let last_vowel = romaji.chars().last().filter(|c| "aiueo".contains(*c));
+        if c == 'ー' {
+            if let (Some(vowel), false) = (last_vowel, collapse_long_vowels) {
+                romaji.push(vowel);
+            }
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let Some((syllable, len)) = mora(rest) else {
+            romaji.push(c);
+            sokuon = false;
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        rest = &rest[rest.char_indices().nth(len).map_or(rest.len(), |(i, _)| i)..];
+
+        if sokuon {
+            match syllable.chars().next() {
+                Some(_) if syllable.starts_with("ch") => romaji.push('t'),
+                Some(consonant) if !"aiueon".contains(consonant) => romaji.push(consonant),
+                _ => {}
+            }
+            sokuon = false;
+        }
+        let extends = match (last_vowel, syllable.as_str()) {
+            (Some(last), "a" | "i" | "u" | "e" | "o") => {
+                let vowel = syllable.chars().next().unwrap();
+                vowel == last || (vowel == 'u' && last == 'o')
+            }
+            _ => false,
+        };
+        if !(collapse_long_vowels && extends) {
+            romaji.push_str(&syllable);
+        }
+    }
+    romaji
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romaji() {
+        assert_eq!(to_romaji("トウキョウ", false), "toukyou");
+        assert_eq!(to_romaji("トウキョウ", true), "tokyo");
+        assert_eq!(to_romaji("トーキョー", false), "tookyoo");
+        assert_eq!(to_romaji("トーキョー", true), "tokyo");
+        assert_eq!(to_romaji("すもも", false), "sumomo");
+        assert_eq!(to_romaji("キッテ", false), "kitte");
+        assert_eq!(to_romaji("マッチ", false), "matchi");
+        assert_eq!(to_romaji("シャツ", false), "shatsu");
+        assert_eq!(to_romaji("ジェイソン", false), "jeison");
+        assert_eq!(to_romaji("コンピューター", true), "konpyuta");
+        assert_eq!(to_romaji("ファイル", false), "fairu");
+        assert_eq!(to_romaji("ChatGPT", false), "ChatGPT");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::filter::long_unit::combine;
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Adds word n-grams (shingles) of consecutive words, e.g. `東京大学` and `大学に` for `東京大学に`,
+/// which match phrase-like queries more precisely than the single words, without the cost of
+/// phrase queries.
+///
+/// Shingles are overlays spanning the positions of their words, with the feature string of
+/// their last word. Words which are not adjacent in the text, e.g. around a word removed by a
+/// previous filter, are not joined, and overlays added by previous filters are not part of any
+/// shingle.
+#[derive(Debug, Clone)]
+pub struct ShingleFilter {
+    min_size: usize,
+    max_size: usize,
+    separator: String,
+    output_unigrams: bool,
+}
+
+impl ShingleFilter {
+    /// Create a filter adding bigrams.
+    pub fn new() -> ShingleFilter {
+        ShingleFilter {
+            min_size: 2,
+            max_size: 2,
+            separator: String::new(),
+            output_unigrams: true,
+        }
+    }
+
+    /// Minimum number of words of the shingles, at least 2. The default is 2.
+    pub fn min_size(mut self, min_size: usize) -> ShingleFilter {
+        self.min_size = min_size.max(2);
+        self.max_size = self.max_size.max(self.min_size);
+        self
+    }
+
+    /// Maximum number of words of the shingles, e.g. 3 for bigrams and trigrams. The default
+    /// is 2.
+    pub fn max_size(mut self, max_size: usize) -> ShingleFilter {
+        self.max_size = max_size.max(2);
+        self.min_size = self.min_size.min(self.max_size);
+        self
+    }
+
+    /// String inserted between the words of a shingle. The default is the empty string.
+    pub fn separator<S: Into<String>>(mut self, separator: S) -> ShingleFilter {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Whether to keep the single words besides the shingles. Enabled by default.
+    pub fn output_unigrams(mut self, output_unigrams: bool) -> ShingleFilter {
+        self.output_unigrams = output_unigrams;
+        self
+    }
+}
+
+impl Default for ShingleFilter {
+    fn default() -> ShingleFilter {
+        ShingleFilter::new()
+    }
+}
+
+impl MorphFilter for ShingleFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let words = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+        let mut shingles = vec![vec![]; words.len()];
+        for (i, starting) in shingles.iter_mut().enumerate() {
+            let mut end = i + 1;
+            while end < words.len()
+                && end - i < self.max_size
+                && words[end - 1].char_to == words[end].char_from
+            {
+                end += 1;
+                if end - i >= self.min_size {
+                    let mut shingle = combine(&words[i..end]);
+                    shingle.text = words[i..end]
+                        .iter()
+                        .map(|w| w.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join(&self.separator);
+                    starting.push(shingle);
+                }
+            }
+        }
+
+        let mut shingles = shingles.into_iter();
+        let mut filtered = Vec::with_capacity(tokens.len() * 2);
+        for token in tokens.drain(..) {
+            let overlay = token.overlay;
+            if overlay || self.output_unigrams {
+                filtered.push(token);
+            }
+            if !overlay {
+                filtered.extend(shingles.next().unwrap_or_default());
+            }
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        format!(
+            "min_size={} max_size={} separator={:?} output_unigrams={}",
+            self.min_size, self.max_size, self.separator, self.output_unigrams
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::PositionMode;
+
+    #[test]
+    fn shingles() {
+        let texts = |filter: ShingleFilter| {
+            test_tokenizer()
+                .filter(filter)
+                .position_mode(PositionMode::CharOffset)
+                .analyze("東京大学に", None)
+                .into_iter()
+                .map(|t| (t.text, t.position, t.position_length))
+                .collect::<Vec<_>>()
+        };
+        let token =
+            |text: &str, position, position_length| (text.to_string(), position, position_length);
+        assert_eq!(
+            texts(ShingleFilter::new().max_size(3)),
+            vec![
+                token("東京", 0, 2),
+                token("東京大学", 0, 4),
+                token("東京大学に", 0, 5),
+                token("大学", 2, 2),
+                token("大学に", 2, 3),
+                token("に", 4, 1),
+            ]
+        );
+        assert_eq!(
+            texts(ShingleFilter::new().separator(" ").output_unigrams(false)),
+            vec![token("東京 大学", 0, 4), token("大学 に", 2, 3)]
+        );
+    }
+}
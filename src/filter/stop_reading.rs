@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use crate::features::DictionaryKind;
+use crate::filter::MorphFilter;
+use crate::sort_key::to_katakana;
+use crate::token::MorphToken;
+
+/// Removes the words whose lemma or reading is a stop word, so that every spelling and every
+/// inflection of a stop word is removed without listing them, e.g. `する`, `為る` and `し` for
+/// `する`, or `いう`, `言う` and `言っ` for `いう`.
+///
+/// A kana stop word matches the reading of the lemma, and any other one the lemma itself.
+/// The reading of the lemma is given by UniDic; for IPADIC it is derived from the reading of
+/// the surface and the inflection of the base form. Homophones of kana stop words are removed
+/// too, e.g. `古都` for `こと`; [`pos_tags`](Self::pos_tags) restricts the matches to some
+/// parts of speech.
+#[derive(Debug, Clone)]
+pub struct StopReadingFilter {
+    readings: HashSet<String>,
+    lemmas: HashSet<String>,
+    pos_tags: Vec<String>,
+}
+
+impl StopReadingFilter {
+    /// Create a filter removing `words`, written in kana to match readings.
+    pub fn new<I, S>(words: I) -> StopReadingFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut readings = HashSet::new();
+        let mut lemmas = HashSet::new();
+        for word in words {
+            let word = word.as_ref();
+            if word.chars().all(is_kana) {
+                readings.insert(to_katakana(word));
+            } else {
+                lemmas.insert(word.to_string());
+            }
+        }
+        StopReadingFilter {
+            readings,
+            lemmas,
+            pos_tags: vec![],
+        }
+    }
+
+    /// Only remove the words whose part-of-speech tag, joined with `-`, starts with one of
+    /// `pos_tags`, e.g. `動詞` or `名詞-非自立`. Every word is matched by default.
+    pub fn pos_tags<I, S>(mut self, pos_tags: I) -> StopReadingFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.pos_tags = pos_tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the stop word matching `token`, if any.
+    fn stop_word(&self, token: &MorphToken) -> Option<String> {
+        let features = token.features();
+        if !self.pos_tags.is_empty() {
+            let pos_tag = features.pos_tag();
+            if !self
+                .pos_tags
+                .iter()
+                .any(|t| pos_tag.starts_with(t.as_str()))
+            {
+                return None;
+            }
+        }
+        if let Some(lemma) = features.lemma().filter(|l| self.lemmas.contains(*l)) {
+            return Some(lemma.to_string());
+        }
+        lemma_reading(token).filter(|r| self.readings.contains(r))
+    }
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}' | 'ー')
+}
+
+/// Returns the reading of the lemma of `token` in katakana.
+fn lemma_reading(token: &MorphToken) -> Option<String> {
+    let features = token.features();
+    if token.kind == DictionaryKind::Unidic {
+        return features.field(6).map(str::to_string);
+    }
+    // The surface and the base form only differ by their kana inflection, e.g. `言っ` and
+    // `言う`: replace the inflection of the surface at the end of its reading.
+    let reading = features.reading()?;
+    let base_form = features.base_form()?;
+    let common = token
+        .surface
+        .char_indices()
+        .zip(base_form.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(token.surface.len().min(base_form.len()), |((i, _), _)| i);
+    let inflection = to_katakana(&token.surface[common..]);
+    let ending = to_katakana(base_form.get(common..)?);
+    let stem = reading.strip_suffix(inflection.as_str())?;
+    Some(format!("{stem}{ending}"))
+}
+
+impl MorphFilter for StopReadingFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        tokens.retain(|t| self.stop_word(t).is_none());
+    }
+
+    fn config(&self) -> String {
+        let mut words = self
+            .readings
+            .iter()
+            .chain(&self.lemmas)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        words.sort_unstable();
+        format!("{}\n{}", self.pos_tags.join(","), words.join("\n"))
+    }
+
+    fn reason(&self, token: &MorphToken) -> Option<String> {
+        self.stop_word(token)
+            .map(|word| format!("stop word {word}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn stop_readings() {
+        let texts = |filter: StopReadingFilter| {
+            test_tokenizer()
+                .filter(filter)
+                .analyze("行きました。行かない。読んでいる", None)
+                .into_iter()
+                .map(|t| t.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            texts(StopReadingFilter::new(["いく", "イル", "です"])),
+            vec!["まし", "た", "。", "ない", "。", "読ん", "で"]
+        );
+        assert_eq!(
+            texts(StopReadingFilter::new(["読む", "ない"]).pos_tags(["動詞"])),
+            vec!["行き", "まし", "た", "。", "行か", "ない", "。", "で", "いる"]
+        );
+    }
+}
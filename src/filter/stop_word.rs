@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use tantivy::tokenizer::{BoxTokenStream, StopWordFilter as TStopWordFilter, TokenFilter};
+
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Lucene's default Japanese stop words (`stopwords.txt` of the kuromoji analysis module).
+pub(crate) const KUROMOJI_STOP_WORDS: &[&str] = &[
+    "の", "に", "は", "を", "た", "が", "で", "て", "と", "し", "れ", "さ", "ある", "いる", "も",
+    "する", "から", "な", "こと", "として", "い", "や", "れる", "など", "なっ", "ない", "この",
+    "ため", "その", "あっ", "よう", "また", "もの", "という", "あり", "まで", "られ", "なる",
+    "へ", "か", "だ", "これ", "によって", "により", "おり", "より", "による", "ず", "なり",
+    "られる", "において", "ば", "なかっ", "なく", "しかし", "について", "せ", "だっ", "その後",
+    "できる", "それ", "う", "ので", "なお", "のみ", "でき", "き", "つ", "における", "および",
+    "いう", "さらに", "でも", "ら", "たり", "その他", "に関する", "たち", "ます", "ん", "なら",
+    "に対して", "特に", "せる", "及び", "これら", "とき", "では", "にて", "ほか", "ながら",
+    "うち", "そして", "とともに", "ただし", "かつて", "それぞれ", "または", "お", "ほど",
+    "ものの", "に対する", "ほとんど", "と共に", "といった", "です", "とも", "ところ", "ここ",
+];
+
+/// Removes tokens whose text is a stop word.
+#[derive(Debug, Clone)]
+pub(crate) struct StopWordFilter {
+    words: HashSet<String>,
+}
+
+impl StopWordFilter {
+    pub(crate) fn new<I, S>(words: I) -> StopWordFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        StopWordFilter {
+            words: words.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl MorphFilter for StopWordFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        tokens.retain(|t| !self.words.contains(&t.text));
+    }
+
+    fn config(&self) -> String {
+        let mut words = self.words.iter().map(String::as_str).collect::<Vec<_>>();
+        words.sort_unstable();
+        words.join("\n")
+    }
+
+    fn reason(&self, token: &MorphToken) -> Option<String> {
+        self.words
+            .contains(&token.text)
+            .then(|| format!("stop word {}", token.text))
+    }
+}
+
+/// A tantivy `TokenFilter` removing Japanese stop words, by default the particles, auxiliaries
+/// and function words of Lucene's Japanese stop word list (`の`, `に`, `は`, `を`, `た`, `です`,
+/// `ます`, ...).
+///
+/// It matches the term text only, so it can follow any filter of a `TextAnalyzer`:
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::tokenizer::TextAnalyzer;
+/// use tantivy_vibrato::filter::JapaneseStopWordFilter;
+/// use tantivy_vibrato::VibratoTokenizer;
+///
+/// let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+/// let analyzer = TextAnalyzer::from(tokenizer).filter(JapaneseStopWordFilter::new());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct JapaneseStopWordFilter {
+    inner: TStopWordFilter,
+}
+
+impl JapaneseStopWordFilter {
+    /// Create a filter removing the bundled stop words.
+    pub fn new() -> JapaneseStopWordFilter {
+        JapaneseStopWordFilter::with_words(KUROMOJI_STOP_WORDS.iter().copied())
+    }
+
+    /// Create a filter removing `words` instead of the bundled stop words. Extend
+    /// [`default_words`](Self::default_words) to add words to them.
+    pub fn with_words<I, S>(words: I) -> JapaneseStopWordFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        JapaneseStopWordFilter {
+            inner: TStopWordFilter::remove(words.into_iter().map(Into::into)),
+        }
+    }
+
+    /// Returns the bundled stop words.
+    pub fn default_words() -> &'static [&'static str] {
+        KUROMOJI_STOP_WORDS
+    }
+}
+
+impl Default for JapaneseStopWordFilter {
+    fn default() -> JapaneseStopWordFilter {
+        JapaneseStopWordFilter::new()
+    }
+}
+
+impl TokenFilter for JapaneseStopWordFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        self.inner.transform(token_stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TextAnalyzer;
+
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn japanese_stop_words() {
+        let texts = |filter: JapaneseStopWordFilter| {
+            let tokenizer = test_tokenizer();
+            let analyzer = TextAnalyzer::from(tokenizer).filter(filter);
+            let mut stream = analyzer.token_stream("私は東京に行きました");
+            let mut texts = vec![];
+            while let Some(token) = stream.next() {
+                texts.push(token.text.clone());
+            }
+            texts
+        };
+        assert_eq!(
+            texts(JapaneseStopWordFilter::new()),
+            vec!["私", "東京", "行き", "まし"]
+        );
+        let words = JapaneseStopWordFilter::default_words()
+            .iter()
+            .copied()
+            .chain(["私"]);
+        assert_eq!(
+            texts(JapaneseStopWordFilter::with_words(words)),
+            vec!["東京", "行き", "まし"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use crate::filter::MorphFilter;
+use crate::sort_key::to_katakana;
+use crate::token::MorphToken;
+
+/// What the matched words become.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Rule {
+    synonyms: Vec<String>,
+    /// Whether the matched words are replaced by the synonyms instead of being kept.
+    replace: bool,
+}
+
+/// Adds the synonyms of words or sequences of words at their position, e.g. `犬` and `いぬ` for
+/// `イヌ`, or `東大` for `東京` `大学`.
+///
+/// Rules match the concatenated text of adjacent words, so `東京大学` matches the two words
+/// `東京` `大学`, and a synonym spans the positions of all the words it matches. By default,
+/// hiragana and katakana are matched alike, so that `いぬ` in a rule also matches `イヌ`.
+///
+/// The synonyms are added as overlay tokens and the matched words are kept, except for
+/// explicit mappings, which replace them. The filter matches the text of the tokens as
+/// rewritten by the filters before it, so the rules must be normalized like them.
+#[derive(Debug, Clone)]
+pub struct SynonymFilter {
+    /// The words, synonyms and replacement flags as added, to rebuild the keys of the rules
+    /// when the kana folding changes.
+    added: Vec<(String, Vec<String>, bool)>,
+    rules: HashMap<String, Rule>,
+    /// Length in characters of the longest matched text.
+    max_len: usize,
+    fold_kana: bool,
+}
+
+impl SynonymFilter {
+    /// Create a filter without rules.
+    pub fn new() -> SynonymFilter {
+        SynonymFilter {
+            added: vec![],
+            rules: HashMap::new(),
+            max_len: 0,
+            fold_kana: true,
+        }
+    }
+
+    /// Whether to match hiragana and katakana alike. Enabled by default.
+    pub fn fold_kana(mut self, fold_kana: bool) -> SynonymFilter {
+        self.fold_kana = fold_kana;
+        self.rules.clear();
+        self.max_len = 0;
+        for (word, synonyms, replace) in std::mem::take(&mut self.added) {
+            self.insert(&word, synonyms, replace);
+        }
+        self
+    }
+
+    /// Add words that are synonyms of each other: each of them gets the others.
+    pub fn equivalent<I, S>(mut self, words: I) -> SynonymFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let words = words.into_iter().map(Into::into).collect::<Vec<String>>();
+        for word in &words {
+            let synonyms = words.iter().filter(|w| *w != word).cloned().collect();
+            self.insert(word, synonyms, false);
+        }
+        self
+    }
+
+    /// Add a mapping replacing each of the words `from` by the words `to`.
+    pub fn mapping<I, J, S, T>(mut self, from: I, to: J) -> SynonymFilter
+    where
+        I: IntoIterator<Item = S>,
+        J: IntoIterator<Item = T>,
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let to = to.into_iter().map(Into::into).collect::<Vec<String>>();
+        for word in from {
+            self.insert(&word.into(), to.clone(), true);
+        }
+        self
+    }
+
+    fn key(&self, text: &str) -> String {
+        let text = text.split_whitespace().collect::<String>();
+        if self.fold_kana {
+            to_katakana(&text)
+        } else {
+            text
+        }
+    }
+
+    fn insert(&mut self, word: &str, synonyms: Vec<String>, replace: bool) {
+        let key = self.key(word);
+        if key.is_empty() {
+            return;
+        }
+        self.max_len = self.max_len.max(key.chars().count());
+        let rule = self.rules.entry(key).or_default();
+        rule.replace |= replace;
+        for synonym in &synonyms {
+            if !rule.synonyms.contains(synonym) {
+                rule.synonyms.push(synonym.clone());
+            }
+        }
+        self.added.push((word.to_string(), synonyms, replace));
+    }
+
+    /// Read rules in the format of Solr's synonym files: a line of comma separated words is a
+    /// group of equivalent words, and `a, b => c, d` maps `a` and `b` to `c` and `d`. Empty
+    /// lines and lines starting with `#` are ignored.
+    pub fn from_solr_reader<R: BufRead>(reader: R) -> io::Result<SynonymFilter> {
+        let mut filter = SynonymFilter::new();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words = |s: &str| {
+                s.split(',')
+                    .map(str::trim)
+                    .filter(|w| !w.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            };
+            filter = match line.split_once("=>") {
+                Some((from, to)) => {
+                    let (from, to) = (words(from), words(to));
+                    if from.is_empty() || to.is_empty() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("invalid synonym mapping: {line}"),
+                        ));
+                    }
+                    filter.mapping(from, to)
+                }
+                None => filter.equivalent(words(line)),
+            };
+        }
+        Ok(filter)
+    }
+
+    /// Read groups of equivalent words, one group per line with the words separated by tabs.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_tsv_reader<R: BufRead>(reader: R) -> io::Result<SynonymFilter> {
+        let mut filter = SynonymFilter::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            filter = filter.equivalent(line.split('\t').map(str::trim).filter(|w| !w.is_empty()));
+        }
+        Ok(filter)
+    }
+
+    /// Returns the end of the longest match of the words from `start` and its rule.
+    fn longest_match(&self, tokens: &[MorphToken], start: usize) -> Option<(usize, &Rule)> {
+        let mut text = String::new();
+        let mut len = 0;
+        let mut longest = None;
+        for end in start..tokens.len() {
+            let token = &tokens[end];
+            if token.overlay || (end > start && tokens[end - 1].offset_to != token.offset_from) {
+                break;
+            }
+            len += token.text.chars().count();
+            if len > self.max_len {
+                break;
+            }
+            text.push_str(&token.text);
+            if let Some(rule) = self.rules.get(&self.key(&text)) {
+                longest = Some((end + 1, rule));
+            }
+        }
+        longest
+    }
+}
+
+impl Default for SynonymFilter {
+    fn default() -> SynonymFilter {
+        SynonymFilter::new()
+    }
+}
+
+impl MorphFilter for SynonymFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        if self.rules.is_empty() {
+            return;
+        }
+        let mut filtered = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let Some((end, rule)) = self.longest_match(tokens, i) else {
+                filtered.push(tokens[i].clone());
+                i += 1;
+                continue;
+            };
+            let run = &tokens[i..end];
+            let last = &run[run.len() - 1];
+            let text = run.iter().map(|t| t.text.as_str()).collect::<String>();
+            let span = MorphToken {
+                surface: run.iter().map(|t| t.surface.as_str()).collect(),
+                offset_to: last.offset_to,
+                char_to: last.char_to,
+                total_cost: last.total_cost,
+                ..run[0].clone()
+            };
+            let mut synonyms = rule.synonyms.iter().filter(|s| **s != text || rule.replace);
+            if rule.replace {
+                // The first synonym takes the place of the words, spanning their positions.
+                if let Some(first) = synonyms.next() {
+                    filtered.push(MorphToken {
+                        text: first.clone(),
+                        ..span.clone()
+                    });
+                }
+            } else {
+                filtered.extend(run.iter().cloned());
+            }
+            filtered.extend(synonyms.map(|synonym| MorphToken {
+                text: synonym.clone(),
+                overlay: true,
+                ..span.clone()
+            }));
+            i = end;
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        let mut rules = self
+            .rules
+            .iter()
+            .map(|(key, rule)| format!("{key}\t{}\t{}", rule.replace, rule.synonyms.join("\t")))
+            .collect::<Vec<_>>();
+        rules.sort_unstable();
+        rules.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::tokenizer::PositionMode;
+
+    #[test]
+    fn synonyms() {
+        let rules = "# fruits\nスモモ, 李\n東京大学 => 東大\n";
+        let filter = SynonymFilter::from_solr_reader(rules.as_bytes()).unwrap();
+        let tokenizer = test_tokenizer()
+            .filter(filter)
+            .position_mode(PositionMode::Sequential);
+        let tokens = |text: &str| {
+            tokenizer
+                .analyze(text, None)
+                .into_iter()
+                .map(|t| (t.text, t.position, t.position_length))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            tokens("すもものうち東京大学"),
+            vec![
+                ("すもも".to_string(), 0, 1),
+                ("李".to_string(), 0, 1),
+                ("の".to_string(), 1, 1),
+                ("うち".to_string(), 2, 1),
+                ("東大".to_string(), 3, 2),
+            ]
+        );
+
+        let filter = SynonymFilter::from_tsv_reader("東京大学\t東大\n".as_bytes()).unwrap();
+        let mut tokens = tokenizer.morphemes("東京大学");
+        filter.filter(&mut tokens);
+        let texts = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.overlay))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![("東京", false), ("大学", false), ("東大", true)]
+        );
+        assert!(SynonymFilter::from_solr_reader("=> 東大".as_bytes()).is_err());
+
+        let filter = SynonymFilter::from_solr_reader("すもも, 李".as_bytes())
+            .unwrap()
+            .fold_kana(false);
+        let mut tokens = tokenizer.morphemes("すもも");
+        filter.filter(&mut tokens);
+        let texts = tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["すもも", "李"]);
+    }
+}
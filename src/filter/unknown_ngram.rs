@@ -0,0 +1,125 @@
+use vibrato::dictionary::LexType;
+
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Default size of the n-grams.
+const DEFAULT_SIZE: usize = 2;
+
+/// Adds the character n-grams, bigrams by default, of unknown words, so that new slang,
+/// product codes and rare names still match partially when Vibrato analyzes them as one long
+/// unknown word.
+///
+/// The unknown words are kept and the n-grams are added as overlays at the characters they
+/// cover. Words of spaces or symbols only, and words no longer than an n-gram, are left alone.
+#[derive(Debug, Clone)]
+pub struct UnknownWordNgramFilter {
+    size: usize,
+}
+
+impl UnknownWordNgramFilter {
+    /// Create a filter adding the bigrams of unknown words.
+    pub fn new() -> UnknownWordNgramFilter {
+        UnknownWordNgramFilter { size: DEFAULT_SIZE }
+    }
+
+    /// Number of characters of the n-grams, at least 1. The default is 2.
+    pub fn size(mut self, size: usize) -> UnknownWordNgramFilter {
+        self.size = size.max(1);
+        self
+    }
+
+    fn ngrams(&self, token: &MorphToken) -> Vec<MorphToken> {
+        let chars = token.text.chars().collect::<Vec<_>>();
+        if token.overlay
+            || token.lex_type != LexType::Unknown
+            || chars.len() <= self.size
+            || !chars.iter().any(|c| c.is_alphanumeric())
+        {
+            return vec![];
+        }
+        // Byte offsets of the characters in the surface, if the text still has its characters.
+        let offsets = token
+            .surface
+            .char_indices()
+            .map(|(i, _)| token.offset_from + i)
+            .chain([token.offset_to])
+            .collect::<Vec<_>>();
+        let aligned = offsets.len() == chars.len() + 1;
+        chars
+            .windows(self.size)
+            .enumerate()
+            .map(|(i, gram)| {
+                let (offset_from, offset_to) = if aligned {
+                    (offsets[i], offsets[i + self.size])
+                } else {
+                    (token.offset_from, token.offset_to)
+                };
+                MorphToken {
+                    text: gram.iter().collect(),
+                    surface: token.surface
+                        [offset_from - token.offset_from..offset_to - token.offset_from]
+                        .to_string(),
+                    offset_from,
+                    offset_to,
+                    char_from: token.char_from + i,
+                    char_to: token.char_from + i + self.size,
+                    overlay: true,
+                    ..token.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for UnknownWordNgramFilter {
+    fn default() -> UnknownWordNgramFilter {
+        UnknownWordNgramFilter::new()
+    }
+}
+
+impl MorphFilter for UnknownWordNgramFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        let mut filtered = Vec::with_capacity(tokens.len());
+        for token in tokens.drain(..) {
+            let ngrams = self.ngrams(&token);
+            filtered.push(token);
+            filtered.extend(ngrams);
+        }
+        *tokens = filtered;
+    }
+
+    fn config(&self) -> String {
+        self.size.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::tokenizer::PositionMode;
+
+    #[test]
+    fn ngrams() {
+        let tokenizer = test_tokenizer()
+            .filter(UnknownWordNgramFilter::new())
+            .position_mode(PositionMode::Sequential);
+        let tokens = tokenizer
+            .analyze("東京のABCD", None)
+            .into_iter()
+            .map(|t| (t.text, t.offset_from, t.position))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                ("東京".to_string(), 0, 0),
+                ("の".to_string(), 6, 1),
+                ("ABCD".to_string(), 9, 2),
+                ("AB".to_string(), 9, 2),
+                ("BC".to_string(), 10, 2),
+                ("CD".to_string(), 11, 2),
+            ]
+        );
+    }
+}
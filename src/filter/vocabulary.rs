@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// What a [`VocabularyFilter`] does with the tokens of its vocabulary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VocabularyMode {
+    /// Keep the tokens of the vocabulary only, e.g. the terms of a controlled tag list.
+    Keep,
+    /// Remove the tokens of the vocabulary.
+    Remove,
+}
+
+/// Keeps only, or removes, the tokens whose text is in a vocabulary, such as a controlled tag
+/// list or a list of the terms above a minimum frequency produced offline, e.g. to build clean
+/// keyword or faceting fields.
+///
+/// The filter matches the text of the tokens as rewritten by the filters before it, so the
+/// vocabulary must be normalized like them.
+#[derive(Debug, Clone)]
+pub struct VocabularyFilter {
+    words: HashSet<String>,
+    mode: VocabularyMode,
+}
+
+impl VocabularyFilter {
+    /// Create a filter applying `mode` to the tokens of `words`.
+    pub fn new<I, S>(words: I, mode: VocabularyMode) -> VocabularyFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        VocabularyFilter {
+            words: words.into_iter().map(Into::into).collect(),
+            mode,
+        }
+    }
+
+    /// Read a vocabulary with one term per line. Anything after a tab, such as a frequency, is
+    /// ignored, as are empty lines and lines starting with `#`.
+    pub fn from_reader<R: BufRead>(
+        reader: R,
+        mode: VocabularyMode,
+    ) -> io::Result<VocabularyFilter> {
+        let mut words = HashSet::new();
+        for line in reader.lines() {
+            let line = line?;
+            let term = line.split('\t').next().unwrap_or_default().trim();
+            if !term.is_empty() && !term.starts_with('#') {
+                words.insert(term.to_string());
+            }
+        }
+        Ok(VocabularyFilter { words, mode })
+    }
+
+    fn keeps(&self, token: &MorphToken) -> bool {
+        self.words.contains(&token.text) == (self.mode == VocabularyMode::Keep)
+    }
+}
+
+impl MorphFilter for VocabularyFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        tokens.retain(|t| self.keeps(t));
+    }
+
+    fn config(&self) -> String {
+        let mut words = self.words.iter().map(String::as_str).collect::<Vec<_>>();
+        words.sort_unstable();
+        format!("{:?}\n{}", self.mode, words.join("\n"))
+    }
+
+    fn reason(&self, token: &MorphToken) -> Option<String> {
+        (!self.keeps(token)).then(|| match self.mode {
+            VocabularyMode::Keep => format!("{} not in the vocabulary", token.text),
+            VocabularyMode::Remove => format!("{} in the vocabulary", token.text),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn vocabulary() {
+        let vocabulary = "# tags\n東京\t120\n大学\n\n";
+        let texts = |mode| {
+            let filter = VocabularyFilter::from_reader(vocabulary.as_bytes(), mode).unwrap();
+            let tokenizer = test_tokenizer().filter(filter);
+            tokenizer
+                .analyze("東京の大学の東京", None)
+                .into_iter()
+                .map(|t| t.text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(VocabularyMode::Keep), vec!["東京", "大学", "東京"]);
+        assert_eq!(texts(VocabularyMode::Remove), vec!["の", "の"]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::filter::MorphFilter;
+use crate::token::MorphToken;
+
+/// Half-width katakana from U+FF65 to U+FF9F and their full-width forms.
+const HALF_WIDTH_KATAKANA: &str = "・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+
+/// Folds full-width ASCII to half-width and half-width katakana to full-width, like Lucene's
+/// `CJKWidthFilter`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WidthFilter;
+
+impl MorphFilter for WidthFilter {
+    fn filter(&self, tokens: &mut Vec<MorphToken>) {
+        for token in tokens {
+            if token.text.chars().any(needs_folding) {
+                token.text = fold_width(&token.text);
+            }
+        }
+    }
+}
+
+fn needs_folding(c: char) -> bool {
+    ('\u{FF01}'..='\u{FF5E}').contains(&c) || ('\u{FF65}'..='\u{FF9F}').contains(&c)
+}
+
+pub(crate) fn fold_width(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{FF01}'..='\u{FF5E}' => {
+                folded.push(char::from_u32(c as u32 - 0xFEE0).unwrap());
+            }
+            '\u{FF9E}' | '\u{FF9F}' => {
+                let semi_voiced = c == '\u{FF9F}';
+                match folded.chars().last().and_then(|p| compose(p, semi_voiced)) {
+                    Some(composed) => {
+                        folded.pop();
+                        folded.push(composed);
+                    }
+                    None => folded.push(full_width_katakana(c)),
+                }
+            }
+            '\u{FF65}'..='\u{FF9D}' => folded.push(full_width_katakana(c)),
+            _ => folded.push(c),
+        }
+    }
+    folded
+}
+
+fn full_width_katakana(c: char) -> char {
+    HALF_WIDTH_KATAKANA
+        .chars()
+        .nth(c as usize - 0xFF65)
+        .unwrap_or(c)
+}
+
+/// Compose a full-width katakana with a (semi-)voiced sound mark, e.g. `カ` + `゛` to `ガ`.
+fn compose(c: char, semi_voiced: bool) -> Option<char> {
+    let code = c as u32;
+    let composed = if semi_voiced {
+        matches!(c, 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ').then_some(code + 2)
+    } else if c == 'ウ' {
+        Some('ヴ' as u32)
+    } else {
+        matches!(
+            c,
+            'カ' | 'キ' | 'ク' | 'ケ' | 'コ' | 'サ' | 'シ' | 'ス' | 'セ' | 'ソ' | 'タ' | 'チ' | 'ツ'
+                | 'テ' | 'ト' | 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ'
+        )
+        .then_some(code + 1)
+    };
+    composed.and_then(char::from_u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold() {
+        assert_eq!(fold_width("ＡＢＣ１２３！"), "ABC123!");
+        assert_eq!(fold_width("ｱｲｳｴｵ"), "アイウエオ");
+        assert_eq!(fold_width("ｶﾞｷﾞﾊﾟｳﾞ"), "ガギパヴ");
+        assert_eq!(fold_width("ｰﾞ"), "ー゛");
+        assert_eq!(fold_width("漢字かな"), "漢字かな");
+    }
+}
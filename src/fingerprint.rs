@@ -0,0 +1,127 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Version of the analysis behavior of this crate.
+///
+/// Bump this whenever a change makes the same configuration emit different tokens.
+const ANALYSIS_VERSION: u32 = 3;
+
+/// Stable identity of an analyzer: dictionary content, configuration and analysis behavior.
+///
+/// Store it with the index and compare it with the fingerprint of the analyzer used at query
+/// time to detect analyzer drift. It is rendered as 16 hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Returns the fingerprint as an integer.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Fingerprint)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust versions and platforms.
+#[derive(Debug, Clone)]
+pub(crate) struct Hasher(u64);
+
+impl Hasher {
+    pub(crate) fn new() -> Hasher {
+        Hasher(0xcbf29ce484222325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Write a length-prefixed string so that consecutive fields cannot collide.
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+
+    pub(crate) fn fingerprint(mut self) -> Fingerprint {
+        self.write(&ANALYSIS_VERSION.to_le_bytes());
+        self.write_str(vibrato::VERSION);
+        Fingerprint(self.finish())
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Hasher::write(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reader hashing the bytes read through it.
+pub(crate) struct HashingReader<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub(crate) fn new(inner: R) -> HashingReader<R> {
+        HashingReader {
+            inner,
+            hasher: Hasher::new(),
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.write(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a() {
+        let mut hasher = Hasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn round_trip() {
+        let fingerprint = Fingerprint(0x0123456789abcdef);
+        assert_eq!(fingerprint.to_string(), "0123456789abcdef");
+        assert_eq!("0123456789abcdef".parse(), Ok(fingerprint));
+    }
+}
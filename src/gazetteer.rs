@@ -0,0 +1,228 @@
+//! Entities which must be kept as single tokens.
+use std::fmt::Write;
+use std::io::{self, BufRead};
+
+use vibrato::Tokenizer;
+
+use crate::features::DictionaryKind;
+
+/// Default margin subtracted from the cost of the best segmentation of an entity.
+const DEFAULT_MARGIN: i32 = 1000;
+
+/// A list of entities, such as product or person names, which must always be segmented as
+/// single tokens. Attach it with
+/// [`VibratoTokenizer::gazetteer`](crate::VibratoTokenizer::gazetteer).
+///
+/// Each entity is converted into a user lexicon entry whose cost is tuned from the dictionary:
+/// the entity is analyzed on its own, and the entry gets the connection ids of the first and
+/// last words of that segmentation and a word cost lower than its total cost by a margin.
+/// Since the entry connects to its neighbors like the segmentation it replaces, it wins over
+/// that segmentation in any context, and the margin makes it win over competing segmentations
+/// crossing its boundaries.
+#[derive(Debug, Clone)]
+pub struct Gazetteer {
+    /// Surface, feature and fixed word cost of the entries.
+    entries: Vec<(String, Feature, Option<i16>)>,
+    margin: i32,
+}
+
+/// Feature string of an entity.
+#[derive(Debug, Clone)]
+enum Feature {
+    /// The feature of a proper noun of the given subcategories, e.g. `一般`.
+    ProperNoun(&'static str, &'static str),
+    Custom(String),
+    /// The feature of the dictionary word of the same surface, or of a common proper noun if
+    /// there is none.
+    Dictionary,
+}
+
+impl Gazetteer {
+    /// Create an empty gazetteer.
+    pub fn new() -> Gazetteer {
+        Gazetteer {
+            entries: vec![],
+            margin: DEFAULT_MARGIN,
+        }
+    }
+
+    /// Read a gazetteer with one entity per line, optionally followed by a tab and the feature
+    /// string of its entry, and by another tab and a fixed word cost, see
+    /// [`word_cost`](Self::word_cost). The feature string can be empty to keep the one of the
+    /// dictionary when there is a cost. Empty lines and lines starting with `#` are ignored.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Gazetteer> {
+        let mut gazetteer = Gazetteer::new();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.splitn(3, '\t');
+            let surface = fields.next().unwrap_or_default();
+            gazetteer = match (fields.next(), fields.next()) {
+                (Some(feature), Some(cost)) => {
+                    let cost = cost.trim().parse().map_err(|e| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("invalid cost {cost:?}: {e}"),
+                        )
+                    })?;
+                    let feature = match feature {
+                        "" => Feature::Dictionary,
+                        feature => Feature::Custom(feature.to_string()),
+                    };
+                    gazetteer
+                        .entries
+                        .push((surface.to_string(), feature, Some(cost)));
+                    gazetteer
+                }
+                (Some(feature), None) => gazetteer.entity_with_feature(surface, feature),
+                _ => gazetteer.entity(surface),
+            };
+        }
+        Ok(gazetteer)
+    }
+
+    /// Add an entity, tagged as a proper noun.
+    pub fn entity<S: Into<String>>(mut self, surface: S) -> Gazetteer {
+        self.entries
+            .push((surface.into(), Feature::ProperNoun("一般", "*"), None));
+        self
+    }
+
+    /// Add a surname, tagged as `名詞,固有名詞,人名,姓`. Together with
+    /// [`given_name`](Self::given_name), this makes full names like `山田太郎` split between the
+    /// surname and the given name, see [`PersonNameFilter`](crate::filter::PersonNameFilter).
+    pub fn surname<S: Into<String>>(mut self, surface: S) -> Gazetteer {
+        self.entries
+            .push((surface.into(), Feature::ProperNoun("人名", "姓"), None));
+        self
+    }
+
+    /// Add a given name, tagged as `名詞,固有名詞,人名,名`.
+    pub fn given_name<S: Into<String>>(mut self, surface: S) -> Gazetteer {
+        self.entries
+            .push((surface.into(), Feature::ProperNoun("人名", "名"), None));
+        self
+    }
+
+    /// Add an entity with the comma separated feature string of its entry.
+    pub fn entity_with_feature<S: Into<String>, F: Into<String>>(
+        mut self,
+        surface: S,
+        feature: F,
+    ) -> Gazetteer {
+        self.entries
+            .push((surface.into(), Feature::Custom(feature.into()), None));
+        self
+    }
+
+    /// Override the word cost of `surface` with `cost` instead of tuning it, to nudge a
+    /// systematic mis-segmentation without rebuilding the dictionary.
+    ///
+    /// If `surface` is a word of the dictionary, the entry copies its connection ids and its
+    /// feature string, so that a lower cost makes the word more likely to be selected. A cost
+    /// higher than the one of the dictionary has no effect, since the dictionary entry remains.
+    /// Otherwise, the entry is added as a proper noun with the connection ids of the
+    /// segmentation it replaces, like [`entity`](Self::entity).
+    ///
+    /// The connection costs between parts of speech cannot be overridden: Vibrato does not
+    /// allow to edit the connection matrix of a loaded dictionary.
+    pub fn word_cost<S: Into<String>>(mut self, surface: S, cost: i16) -> Gazetteer {
+        self.entries
+            .push((surface.into(), Feature::Dictionary, Some(cost)));
+        self
+    }
+
+    /// Margin by which the cost of an entity is lower than the one of the segmentation it
+    /// replaces. The default is 1000.
+    pub fn margin(mut self, margin: i32) -> Gazetteer {
+        self.margin = margin;
+        self
+    }
+
+    /// Returns the number of entities.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there is no entity.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Convert the entities into user lexicon entries in the MeCab CSV format, with costs
+    /// tuned with `tokenizer`.
+    pub(crate) fn to_user_lexicon(&self, tokenizer: &Tokenizer, kind: DictionaryKind) -> String {
+        let mut worker = tokenizer.new_worker();
+        let mut lexicon = String::new();
+        for (surface, feature, fixed_cost) in &self.entries {
+            if surface.is_empty() {
+                continue;
+            }
+            worker.reset_sentence(surface);
+            worker.tokenize();
+            let (Some(first), Some(last)) =
+                (worker.token_iter().next(), worker.token_iter().last())
+            else {
+                continue;
+            };
+            // The cost of the segmentation without the connection from BOS.
+            let cost = last.total_cost() - (first.total_cost() - i32::from(first.word_cost()));
+            let cost = match fixed_cost {
+                Some(cost) => i32::from(*cost),
+                None => (cost - self.margin).clamp(i32::from(i16::MIN), i32::from(i16::MAX)),
+            };
+            let single_word = worker.num_tokens() == 1;
+            let feature = match feature {
+                Feature::Custom(feature) => feature.clone(),
+                Feature::ProperNoun(sub1, sub2) => proper_noun_feature(surface, kind, sub1, sub2),
+                Feature::Dictionary if single_word => first.feature().to_string(),
+                Feature::Dictionary => proper_noun_feature(surface, kind, "一般", "*"),
+            };
+            writeln!(
+                lexicon,
+                "{},{},{},{},{}",
+                escape(surface),
+                first.left_id(),
+                last.right_id(),
+                cost,
+                feature
+            )
+            .unwrap();
+        }
+        lexicon
+    }
+}
+
+impl Default for Gazetteer {
+    fn default() -> Gazetteer {
+        Gazetteer::new()
+    }
+}
+
+fn proper_noun_feature(surface: &str, kind: DictionaryKind, sub1: &str, sub2: &str) -> String {
+    entry_feature(surface, kind, ["名詞", "固有名詞", sub1, sub2])
+}
+
+/// Returns the feature string of an entry of the part of speech `pos` without reading.
+pub(crate) fn entry_feature(surface: &str, kind: DictionaryKind, pos: [&str; 4]) -> String {
+    // Feature fields cannot be quoted.
+    let surface = if surface.contains(',') { "*" } else { surface };
+    let pos = pos.join(",");
+    match kind {
+        DictionaryKind::Ipadic => format!("{pos},*,*,{surface},*,*"),
+        DictionaryKind::Unidic => {
+            format!("{pos},*,*,*,{surface},{surface},*,{surface},*,固")
+        }
+    }
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,186 @@
+//! Keyword extraction with the dictionary used for indexing.
+//!
+//! The candidates are the noun phrases of the text: runs of nouns, optionally preceded by noun
+//! prefixes and followed by noun suffixes, e.g. `東京大学` or `東京人`. They are ranked by their
+//! frequency in the text (TF), or by TF-IDF against document frequencies supplied by the
+//! application:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy_vibrato::keywords::{DocumentFrequencies, KeywordExtractor};
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! let extractor = KeywordExtractor::new(VibratoTokenizer::new("/path/to/dictionary")?);
+//! let tags = extractor.extract("東京大学の研究者が東京大学で講演した", 5);
+//!
+//! let mut stats = DocumentFrequencies::new(1000);
+//! stats.insert("東京大学", 120);
+//! let related = extractor.extract_with_stats("東京大学の研究者が東京大学で講演した", &stats, 5);
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+
+use crate::filter::long_unit::noun_runs;
+use crate::VibratoTokenizer;
+
+/// A keyword extracted from a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    /// Text of the noun phrase.
+    pub text: String,
+    /// Number of occurrences in the text.
+    pub count: usize,
+    /// Score used for ranking: the count for TF, the TF-IDF weight otherwise.
+    pub score: f64,
+}
+
+/// Statistics of a corpus used to weight keywords by their inverse document frequency.
+pub trait TermStatistics {
+    /// Number of documents of the corpus.
+    fn num_docs(&self) -> u64;
+
+    /// Number of documents of the corpus containing `term`.
+    fn doc_freq(&self, term: &str) -> u64;
+}
+
+/// Document frequencies held in memory.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentFrequencies {
+    num_docs: u64,
+    doc_freqs: HashMap<String, u64>,
+}
+
+impl DocumentFrequencies {
+    /// Create statistics of a corpus of `num_docs` documents.
+    pub fn new(num_docs: u64) -> DocumentFrequencies {
+        DocumentFrequencies {
+            num_docs,
+            doc_freqs: HashMap::new(),
+        }
+    }
+
+    /// Set the number of documents containing `term`.
+    pub fn insert<S: Into<String>>(&mut self, term: S, doc_freq: u64) {
+        self.doc_freqs.insert(term.into(), doc_freq);
+    }
+}
+
+impl TermStatistics for DocumentFrequencies {
+    fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+
+    fn doc_freq(&self, term: &str) -> u64 {
+        self.doc_freqs.get(term).copied().unwrap_or(0)
+    }
+}
+
+/// Extracts the keywords of texts. See the [module documentation](self).
+#[derive(Clone)]
+pub struct KeywordExtractor {
+    tokenizer: VibratoTokenizer,
+}
+
+impl KeywordExtractor {
+    /// Create an extractor analyzing texts with `tokenizer`. Its char filters are applied, but
+    /// not its filters: the noun phrases are built from the raw morphemes.
+    pub fn new(tokenizer: VibratoTokenizer) -> KeywordExtractor {
+        KeywordExtractor { tokenizer }
+    }
+
+    /// Returns the noun phrases of `text` in order of appearance.
+    pub fn candidates(&self, text: &str) -> Vec<String> {
+        let morphemes = self.tokenizer.char_filtered_morphemes(text);
+        noun_runs(&morphemes)
+            .into_iter()
+            .map(|run| run.iter().map(|t| t.surface.as_str()).collect())
+            .collect()
+    }
+
+    /// Returns the `k` most frequent noun phrases of `text`.
+    pub fn extract(&self, text: &str, k: usize) -> Vec<Keyword> {
+        self.rank(text, k, |count, _| count as f64)
+    }
+
+    /// Returns the `k` noun phrases of `text` with the highest TF-IDF weight against `stats`.
+    ///
+    /// The weight is `tf * (ln((N + 1) / (df + 1)) + 1)`, so that phrases unknown to the corpus
+    /// get the highest weight.
+    pub fn extract_with_stats<S: TermStatistics + ?Sized>(
+        &self,
+        text: &str,
+        stats: &S,
+        k: usize,
+    ) -> Vec<Keyword> {
+        let num_docs = stats.num_docs() as f64;
+        self.rank(text, k, |count, term| {
+            let idf = ((num_docs + 1.0) / (stats.doc_freq(term) as f64 + 1.0)).ln() + 1.0;
+            count as f64 * idf
+        })
+    }
+
+    fn rank<F: Fn(usize, &str) -> f64>(&self, text: &str, k: usize, score: F) -> Vec<Keyword> {
+        // Keywords in order of appearance, and the index of each phrase among them.
+        let mut keywords: Vec<Keyword> = vec![];
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for candidate in self.candidates(text) {
+            match indices.get(&candidate) {
+                Some(&i) => keywords[i].count += 1,
+                None => {
+                    indices.insert(candidate.clone(), keywords.len());
+                    keywords.push(Keyword {
+                        text: candidate,
+                        count: 1,
+                        score: 0.0,
+                    });
+                }
+            }
+        }
+        for keyword in &mut keywords {
+            keyword.score = score(keyword.count, &keyword.text);
+        }
+        // Stable, so that ties keep the order of appearance.
+        keywords.sort_by(|a, b| b.score.total_cmp(&a.score));
+        keywords.truncate(k);
+        keywords
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    const TEXT: &str = "東京大学の東京大学、東京人";
+
+    fn extractor() -> KeywordExtractor {
+        KeywordExtractor::new(test_tokenizer())
+    }
+
+    #[test]
+    fn tf() {
+        let extractor = extractor();
+        assert_eq!(
+            extractor.candidates(TEXT),
+            vec!["東京大学", "東京大学", "東京人"]
+        );
+        let keywords = extractor.extract(TEXT, 10);
+        let keywords = keywords
+            .iter()
+            .map(|k| (k.text.as_str(), k.count))
+            .collect::<Vec<_>>();
+        assert_eq!(keywords, vec![("東京大学", 2), ("東京人", 1)]);
+        assert_eq!(extractor.extract(TEXT, 1).len(), 1);
+    }
+
+    #[test]
+    fn tf_idf() {
+        let mut stats = DocumentFrequencies::new(100);
+        stats.insert("東京大学", 90);
+        stats.insert("東京人", 1);
+        let keywords = extractor().extract_with_stats(TEXT, &stats, 10);
+        let texts = keywords.iter().map(|k| k.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["東京人", "東京大学"]);
+    }
+}
@@ -0,0 +1,26 @@
+//! Reproduction of the default analysis of Lucene's Kuromoji (the `kuromoji` analyzer of
+//! Elasticsearch), used by [`VibratoTokenizer::kuromoji_compatible`].
+use crate::filter::base_form::BaseFormFilter;
+use crate::filter::katakana_stem::KatakanaStemFilter;
+use crate::filter::lower_case::LowerCaseFilter;
+use crate::filter::pos::{PosFilter, KUROMOJI_STOP_TAGS};
+use crate::filter::stop_word::{StopWordFilter, KUROMOJI_STOP_WORDS};
+use crate::filter::width::WidthFilter;
+use crate::search_mode::SearchMode;
+use crate::tokenizer::PositionMode;
+use crate::VibratoTokenizer;
+
+/// Minimum length of katakana words stemmed by `JapaneseKatakanaStemFilter`.
+const KATAKANA_STEM_MIN_LEN: usize = 4;
+
+pub(crate) fn configure(tokenizer: VibratoTokenizer) -> VibratoTokenizer {
+    tokenizer
+        .filter(BaseFormFilter::new())
+        .filter(PosFilter::new(KUROMOJI_STOP_TAGS.iter().copied()))
+        .filter(WidthFilter)
+        .filter(StopWordFilter::new(KUROMOJI_STOP_WORDS.iter().copied()))
+        .filter(KatakanaStemFilter::new(KATAKANA_STEM_MIN_LEN))
+        .filter(LowerCaseFilter)
+        .search_mode(SearchMode::new().keep_compound(true))
+        .position_mode(PositionMode::Sequential)
+}
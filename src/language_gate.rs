@@ -0,0 +1,132 @@
+use tantivy::tokenizer::{BoxTokenStream, Tokenizer as TTokenizer};
+
+use crate::VibratoTokenizer;
+
+/// Default minimum ratio of kana among the letters of a Japanese text.
+const DEFAULT_MIN_KANA_RATIO: f64 = 0.05;
+
+/// Default number of characters inspected to detect the language.
+const DEFAULT_SAMPLE_LEN: usize = 1000;
+
+/// A tokenizer analyzing Japanese texts with Vibrato and the other texts with a fallback
+/// tokenizer, for fields which are sometimes entirely in another language.
+///
+/// The detector is a lightweight heuristic on the scripts of the first 1000 characters: a
+/// text is Japanese if kana make up at least 5% of its letters, or if it has no kana but is
+/// mostly kanji, like headlines. Texts without letters are analyzed by Vibrato. Chinese texts,
+/// which are also mostly kanji, can be sent to the fallback with
+/// [`kanji_only_is_japanese`](Self::kanji_only_is_japanese).
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::tokenizer::SimpleTokenizer;
+/// use tantivy_vibrato::{LanguageGate, VibratoTokenizer};
+///
+/// let japanese = VibratoTokenizer::new("/path/to/dictionary")?;
+/// let tokenizer = LanguageGate::new(japanese, SimpleTokenizer);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LanguageGate<T> {
+    pub(crate) japanese: VibratoTokenizer,
+    pub(crate) fallback: T,
+    min_kana_ratio: f64,
+    kanji_only_is_japanese: bool,
+    sample_len: usize,
+}
+
+impl<T> LanguageGate<T> {
+    /// Create a gate analyzing Japanese texts with `japanese` and the others with `fallback`.
+    pub fn new(japanese: VibratoTokenizer, fallback: T) -> LanguageGate<T> {
+        LanguageGate {
+            japanese,
+            fallback,
+            min_kana_ratio: DEFAULT_MIN_KANA_RATIO,
+            kanji_only_is_japanese: true,
+            sample_len: DEFAULT_SAMPLE_LEN,
+        }
+    }
+
+    /// Minimum ratio of kana among the letters of a Japanese text. The default is 0.05.
+    pub fn min_kana_ratio(mut self, ratio: f64) -> LanguageGate<T> {
+        self.min_kana_ratio = ratio;
+        self
+    }
+
+    /// Whether texts without kana made mostly of kanji are Japanese. Enabled by default;
+    /// disable it to send Chinese texts to the fallback.
+    pub fn kanji_only_is_japanese(mut self, enabled: bool) -> LanguageGate<T> {
+        self.kanji_only_is_japanese = enabled;
+        self
+    }
+
+    /// Number of characters inspected to detect the language. The default is 1000.
+    pub fn sample_len(mut self, len: usize) -> LanguageGate<T> {
+        self.sample_len = len;
+        self
+    }
+
+    /// Returns `true` if `text` is analyzed as Japanese.
+    pub fn is_japanese(&self, text: &str) -> bool {
+        let (mut letters, mut kana, mut kanji) = (0usize, 0usize, 0usize);
+        for c in text.chars().take(self.sample_len) {
+            match c {
+                '\u{3041}'..='\u{309F}' | '\u{30A1}'..='\u{30FA}' | '\u{FF66}'..='\u{FF9D}' => {
+                    kana += 1
+                }
+                '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => {
+                    kanji += 1
+                }
+                _ if c.is_alphabetic() => {}
+                _ => continue,
+            }
+            letters += 1;
+        }
+        if letters == 0 {
+            return true;
+        }
+        let ratio = |n: usize| n as f64 / letters as f64;
+        ratio(kana) >= self.min_kana_ratio && kana > 0
+            || self.kanji_only_is_japanese && kana == 0 && ratio(kanji) >= 0.5
+    }
+}
+
+impl<T: TTokenizer + Clone> TTokenizer for LanguageGate<T> {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        if self.is_japanese(text) {
+            self.japanese.token_stream(text)
+        } else {
+            self.fallback.token_stream(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use tantivy::tokenizer::SimpleTokenizer;
+
+    #[test]
+    fn detect() {
+        let tokenizer = test_tokenizer();
+        let gate = LanguageGate::new(tokenizer, SimpleTokenizer);
+        assert!(gate.is_japanese("すもももももももものうち"));
+        assert!(gate.is_japanese("iPhone 15 Proを買った"));
+        assert!(gate.is_japanese("東京大学入学式"));
+        assert!(gate.is_japanese("2023"));
+        assert!(!gate.is_japanese("The quick brown fox jumps over the lazy dog"));
+        assert!(!gate
+            .clone()
+            .kanji_only_is_japanese(false)
+            .is_japanese("我们在北京学习汉语"));
+
+        let mut stream = gate.token_stream("Hello, world");
+        let mut texts = vec![];
+        while let Some(token) = stream.next() {
+            texts.push(token.text.clone());
+        }
+        assert_eq!(texts, vec!["Hello", "world"]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use vibrato::dictionary::LexType;
+use vibrato::Tokenizer;
+
+use crate::features::DictionaryKind;
+use crate::lookup;
+
+/// Maximum length in characters of the candidate words searched by [`Lattice`].
+const MAX_WORD_LEN: usize = 24;
+
+/// A node of a [`Lattice`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatticeNode {
+    /// Surface form. `BOS` and `EOS` for the sentence boundaries.
+    pub surface: String,
+    /// Character offset of the first character.
+    pub char_from: usize,
+    /// Character offset of the last character + 1.
+    pub char_to: usize,
+    /// Feature string. Empty for the sentence boundaries.
+    pub feature: String,
+    /// Lexicon the entry comes from. `None` for the sentence boundaries.
+    pub lex_type: Option<LexType>,
+    /// Word cost.
+    pub word_cost: i16,
+    /// Whether the node is on the selected path.
+    pub on_best_path: bool,
+}
+
+/// An edge between two nodes of a [`Lattice`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatticeEdge {
+    /// Index of the left node.
+    pub from: usize,
+    /// Index of the right node.
+    pub to: usize,
+    /// Connection cost. Only known for the edges on the selected path.
+    pub cost: Option<i32>,
+    /// Whether the edge is on the selected path.
+    pub on_best_path: bool,
+}
+
+/// Lattice of a text, returned by [`VibratoTokenizer::lattice`](crate::VibratoTokenizer::lattice).
+///
+/// Vibrato only exposes the selected (lowest cost) path, so the other nodes are the words found
+/// by analyzing every substring of up to 24 characters on its own, and the connection costs are
+/// only known along the selected path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lattice {
+    /// Nodes. The first one is BOS and the last one is EOS.
+    pub nodes: Vec<LatticeNode>,
+    /// Edges between adjacent nodes.
+    pub edges: Vec<LatticeEdge>,
+    /// Total cost of the selected path, excluding the connection to EOS.
+    pub best_cost: i32,
+}
+
+impl Lattice {
+    pub(crate) fn build(tokenizer: &Tokenizer, kind: DictionaryKind, text: &str) -> Lattice {
+        let chars = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+        let len_char = chars.len();
+        let byte = |pos: usize| chars.get(pos).copied().unwrap_or(text.len());
+
+        let mut nodes = vec![boundary("BOS", 0)];
+        let mut path = vec![0];
+        let mut path_costs = vec![];
+        let mut best_cost = 0;
+
+        let mut worker = tokenizer.new_worker();
+        worker.reset_sentence(text);
+        worker.tokenize();
+        for token in worker.token_iter() {
+            path_costs.push(token.total_cost() - best_cost - i32::from(token.word_cost()));
+            best_cost = token.total_cost();
+            path.push(nodes.len());
+            nodes.push(LatticeNode {
+                surface: token.surface().to_string(),
+                char_from: token.range_char().start,
+                char_to: token.range_char().end,
+                feature: token.feature().to_string(),
+                lex_type: Some(token.lex_type()),
+                word_cost: token.word_cost(),
+                on_best_path: true,
+            });
+        }
+
+        for from in 0..len_char {
+            for to in from + 1..=len_char.min(from + MAX_WORD_LEN) {
+                let Some(entry) = lookup::probe(&mut worker, kind, &text[byte(from)..byte(to)])
+                else {
+                    continue;
+                };
+                let duplicated = nodes.iter().any(|n| {
+                    n.char_from == from && n.char_to == to && n.feature == entry.feature
+                });
+                if !duplicated {
+                    nodes.push(LatticeNode {
+                        surface: entry.surface,
+                        char_from: from,
+                        char_to: to,
+                        feature: entry.feature,
+                        lex_type: Some(entry.lex_type),
+                        word_cost: entry.word_cost,
+                        on_best_path: false,
+                    });
+                }
+            }
+        }
+
+        path.push(nodes.len());
+        nodes.push(boundary("EOS", len_char));
+
+        let eos = nodes.len() - 1;
+        let mut edges = vec![];
+        for (i, from) in nodes.iter().enumerate().take(eos) {
+            for (j, to) in nodes.iter().enumerate().skip(1) {
+                if i == j || from.char_to != to.char_from {
+                    continue;
+                }
+                let on_path = path.windows(2).position(|w| w[0] == i && w[1] == j);
+                edges.push(LatticeEdge {
+                    from: i,
+                    to: j,
+                    cost: on_path.and_then(|k| path_costs.get(k).copied()),
+                    on_best_path: on_path.is_some(),
+                });
+            }
+        }
+
+        Lattice {
+            nodes,
+            edges,
+            best_cost,
+        }
+    }
+
+    /// Returns the nodes of the selected path, excluding BOS and EOS.
+    pub fn best_path(&self) -> impl Iterator<Item = &LatticeNode> {
+        self.nodes
+            .iter()
+            .filter(|n| n.on_best_path && n.lex_type.is_some())
+    }
+
+    /// Render the lattice in the GraphViz DOT language.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph lattice {\n  rankdir=LR;\n  node [shape=box];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = match node.lex_type {
+                Some(lex_type) => format!(
+                    "{}\\n{}\\n{:?} cost={}",
+                    escape(&node.surface),
+                    escape(&node.feature),
+                    lex_type,
+                    node.word_cost
+                ),
+                None => node.surface.clone(),
+            };
+            let style = if node.on_best_path {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(dot, "  n{i} [label=\"{label}\"{style}];").unwrap();
+        }
+        for edge in &self.edges {
+            let label = edge
+                .cost
+                .map(|c| format!(" label=\"{c}\""))
+                .unwrap_or_default();
+            let style = if edge.on_best_path {
+                " color=red penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(dot, "  n{} -> n{} [{label}{style} ];", edge.from, edge.to).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn boundary(surface: &str, pos: usize) -> LatticeNode {
+    LatticeNode {
+        surface: surface.to_string(),
+        char_from: pos,
+        char_to: pos,
+        feature: String::new(),
+        lex_type: None,
+        word_cost: 0,
+        on_best_path: true,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
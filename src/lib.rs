@@ -1,3 +1,78 @@
+mod analyzer;
+mod builder;
+pub mod char_filter;
+mod chars;
+pub mod compat;
+#[cfg(all(feature = "serde", feature = "fs"))]
+pub mod config;
+pub mod diff;
+#[cfg(feature = "download")]
+pub mod download;
+#[cfg(feature = "builder")]
+mod dictionary_builder;
+mod entity_field;
+mod error_policy;
+mod explain;
+pub mod features;
+pub mod filter;
+mod fingerprint;
+mod gazetteer;
+pub mod keywords;
+mod kuromoji;
+mod language_gate;
+mod lattice;
+pub mod log_context;
+mod lookup;
+pub mod mecab;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod multi_dict;
+mod nbest;
+pub mod oov;
+mod per_document;
+pub mod recognizer;
+mod reloadable;
+mod script_router;
+mod search_mode;
+mod sentence;
+mod sort_key;
+pub mod stats;
+mod token;
 mod tokenizer;
+mod utf16;
+mod worker_pool;
 
-pub use tokenizer::{TantivyVibratoError, VibratoTokenizer};
+pub use analyzer::register_japanese_analyzer;
+pub use builder::VibratoTokenizerBuilder;
+#[cfg(feature = "builder")]
+pub use dictionary_builder::DictionaryBuilder;
+pub use char_filter::{CharFilter, OffsetMap, OffsetMapBuilder};
+pub use entity_field::EntityTokens;
+pub use error_policy::ErrorPolicy;
+pub use explain::{CharFilterStep, Explanation, FilterStep, TokenEffect};
+pub use features::{DictionaryKind, Features};
+pub use filter::MorphFilter;
+pub use fingerprint::Fingerprint;
+pub use gazetteer::Gazetteer;
+pub use language_gate::LanguageGate;
+pub use lattice::{Lattice, LatticeEdge, LatticeNode};
+pub use lookup::DictionaryEntry;
+pub use multi_dict::MultiDictTokenizer;
+pub use per_document::{PerDocumentTokenizer, TokenizerSelector};
+pub use reloadable::ReloadableVibratoTokenizer;
+pub use script_router::ScriptRouter;
+pub use search_mode::SearchMode;
+pub use sort_key::SortKey;
+pub use token::MorphToken;
+pub use tokenizer::{PositionMode, TantivyVibratoError, VibratoTokenizer};
+pub use utf16::{Utf16Offsets, Utf16Token};
+pub use vibrato::dictionary::LexType;
+
+/// Returns a tokenizer with the dictionary `system.dic` of the project root directory, for the
+/// tests.
+#[cfg(test)]
+pub(crate) fn test_tokenizer() -> VibratoTokenizer {
+    let file = std::fs::File::open("./system.dic")
+        .expect("system.dic is required in the project root directory");
+    VibratoTokenizer::from_reader(file).unwrap()
+}
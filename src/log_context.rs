@@ -0,0 +1,153 @@
+//! Context attached to the log records of the analyzer.
+//!
+//! Warnings and errors of the analyzer are logged through the [`log`] crate with target
+//! `tantivy_vibrato`. Attach a document id, a field name or any other key-value pair so that
+//! they can be traced back to a document:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy::tokenizer::Tokenizer;
+//! use tantivy_vibrato::log_context::{self, LogContext};
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! // Context shared by every text analyzed by this tokenizer.
+//! let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?
+//!     .log_context(LogContext::new().field("body"));
+//!
+//! // Context of the texts analyzed on the current thread while the guard is alive.
+//! let _guard = log_context::enter(LogContext::new().document_id("doc-42"));
+//! let stream = tokenizer.token_stream("すもももももももものうち");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Note that tantivy's `IndexWriter` tokenizes documents on its own indexing threads, where
+//! the scopes entered by the caller are not visible. Use the tokenizer-level context there.
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+thread_local! {
+    static SCOPES: RefCell<Vec<LogContext>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Key-value pairs appended to the log records of the analyzer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogContext {
+    entries: Vec<(String, String)>,
+}
+
+impl LogContext {
+    /// Create an empty context.
+    pub fn new() -> LogContext {
+        LogContext::default()
+    }
+
+    /// Add a key-value pair.
+    pub fn with<K: Into<String>, V: ToString>(mut self, key: K, value: V) -> LogContext {
+        self.entries.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Add the id of the document being analyzed.
+    pub fn document_id<V: ToString>(self, id: V) -> LogContext {
+        self.with("document_id", id)
+    }
+
+    /// Add the name of the field being analyzed.
+    pub fn field<V: ToString>(self, field: V) -> LogContext {
+        self.with("field", field)
+    }
+
+    /// Returns the key-value pairs.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns `true` if there is no key-value pair.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn extend(&mut self, other: &LogContext) {
+        self.entries.extend(other.entries.iter().cloned());
+    }
+}
+
+impl fmt::Display for LogContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{key}={value:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Guard returned by [`enter`]. The context is removed when it is dropped.
+#[must_use = "the context is removed when the guard is dropped"]
+pub struct LogContextGuard {
+    // The scope belongs to the current thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for LogContextGuard {
+    fn drop(&mut self) {
+        SCOPES.with(|scopes| scopes.borrow_mut().pop());
+    }
+}
+
+/// Attach `context` to the log records of the analysis performed on the current thread until
+/// the returned guard is dropped. Scopes can be nested.
+pub fn enter(context: LogContext) -> LogContextGuard {
+    SCOPES.with(|scopes| scopes.borrow_mut().push(context));
+    LogContextGuard {
+        _not_send: PhantomData,
+    }
+}
+
+/// Returns the context of the current thread, merging the nested scopes.
+pub fn current() -> LogContext {
+    SCOPES.with(|scopes| {
+        let mut context = LogContext::new();
+        for scope in scopes.borrow().iter() {
+            context.extend(scope);
+        }
+        context
+    })
+}
+
+/// Log `args` with the tokenizer-level context and the context of the current thread.
+pub(crate) fn log(level: log::Level, context: &LogContext, args: fmt::Arguments<'_>) {
+    if !log::log_enabled!(target: "tantivy_vibrato", level) {
+        return;
+    }
+    let mut merged = context.clone();
+    merged.extend(&current());
+    if merged.is_empty() {
+        log::log!(target: "tantivy_vibrato", level, "{args}");
+    } else {
+        log::log!(target: "tantivy_vibrato", level, "{args} [{merged}]");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_scopes() {
+        assert!(current().is_empty());
+        {
+            let _outer = enter(LogContext::new().document_id(42));
+            {
+                let _inner = enter(LogContext::new().field("body"));
+                assert_eq!(current().to_string(), r#"document_id="42" field="body""#);
+            }
+            assert_eq!(current().to_string(), r#"document_id="42""#);
+        }
+        assert!(current().is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+use vibrato::dictionary::LexType;
+use vibrato::tokenizer::worker::Worker;
+
+use crate::features::{DictionaryKind, Features};
+
+/// A dictionary entry, returned by
+/// [`VibratoTokenizer::best_entry`](crate::VibratoTokenizer::best_entry).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryEntry {
+    /// Surface form.
+    pub surface: String,
+    /// Comma separated feature string.
+    pub feature: String,
+    /// Layout of `feature`.
+    pub kind: DictionaryKind,
+    /// Lexicon the entry comes from.
+    pub lex_type: LexType,
+    /// Left connection id.
+    pub left_id: u16,
+    /// Right connection id.
+    pub right_id: u16,
+    /// Word cost.
+    pub word_cost: i16,
+}
+
+impl DictionaryEntry {
+    /// Returns the parsed feature string.
+    pub fn features(&self) -> Features<'_> {
+        Features::parse(&self.feature, self.kind)
+    }
+}
+
+/// Analyze `surface` on its own and return the entry if it is analyzed as a single word.
+pub(crate) fn probe(
+    worker: &mut Worker<'_>,
+    kind: DictionaryKind,
+    surface: &str,
+) -> Option<DictionaryEntry> {
+    if surface.is_empty() {
+        return None;
+    }
+    worker.reset_sentence(surface);
+    worker.tokenize();
+    if worker.num_tokens() != 1 {
+        return None;
+    }
+    let token = worker.token(0);
+    Some(DictionaryEntry {
+        surface: token.surface().to_string(),
+        feature: token.feature().to_string(),
+        kind,
+        lex_type: token.lex_type(),
+        left_id: token.left_id(),
+        right_id: token.right_id(),
+        word_cost: token.word_cost(),
+    })
+}
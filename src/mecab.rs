@@ -0,0 +1,81 @@
+//! Render analysis results in the output formats of MeCab.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy_vibrato::mecab::{format, OutputFormat};
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+//! let morphemes = tokenizer.morphemes("すもももももももものうち");
+//! print!("{}", format(&morphemes, OutputFormat::Mecab));
+//! # Ok(())
+//! # }
+//! ```
+use crate::token::MorphToken;
+
+/// Output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One `surface\tfeature` line per token followed by `EOS`, like `mecab`.
+    Mecab,
+    /// Surfaces separated by spaces, like `mecab -Owakati`.
+    Wakati,
+}
+
+/// Render the tokens of a sentence in `output_format`, including the trailing newline.
+pub fn format(tokens: &[MorphToken], output_format: OutputFormat) -> String {
+    let mut output = String::new();
+    match output_format {
+        OutputFormat::Mecab => {
+            for token in tokens {
+                output.push_str(&token.surface);
+                output.push('\t');
+                output.push_str(&token.feature);
+                output.push('\n');
+            }
+            output.push_str("EOS\n");
+        }
+        OutputFormat::Wakati => {
+            for (i, token) in tokens.iter().enumerate() {
+                if i > 0 {
+                    output.push(' ');
+                }
+                output.push_str(&token.surface);
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn mecab() {
+        let morphemes = test_tokenizer().morphemes("すもももも");
+        assert_eq!(
+            format(&morphemes, OutputFormat::Mecab),
+            "すもも\t名詞,一般,*,*,*,*,すもも,スモモ,スモモ\n\
+             もも\t名詞,一般,*,*,*,*,もも,モモ,モモ\n\
+             EOS\n"
+        );
+    }
+
+    #[test]
+    fn wakati() {
+        let morphemes = test_tokenizer().morphemes("すもももももももものうち");
+        assert_eq!(
+            format(&morphemes, OutputFormat::Wakati),
+            "すもも も もも も もも の うち\n"
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(format(&[], OutputFormat::Mecab), "EOS\n");
+        assert_eq!(format(&[], OutputFormat::Wakati), "\n");
+    }
+}
@@ -0,0 +1,45 @@
+//! Analyzer health metrics reported through the [`metrics`] facade.
+//!
+//! Only available with the `metrics` feature. Install any `metrics` recorder
+//! (e.g. a Prometheus exporter) in the application and the tokenizer will report:
+//!
+//! - [`DOCUMENTS_TOKENIZED`]: number of texts passed to `token_stream`
+//! - [`TOKENS_EMITTED`]: number of tokens produced
+//! - [`ERRORS`]: number of errors, labelled with `kind`
+//! - [`TOKENIZATION_DURATION`]: time spent tokenizing a single text, in seconds
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+
+/// Counter of texts tokenized.
+pub const DOCUMENTS_TOKENIZED: &str = "tantivy_vibrato_documents_tokenized_total";
+/// Counter of tokens emitted.
+pub const TOKENS_EMITTED: &str = "tantivy_vibrato_tokens_emitted_total";
+/// Counter of errors, labelled with `kind`.
+pub const ERRORS: &str = "tantivy_vibrato_errors_total";
+/// Histogram of tokenization latency in seconds.
+pub const TOKENIZATION_DURATION: &str = "tantivy_vibrato_tokenization_duration_seconds";
+
+/// Register descriptions of the metrics with the installed recorder.
+///
+/// Calling this is optional; it only adds help texts and units to the exported metrics.
+pub fn describe() {
+    describe_counter!(DOCUMENTS_TOKENIZED, Unit::Count, "Number of texts tokenized.");
+    describe_counter!(TOKENS_EMITTED, Unit::Count, "Number of tokens emitted.");
+    describe_counter!(ERRORS, Unit::Count, "Number of errors by kind.");
+    describe_histogram!(
+        TOKENIZATION_DURATION,
+        Unit::Seconds,
+        "Time spent tokenizing a single text."
+    );
+}
+
+pub(crate) fn record_tokenization(num_tokens: usize, elapsed: Duration) {
+    counter!(DOCUMENTS_TOKENIZED).increment(1);
+    counter!(TOKENS_EMITTED).increment(num_tokens as u64);
+    histogram!(TOKENIZATION_DURATION).record(elapsed.as_secs_f64());
+}
+
+pub(crate) fn record_error(kind: &'static str) {
+    counter!(ERRORS, "kind" => kind).increment(1);
+}
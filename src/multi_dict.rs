@@ -0,0 +1,117 @@
+use tantivy::tokenizer::{BoxTokenStream, Tokenizer as TTokenizer};
+
+use crate::fingerprint::{Fingerprint, Hasher};
+use crate::script_router::Emitter;
+use crate::tokenizer::VibratoTokenStream;
+use crate::VibratoTokenizer;
+
+/// Characters ending a sentence.
+const SENTENCE_ENDS: &[char] = &['。', '！', '？', '!', '?', '\n'];
+
+/// A tokenizer analyzing each sentence with the dictionary giving it the lowest cost, for
+/// domains where a single dictionary has a poor coverage, e.g. a general dictionary and a
+/// medical one.
+///
+/// Each sentence is analyzed with every dictionary, and the segmentation of the lowest total
+/// cost is emitted, the first dictionary winning ties. The costs are only comparable if the
+/// dictionaries are trained on the same scale, e.g. a general dictionary and a domain
+/// dictionary built from the same model. To add a few domain words to a dictionary, a
+/// [`Gazetteer`](crate::Gazetteer) is simpler and cheaper.
+///
+/// Each dictionary is used with the char filters, recognizers and filters of its tokenizer.
+/// Positions follow the position mode of the first tokenizer.
+#[derive(Clone)]
+pub struct MultiDictTokenizer {
+    tokenizers: Vec<VibratoTokenizer>,
+}
+
+impl MultiDictTokenizer {
+    /// Create a tokenizer with `primary`, which also wins the ties.
+    pub fn new(primary: VibratoTokenizer) -> MultiDictTokenizer {
+        MultiDictTokenizer {
+            tokenizers: vec![primary],
+        }
+    }
+
+    /// Add a dictionary competing with the previous ones.
+    pub fn dictionary(mut self, tokenizer: VibratoTokenizer) -> MultiDictTokenizer {
+        self.tokenizers.push(tokenizer);
+        self
+    }
+
+    /// Fingerprint of the analyzer, combining the fingerprints of the tokenizers.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new();
+        for tokenizer in &self.tokenizers {
+            hasher.write(&tokenizer.fingerprint().as_u64().to_le_bytes());
+        }
+        hasher.fingerprint()
+    }
+
+    /// Returns the tokenizer giving `sentence` the lowest cost.
+    fn best(&self, sentence: &str) -> &VibratoTokenizer {
+        let mut best = &self.tokenizers[0];
+        let mut best_cost = total_cost(best, sentence);
+        for tokenizer in &self.tokenizers[1..] {
+            let cost = total_cost(tokenizer, sentence);
+            if cost < best_cost {
+                best = tokenizer;
+                best_cost = cost;
+            }
+        }
+        best
+    }
+}
+
+/// Returns the cost of the best segmentation of `sentence` with `tokenizer`.
+fn total_cost(tokenizer: &VibratoTokenizer, sentence: &str) -> i64 {
+    tokenizer
+        .char_filtered_morphemes(sentence)
+        .iter()
+        .rev()
+        .find(|t| !t.overlay)
+        .map_or(0, |t| i64::from(t.total_cost))
+}
+
+impl TTokenizer for MultiDictTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let mut emitter = Emitter::new(self.tokenizers[0].has_sequential_positions());
+        let mut start = 0;
+        let mut char_offset = 0;
+        for sentence in text.split_inclusive(SENTENCE_ENDS) {
+            let tokens = self.best(sentence).analyze(sentence, None);
+            emitter.extend(tokens, &(start..start + sentence.len()), char_offset);
+            start += sentence.len();
+            char_offset += sentence.chars().count();
+        }
+        BoxTokenStream::from(VibratoTokenStream::new(emitter.tokens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::{Gazetteer, PositionMode};
+
+    #[test]
+    fn lowest_cost() {
+        let general = test_tokenizer().position_mode(PositionMode::CharOffset);
+        let domain = general
+            .clone()
+            .gazetteer(&Gazetteer::new().entity("新東京大学"))
+            .unwrap();
+        let tokenizer = MultiDictTokenizer::new(general.clone()).dictionary(domain);
+        assert_ne!(tokenizer.fingerprint(), general.fingerprint());
+
+        let text = "新東京大学に行きました。新東京";
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.offset_from, token.position));
+        }
+        assert_eq!(tokens[0], ("新東京大学".to_string(), 0, 0));
+        let last = tokens.last().unwrap();
+        assert_eq!(last, &("東京".to_string(), text.len() - "東京".len(), 13));
+    }
+}
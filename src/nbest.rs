@@ -0,0 +1,120 @@
+//! Alternative segmentations emitted on top of the best one.
+//!
+//! Vibrato only returns the best path, so the alternatives of each word of the best path are
+//! found by forcing a word boundary inside it: both sides are analyzed on their own and the
+//! segmentations are ranked by the sum of their path costs. Adjacent words which also form a
+//! single dictionary word are added as alternatives as well.
+use vibrato::tokenizer::worker::Worker;
+
+use crate::features::DictionaryKind;
+use crate::lookup;
+use crate::token::MorphToken;
+use crate::worker_pool::WorkerPool;
+
+struct Segmentation {
+    tokens: Vec<MorphToken>,
+    cost: i64,
+}
+
+/// Returns the tokens of up to `k - 1` alternative segmentations of each word of `tokens`,
+/// flagged as overlays.
+pub(crate) fn alternatives(
+    workers: &WorkerPool,
+    kind: DictionaryKind,
+    text: &str,
+    tokens: &[MorphToken],
+    k: usize,
+) -> Vec<MorphToken> {
+    if k < 2 {
+        return vec![];
+    }
+    let mut worker = workers.get();
+    let mut overlays = vec![];
+
+    for token in tokens.iter().filter(|t| !t.overlay) {
+        let surface = &text[token.offset_from..token.offset_to];
+        let mut candidates: Vec<Segmentation> = vec![];
+        for (chars, (cut, _)) in surface.char_indices().enumerate().skip(1) {
+            let cut = (token.offset_from + cut, token.char_from + chars);
+            let mut left = analyze(
+                &mut worker,
+                kind,
+                text,
+                (token.offset_from, token.char_from),
+                cut.0,
+            );
+            let right = analyze(&mut worker, kind, text, cut, token.offset_to);
+            left.cost += right.cost;
+            left.tokens.extend(right.tokens);
+            let duplicated = candidates
+                .iter()
+                .any(|c| same_boundaries(&c.tokens, &left.tokens));
+            if !duplicated {
+                candidates.push(left);
+            }
+        }
+        candidates.sort_by_key(|c| c.cost);
+        for segmentation in candidates.into_iter().take(k - 1) {
+            overlays.extend(segmentation.tokens);
+        }
+    }
+
+    let primary = tokens.iter().filter(|t| !t.overlay).collect::<Vec<_>>();
+    for pair in primary.windows(2) {
+        let (from, to) = (pair[0].offset_from, pair[1].offset_to);
+        if let Some(entry) = lookup::probe(&mut worker, kind, &text[from..to]) {
+            overlays.push(MorphToken {
+                text: entry.surface.clone(),
+                surface: entry.surface,
+                offset_from: from,
+                offset_to: to,
+                char_from: pair[0].char_from,
+                char_to: pair[1].char_to,
+                feature: entry.feature,
+                kind,
+                lex_type: entry.lex_type,
+                word_cost: entry.word_cost,
+                total_cost: 0,
+                overlay: true,
+                negated: false,
+            });
+        }
+    }
+
+    overlays
+}
+
+/// Analyze `text[from.0..to]` on its own, where `from` is the byte and char offsets of its
+/// start.
+fn analyze(
+    worker: &mut Worker<'_>,
+    kind: DictionaryKind,
+    text: &str,
+    from: (usize, usize),
+    to: usize,
+) -> Segmentation {
+    let (from, char_base) = from;
+    worker.reset_sentence(&text[from..to]);
+    worker.tokenize();
+    let tokens = worker
+        .token_iter()
+        .map(|t| {
+            let mut token = MorphToken::from_vibrato(&t, kind);
+            token.offset_from += from;
+            token.offset_to += from;
+            token.char_from += char_base;
+            token.char_to += char_base;
+            token.overlay = true;
+            token
+        })
+        .collect::<Vec<_>>();
+    let cost = tokens.last().map_or(0, |t| i64::from(t.total_cost));
+    Segmentation { tokens, cost }
+}
+
+fn same_boundaries(a: &[MorphToken], b: &[MorphToken]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.char_from == b.char_from && a.char_to == b.char_to)
+}
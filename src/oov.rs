@@ -0,0 +1,222 @@
+//! Out-of-vocabulary (OOV) analysis of a corpus.
+//!
+//! Words missing from the dictionary are analyzed as unknown words, which are often split or
+//! merged wrongly and so hard to search. [`OovAnalyzer`] analyzes a sample of the documents,
+//! reports the rate of unknown words and ranks the most frequent ones as candidate entries of
+//! a [`Gazetteer`], with the part of speech given by Vibrato's unknown word handling:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy_vibrato::oov::OovAnalyzer;
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! let tokenizer = VibratoTokenizer::new("/path/to/dictionary")?;
+//! let report = OovAnalyzer::new(tokenizer.clone()).analyze(["ChatGPTで検索した"]);
+//! println!("{:.2}% of unknown words", report.unknown_rate() * 100.0);
+//! for candidate in &report.candidates {
+//!     println!("{}\t{}\t{}", candidate.surface, candidate.count, candidate.feature);
+//! }
+//! // After reviewing the candidates:
+//! let tokenizer = tokenizer.gazetteer(&report.gazetteer())?;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use vibrato::dictionary::LexType;
+
+use crate::gazetteer::{entry_feature, Gazetteer};
+use crate::token::MorphToken;
+use crate::VibratoTokenizer;
+
+/// Default minimum number of occurrences of a candidate.
+const DEFAULT_MIN_COUNT: usize = 2;
+
+/// An unknown word suggested as a dictionary entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OovCandidate {
+    /// Surface form of the unknown word.
+    pub surface: String,
+    /// Number of occurrences in the sample.
+    pub count: usize,
+    /// Number of documents of the sample containing it.
+    pub doc_freq: usize,
+    /// Suggested feature string of the entry.
+    pub feature: String,
+}
+
+/// Unknown words of a sample of documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OovReport {
+    /// Number of documents analyzed.
+    pub documents: usize,
+    /// Number of words analyzed, spaces and symbols excluded.
+    pub words: usize,
+    /// Number of unknown words.
+    pub unknown_words: usize,
+    /// Unknown words occurring at least the minimum number of times, most frequent first.
+    pub candidates: Vec<OovCandidate>,
+}
+
+impl OovReport {
+    /// Returns the ratio of unknown words among the words analyzed.
+    pub fn unknown_rate(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.unknown_words as f64 / self.words as f64
+        }
+    }
+
+    /// Returns a gazetteer of the candidates with their suggested feature strings.
+    pub fn gazetteer(&self) -> Gazetteer {
+        self.candidates.iter().fold(Gazetteer::new(), |g, c| {
+            g.entity_with_feature(c.surface.as_str(), c.feature.as_str())
+        })
+    }
+}
+
+/// Analyzes the unknown words of a corpus. See the [module documentation](self).
+#[derive(Clone)]
+pub struct OovAnalyzer {
+    tokenizer: VibratoTokenizer,
+    min_count: usize,
+    max_documents: Option<usize>,
+}
+
+impl OovAnalyzer {
+    /// Create an analyzer with `tokenizer`. Its char filters and recognizers are applied, so
+    /// that the spans of the recognizers are not reported, but not its filters.
+    pub fn new(tokenizer: VibratoTokenizer) -> OovAnalyzer {
+        OovAnalyzer {
+            tokenizer,
+            min_count: DEFAULT_MIN_COUNT,
+            max_documents: None,
+        }
+    }
+
+    /// Minimum number of occurrences of the candidates. The default is 2.
+    pub fn min_count(mut self, min_count: usize) -> OovAnalyzer {
+        self.min_count = min_count;
+        self
+    }
+
+    /// Stop after `max_documents` documents. The whole corpus is analyzed by default.
+    pub fn max_documents(mut self, max_documents: usize) -> OovAnalyzer {
+        self.max_documents = Some(max_documents);
+        self
+    }
+
+    /// Analyze the documents of `corpus`.
+    pub fn analyze<I, S>(&self, corpus: I) -> OovReport
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut report = OovReport::default();
+        // Count, document frequency and feature of each unknown word.
+        let mut unknown: HashMap<String, (usize, usize, String)> = HashMap::new();
+        let corpus = corpus
+            .into_iter()
+            .take(self.max_documents.unwrap_or(usize::MAX));
+        for doc in corpus {
+            report.documents += 1;
+            let mut seen = HashSet::new();
+            for token in self.tokenizer.unrecognized_morphemes(doc.as_ref()) {
+                if token.overlay || !token.surface.chars().any(char::is_alphanumeric) {
+                    continue;
+                }
+                report.words += 1;
+                if token.lex_type != LexType::Unknown {
+                    continue;
+                }
+                report.unknown_words += 1;
+                if token.surface.chars().all(|c| c.is_numeric()) {
+                    continue;
+                }
+                let entry = unknown
+                    .entry(token.surface.clone())
+                    .or_insert_with(|| (0, 0, suggested_feature(&token)));
+                entry.0 += 1;
+                if seen.insert(token.surface) {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let mut candidates = unknown
+            .into_iter()
+            .filter(|(_, (count, _, _))| *count >= self.min_count)
+            .map(|(surface, (count, doc_freq, feature))| OovCandidate {
+                surface,
+                count,
+                doc_freq,
+                feature,
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.surface.cmp(&b.surface))
+        });
+        report.candidates = candidates;
+        report
+    }
+}
+
+/// Returns the feature string of an entry for the unknown word `token`, with its part of
+/// speech and its surface as base form.
+fn suggested_feature(token: &MorphToken) -> String {
+    let features = token.features();
+    let pos = features.pos();
+    let pos = [0, 1, 2, 3].map(|i| pos.get(i).copied().unwrap_or("*"));
+    entry_feature(&token.surface, token.kind, pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recognizer::HashtagRecognizer;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn candidates() {
+        let tokenizer = test_tokenizer().recognizer(HashtagRecognizer::new());
+        let corpus = ["ChatGPTと東京 #タグ", "ChatGPTとChatGPT 2023", "東京"];
+        let report = OovAnalyzer::new(tokenizer.clone()).analyze(corpus);
+        assert_eq!(report.documents, 3);
+        // ChatGPT x3, と x2, 2023 and 東京 x2.
+        assert_eq!(report.words, 8);
+        assert_eq!(report.unknown_words, 6);
+        assert_eq!(
+            report.candidates,
+            vec![
+                OovCandidate {
+                    surface: "ChatGPT".to_string(),
+                    count: 3,
+                    doc_freq: 2,
+                    feature: "名詞,固有名詞,組織,*,*,*,ChatGPT,*,*".to_string(),
+                },
+                OovCandidate {
+                    surface: "と".to_string(),
+                    count: 2,
+                    doc_freq: 2,
+                    feature: "名詞,一般,*,*,*,*,と,*,*".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            OovAnalyzer::new(tokenizer.clone())
+                .max_documents(1)
+                .min_count(1)
+                .analyze(corpus)
+                .candidates
+                .len(),
+            2
+        );
+
+        let tokenizer = tokenizer.gazetteer(&report.gazetteer()).unwrap();
+        let report = OovAnalyzer::new(tokenizer).analyze(corpus);
+        assert_eq!(report.unknown_words, 1);
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use tantivy::tokenizer::{PreTokenizedString, TextAnalyzer};
+
+/// Chooses the analyzer configuration of a document, e.g. from its language or its source.
+pub trait TokenizerSelector<D: ?Sized>: Send + Sync {
+    /// Returns the name of the configuration analyzing `doc`, or `None` for the default one.
+    fn select<'a>(&'a self, doc: &'a D) -> Option<&'a str>;
+}
+
+/// Analyzes each document with the configuration chosen by a [`TokenizerSelector`], for
+/// heterogeneous corpora indexed in one field, e.g. news articles and chat messages needing
+/// different filters.
+///
+/// tantivy analyzes a field with the same tokenizer for every document, so the documents are
+/// analyzed by the application with [`pre_tokenize`](Self::pre_tokenize) and indexed as
+/// pre-tokenized strings. Configurations derived from the same
+/// [`VibratoTokenizer`](crate::VibratoTokenizer) with `clone` share its dictionary, which is
+/// loaded once.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::schema::Value;
+/// use tantivy_vibrato::filter::SentenceFinalParticleFilter;
+/// use tantivy_vibrato::{PerDocumentTokenizer, TokenizerSelector, VibratoTokenizer};
+///
+/// struct Post {
+///     source: String,
+///     body: String,
+/// }
+///
+/// struct BySource;
+///
+/// impl TokenizerSelector<Post> for BySource {
+///     fn select<'a>(&'a self, post: &'a Post) -> Option<&'a str> {
+///         Some(post.source.as_str())
+///     }
+/// }
+///
+/// let news = VibratoTokenizer::new("/path/to/dictionary")?;
+/// let chat = news.clone().filter(SentenceFinalParticleFilter::new());
+/// let tokenizer = PerDocumentTokenizer::new(news, BySource).configuration("chat", chat);
+///
+/// let post = Post { source: "chat".to_string(), body: "行きましたよね".to_string() };
+/// let value = Value::PreTokStr(tokenizer.pre_tokenize(&post, &post.body));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The configuration analyzing the queries must emit the same terms for the same words. Pick
+/// it with [`analyzer`](Self::analyzer) when the queries have the same attributes, e.g. the
+/// language of the user.
+pub struct PerDocumentTokenizer<S> {
+    default: TextAnalyzer,
+    configurations: HashMap<String, TextAnalyzer>,
+    selector: S,
+}
+
+impl<S> PerDocumentTokenizer<S> {
+    /// Create a tokenizer choosing configurations with `selector`, analyzing the documents
+    /// without configuration with `default`.
+    pub fn new<A: Into<TextAnalyzer>>(default: A, selector: S) -> PerDocumentTokenizer<S> {
+        PerDocumentTokenizer {
+            default: default.into(),
+            configurations: HashMap::new(),
+            selector,
+        }
+    }
+
+    /// Add the configuration `name`, e.g. a [`VibratoTokenizer`](crate::VibratoTokenizer) or a
+    /// `TextAnalyzer` wrapping it.
+    pub fn configuration<N: Into<String>, A: Into<TextAnalyzer>>(
+        mut self,
+        name: N,
+        analyzer: A,
+    ) -> PerDocumentTokenizer<S> {
+        self.configurations.insert(name.into(), analyzer.into());
+        self
+    }
+
+    /// Returns the configuration `name`, or the default one if there is no such configuration.
+    pub fn analyzer(&self, name: Option<&str>) -> &TextAnalyzer {
+        name.and_then(|name| self.configurations.get(name))
+            .unwrap_or(&self.default)
+    }
+
+    /// Returns the configuration analyzing `doc`.
+    pub fn analyzer_for<'a, D: ?Sized>(&'a self, doc: &'a D) -> &'a TextAnalyzer
+    where
+        S: TokenizerSelector<D>,
+    {
+        self.analyzer(self.selector.select(doc))
+    }
+
+    /// Analyze `text`, a field of `doc`, with the configuration of `doc`.
+    pub fn pre_tokenize<D: ?Sized>(&self, doc: &D, text: &str) -> PreTokenizedString
+    where
+        S: TokenizerSelector<D>,
+    {
+        let mut stream = self.analyzer_for(doc).token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.clone());
+        }
+        PreTokenizedString {
+            text: text.to_string(),
+            tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::SentenceFinalParticleFilter;
+    use crate::test_tokenizer;
+
+    struct ByPrefix;
+
+    impl TokenizerSelector<str> for ByPrefix {
+        fn select<'a>(&'a self, doc: &'a str) -> Option<&'a str> {
+            doc.split_once(':').map(|(source, _)| source)
+        }
+    }
+
+    #[test]
+    fn select() {
+        let news = test_tokenizer();
+        let chat = news.clone().filter(SentenceFinalParticleFilter::new());
+        let tokenizer = PerDocumentTokenizer::new(news, ByPrefix).configuration("chat", chat);
+        let texts = |doc: &str| {
+            tokenizer
+                .pre_tokenize(doc, "行きましたよね")
+                .tokens
+                .into_iter()
+                .map(|t| t.text)
+                .collect::<Vec<_>>()
+        };
+        let analyzed = vec!["行き", "まし", "た", "よ", "ね"];
+        assert_eq!(texts("chat:"), analyzed[..3]);
+        assert_eq!(texts("news:"), analyzed);
+        assert_eq!(texts("no source"), analyzed);
+    }
+}
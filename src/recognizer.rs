@@ -0,0 +1,114 @@
+//! Recognizers protecting spans of the text from morphological analysis.
+//!
+//! Some strings are destroyed by morphological analysis: hashtags, mentions, emoticons, phone
+//! numbers, ... A [`Recognizer`] finds them in the text given to Vibrato, after char filters
+//! are applied. Each recognized span is emitted as a single token with the offsets of the
+//! span, and the text between the spans is analyzed as usual. Recognizers are attached with
+//! [`VibratoTokenizer::recognizer`](crate::VibratoTokenizer::recognizer); when the spans of
+//! several recognizers overlap, the recognizer attached first wins.
+use std::ops::Range;
+
+use crate::char_filter::short_type_name;
+
+mod hashtag;
+mod kaomoji;
+mod latin;
+mod pattern;
+mod ruby;
+
+pub use hashtag::HashtagRecognizer;
+pub use kaomoji::KaomojiRecognizer;
+pub use latin::LatinRecognizer;
+pub use pattern::PatternRecognizer;
+pub use ruby::RubyRecognizer;
+
+/// A span found by a [`Recognizer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recognized {
+    /// Byte range of the span.
+    pub range: Range<usize>,
+    /// Feature string of the emitted token, e.g. `名詞,固有名詞,ハッシュタグ`.
+    pub feature: String,
+    /// Term text of the emitted token if it is not the span itself, e.g. the annotated text
+    /// of a ruby annotation. Positions then cover this text rather than the span.
+    pub text: Option<String>,
+    /// Byte range also analyzed by Vibrato, whose morphemes are emitted as overlays.
+    pub inner: Option<Range<usize>>,
+    /// Whether to emit the span. If `false`, the span is protected from analysis but removed.
+    pub emit: bool,
+}
+
+impl Recognized {
+    /// Create a span emitted as a single token.
+    pub fn new<F: Into<String>>(range: Range<usize>, feature: F) -> Recognized {
+        Recognized {
+            range,
+            feature: feature.into(),
+            text: None,
+            inner: None,
+            emit: true,
+        }
+    }
+
+    /// Emit `text` instead of the span.
+    pub fn text<S: Into<String>>(mut self, text: S) -> Recognized {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Also analyze `inner` and emit its morphemes as overlays.
+    pub fn analyze_inner(mut self, inner: Range<usize>) -> Recognized {
+        self.inner = Some(inner);
+        self
+    }
+
+    /// Remove the span instead of emitting it.
+    pub fn dropped(mut self) -> Recognized {
+        self.emit = false;
+        self
+    }
+}
+
+/// A recognizer of spans which must not be split by morphological analysis.
+pub trait Recognizer: Send + Sync + 'static {
+    /// Name of the recognizer.
+    fn name(&self) -> &'static str {
+        short_type_name::<Self>()
+    }
+
+    /// Returns the spans of `text` to protect, in any order.
+    fn recognize(&self, text: &str) -> Vec<Recognized>;
+
+    /// Parameters of the recognizer, included in the
+    /// [`fingerprint`](crate::VibratoTokenizer::fingerprint) of the tokenizer.
+    fn config(&self) -> String {
+        String::new()
+    }
+}
+
+/// Returns the spans of all `recognizers` sorted by position, removing the spans overlapping
+/// a span of a recognizer listed before.
+pub(crate) fn recognize_all<'a, I>(recognizers: I, text: &str) -> Vec<Recognized>
+where
+    I: IntoIterator<Item = &'a dyn Recognizer>,
+{
+    let mut spans: Vec<Recognized> = vec![];
+    for recognizer in recognizers {
+        let mut found = recognizer.recognize(text);
+        found.sort_by_key(|s| s.range.start);
+        for span in found {
+            let valid = span.range.start < span.range.end
+                && span.range.end <= text.len()
+                && text.is_char_boundary(span.range.start)
+                && text.is_char_boundary(span.range.end);
+            let overlapping = spans
+                .iter()
+                .any(|s| s.range.start < span.range.end && span.range.start < s.range.end);
+            if valid && !overlapping {
+                spans.push(span);
+            }
+        }
+    }
+    spans.sort_by_key(|s| s.range.start);
+    spans
+}
@@ -0,0 +1,123 @@
+use crate::recognizer::{Recognized, Recognizer};
+
+/// Recognizes hashtags (`#ハッシュタグ`, `＃タグ`) and mentions (`@user`, `＠user`).
+///
+/// A hashtag is a `#` followed by letters, digits, `_` or `ー` in any script, and a mention is
+/// an `@` followed by ASCII letters, digits or `_`. Both must not follow an ASCII letter, digit
+/// or `_`, so that `a#b` and `mail@example.com` are left alone, but `東京に@user` is not. The
+/// whole hashtag is emitted as one token; enable [`analyze_body`](Self::analyze_body) to also
+/// emit the morphemes of its body.
+#[derive(Debug, Clone)]
+pub struct HashtagRecognizer {
+    mentions: bool,
+    analyze_body: bool,
+}
+
+impl HashtagRecognizer {
+    /// Create a recognizer of hashtags and mentions.
+    pub fn new() -> HashtagRecognizer {
+        HashtagRecognizer {
+            mentions: true,
+            analyze_body: false,
+        }
+    }
+
+    /// Whether to recognize mentions. Enabled by default.
+    pub fn mentions(mut self, mentions: bool) -> HashtagRecognizer {
+        self.mentions = mentions;
+        self
+    }
+
+    /// Whether to also emit the morphemes of the body of hashtags as overlays, so that
+    /// `#東京大学` also matches `東京`. Disabled by default.
+    pub fn analyze_body(mut self, analyze_body: bool) -> HashtagRecognizer {
+        self.analyze_body = analyze_body;
+        self
+    }
+}
+
+impl Default for HashtagRecognizer {
+    fn default() -> HashtagRecognizer {
+        HashtagRecognizer::new()
+    }
+}
+
+fn is_hashtag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == 'ー' || c == '々'
+}
+
+fn is_mention_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl Recognizer for HashtagRecognizer {
+    fn recognize(&self, text: &str) -> Vec<Recognized> {
+        let mut spans = vec![];
+        let mut previous: Option<char> = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let boundary = previous.is_none_or(|p| !is_mention_char(p));
+            previous = Some(c);
+            let (is_body_char, feature): (fn(char) -> bool, _) = match c {
+                '#' | '＃' => (is_hashtag_char, "名詞,固有名詞,ハッシュタグ"),
+                '@' | '＠' if self.mentions => (is_mention_char, "名詞,固有名詞,メンション"),
+                _ => continue,
+            };
+            if !boundary {
+                continue;
+            }
+            let body_start = start + c.len_utf8();
+            let mut end = body_start;
+            while let Some(&(i, c)) = chars.peek().filter(|(_, c)| is_body_char(*c)) {
+                end = i + c.len_utf8();
+                previous = Some(c);
+                chars.next();
+            }
+            // An `＃` alone or followed by digits only is not a hashtag, e.g. `#1`.
+            if text[body_start..end].chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let span = Recognized::new(start..end, feature);
+            spans.push(if self.analyze_body && feature.ends_with("ハッシュタグ") {
+                span.analyze_inner(body_start..end)
+            } else {
+                span
+            });
+        }
+        spans
+    }
+
+    fn config(&self) -> String {
+        format!(
+            "mentions={} analyze_body={}",
+            self.mentions, self.analyze_body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(recognizer: &HashtagRecognizer, text: &str) -> Vec<String> {
+        recognizer
+            .recognize(text)
+            .into_iter()
+            .map(|s| text[s.range].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn recognize() {
+        let recognizer = HashtagRecognizer::new();
+        assert_eq!(
+            spans(&recognizer, "#東京 に行きました＃すもも_2 @user_1さん"),
+            vec!["#東京", "＃すもも_2", "@user_1"]
+        );
+        assert!(spans(&recognizer, "a#b mail@example.com #1 #").is_empty());
+        assert_eq!(
+            spans(&recognizer.clone().mentions(false), "@user #tag"),
+            vec!["#tag"]
+        );
+    }
+}
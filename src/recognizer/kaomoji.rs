@@ -0,0 +1,167 @@
+use crate::recognizer::{Recognized, Recognizer};
+
+/// Emoticons written without parentheses, longest first.
+const WORDS: [&str; 6] = ["orz", "OTL", "ｏｒｚ", "^^;", "^_^", "^^"];
+
+/// Characters drawing the face of a kaomoji besides symbols, e.g. the mouth of `(＾ω＾)`.
+const FACE_CHARS: &str = "ωдДзεσ∀ヮ艸皿口益ゝﾟ゜ェｪ";
+
+/// Arms before the opening parenthesis, e.g. `ヽ(`.
+const LEFT_ARMS: &str = "ヽ＼\\ヾ٩ლｍm";
+
+/// Arms after the closing parenthesis, e.g. `)ノ`.
+const RIGHT_ARMS: &str = "ノﾉ／/۶ლｍm";
+
+/// Content which is punctuation rather than a face, e.g. `(!?)`.
+const PUNCTUATION: &str = "!?！？.。、,，…・-ー~〜";
+
+/// Maximum number of characters between the parentheses.
+const MAX_FACE_LEN: usize = 12;
+
+/// Recognizes kaomoji and emoticons, e.g. `(＾ω＾)`, `ヽ(´∀`)ノ` or `orz`, so that their
+/// characters are not scattered into symbol tokens.
+///
+/// A kaomoji is a pair of parentheses around up to 12 characters drawing a face: symbols,
+/// spaces, common mouths and eyes like `ω` or `∀`, and ASCII letters not followed by another
+/// letter like the `o` of `(^o^)`. `(株)` or `(1)` are not kaomoji. Arms around the
+/// parentheses, e.g. `ヽ` and `ノ`, are included. Kaomoji are emitted with the feature
+/// `記号,顔文字`, or removed with [`remove`](Self::remove).
+#[derive(Debug, Clone, Default)]
+pub struct KaomojiRecognizer {
+    remove: bool,
+}
+
+impl KaomojiRecognizer {
+    /// Create a recognizer keeping kaomoji as single tokens.
+    pub fn new() -> KaomojiRecognizer {
+        KaomojiRecognizer { remove: false }
+    }
+
+    /// Whether to remove the kaomoji instead of emitting them. Disabled by default.
+    pub fn remove(mut self, remove: bool) -> KaomojiRecognizer {
+        self.remove = remove;
+        self
+    }
+}
+
+fn is_face(content: &str) -> bool {
+    let chars = content.chars().collect::<Vec<_>>();
+    let visible = chars.iter().filter(|c| !c.is_whitespace()).count();
+    if !(2..=MAX_FACE_LEN).contains(&visible) || chars.iter().all(|&c| PUNCTUATION.contains(c)) {
+        return false;
+    }
+    let mut symbols = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_alphabetic() {
+            if chars.get(i + 1).is_some_and(|n| n.is_alphanumeric()) {
+                return false;
+            }
+        } else if c.is_alphanumeric() && !FACE_CHARS.contains(c) {
+            return false;
+        } else if !c.is_whitespace() {
+            symbols += 1;
+        }
+    }
+    symbols > 0
+}
+
+/// Returns the end of the kaomoji whose face starts at the parenthesis at `open`.
+fn face_end(text: &str, open: usize) -> Option<usize> {
+    let inner = open + text[open..].chars().next()?.len_utf8();
+    let mut depth = 0;
+    for (i, c) in text[inner..].char_indices() {
+        match c {
+            '(' | '（' => depth += 1,
+            ')' | '）' if depth > 0 => depth -= 1,
+            ')' | '）' => {
+                let close = inner + i;
+                return is_face(&text[inner..close]).then_some(close + c.len_utf8());
+            }
+            '\n' => return None,
+            _ => {}
+        }
+        if i > MAX_FACE_LEN * 4 {
+            return None;
+        }
+    }
+    None
+}
+
+fn is_word_boundary(c: Option<char>) -> bool {
+    c.is_none_or(|c| !c.is_ascii_alphanumeric())
+}
+
+impl Recognizer for KaomojiRecognizer {
+    fn recognize(&self, text: &str) -> Vec<Recognized> {
+        let mut spans = vec![];
+        let mut i = 0;
+        while i < text.len() {
+            let rest = &text[i..];
+            let previous = text[..i].chars().next_back();
+            let mut found = None;
+            if let Some(word) = WORDS.iter().find(|w| rest.starts_with(*w)) {
+                if is_word_boundary(previous) && is_word_boundary(rest[word.len()..].chars().next())
+                {
+                    found = Some(i + word.len());
+                }
+            }
+            // The `（` of the face, after the left arm if any.
+            let mut open = i;
+            if let Some(arm) = rest.chars().next().filter(|&c| LEFT_ARMS.contains(c)) {
+                if rest[arm.len_utf8()..].starts_with(['(', '（'])
+                    && (is_word_boundary(previous) || !arm.is_ascii_alphabetic())
+                {
+                    open += arm.len_utf8();
+                }
+            }
+            if found.is_none() && text[open..].starts_with(['(', '（']) {
+                if let Some(mut end) = face_end(text, open) {
+                    if let Some(arm) = text[end..]
+                        .chars()
+                        .next()
+                        .filter(|&c| RIGHT_ARMS.contains(c))
+                    {
+                        end += arm.len_utf8();
+                    }
+                    found = Some(end);
+                }
+            }
+            match found {
+                Some(end) => {
+                    let span = Recognized::new(i..end, "記号,顔文字");
+                    spans.push(if self.remove { span.dropped() } else { span });
+                    i = end;
+                }
+                None => i += rest.chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        spans
+    }
+
+    fn config(&self) -> String {
+        format!("remove={}", self.remove)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(text: &str) -> Vec<&str> {
+        KaomojiRecognizer::new()
+            .recognize(text)
+            .into_iter()
+            .map(|s| &text[s.range])
+            .collect()
+    }
+
+    #[test]
+    fn recognize() {
+        assert_eq!(
+            spans("すもも(＾ω＾)ももヽ(´∀`)ノ(^o^)orz"),
+            vec!["(＾ω＾)", "ヽ(´∀`)ノ", "(^o^)", "orz"]
+        );
+        assert_eq!(spans("m(_ _)m（；・∀・）"), vec!["m(_ _)m", "（；・∀・）"]);
+        assert!(spans("(株)すもも(1)(笑)(!?)(abc) orzo").is_empty());
+    }
+}
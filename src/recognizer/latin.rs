@@ -0,0 +1,135 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::filter::width::fold_width;
+use crate::recognizer::{Recognized, Recognizer};
+
+/// Splits the runs of Latin script embedded in Japanese text into words on Unicode word
+/// boundaries (UAX #29), instead of leaving them to the grouping of unknown words of the
+/// dictionary, which glues or splits them depending on the surrounding characters.
+///
+/// Words keep their inner apostrophes and periods, so that `it's` and acronyms like `U.S.A`
+/// are emitted as single tokens, and numbers keep their decimal separators (`3.14`). Words
+/// are emitted with the feature `名詞,一般,ラテン文字` and numbers with `名詞,数`. Spaces and
+/// punctuation between the words are analyzed as usual.
+///
+/// The words can be lowercased and folded from full-width to half-width, e.g. `iphone15pro`
+/// for `ｉＰｈｏｎｅ15Ｐｒｏ`, while their offsets stay those of the original text.
+#[derive(Debug, Clone, Default)]
+pub struct LatinRecognizer {
+    lowercase: bool,
+    fold_width: bool,
+}
+
+impl LatinRecognizer {
+    /// Create a new `LatinRecognizer` emitting the words as they are.
+    pub fn new() -> LatinRecognizer {
+        LatinRecognizer::default()
+    }
+
+    /// Whether to lowercase the ASCII letters of the words. Disabled by default.
+    pub fn lowercase(mut self, lowercase: bool) -> LatinRecognizer {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Whether to fold full-width letters and digits to ASCII. Disabled by default.
+    pub fn fold_width(mut self, fold_width: bool) -> LatinRecognizer {
+        self.fold_width = fold_width;
+        self
+    }
+
+    fn normalize(&self, word: &str) -> Option<String> {
+        let mut normalized = if self.fold_width {
+            fold_width(word)
+        } else {
+            word.to_string()
+        };
+        if self.lowercase {
+            normalized.make_ascii_lowercase();
+        }
+        (normalized != word).then_some(normalized)
+    }
+}
+
+/// Returns `true` for the letters and digits of the Latin script, fullwidth forms included.
+fn is_latin(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(c, 'À'..='ɏ' if c != '×' && c != '÷')
+        || matches!(c, '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ')
+}
+
+fn is_joiner(c: char) -> bool {
+    matches!(c, '.' | ',' | '\'' | '’' | '_' | '．' | '，')
+}
+
+impl Recognizer for LatinRecognizer {
+    fn recognize(&self, text: &str) -> Vec<Recognized> {
+        text.split_word_bound_indices()
+            .filter(|(_, word)| {
+                word.chars().all(|c| is_latin(c) || is_joiner(c)) && word.chars().any(is_latin)
+            })
+            .map(|(start, word)| {
+                let numeric = word.chars().all(|c| !c.is_alphabetic());
+                let feature = if numeric {
+                    "名詞,数"
+                } else {
+                    "名詞,一般,ラテン文字"
+                };
+                let recognized = Recognized::new(start..start + word.len(), feature);
+                match self.normalize(word) {
+                    Some(text) => recognized.text(text),
+                    None => recognized,
+                }
+            })
+            .collect()
+    }
+
+    fn config(&self) -> String {
+        // Empty by default, to keep the fingerprints of the tokenizers from before the options.
+        if !self.lowercase && !self.fold_width {
+            return String::new();
+        }
+        format!(
+            "lowercase={},fold_width={}",
+            self.lowercase, self.fold_width
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use tantivy::tokenizer::Tokenizer;
+
+    #[test]
+    fn mixed() {
+        let tokenizer = test_tokenizer().recognizer(LatinRecognizer::new());
+        let mut stream =
+            tokenizer.token_stream("東京でHello worldと言った。It's U.S.A.のiPhone15は3.14ドル");
+        let mut texts = vec![];
+        while let Some(token) = stream.next() {
+            texts.push(token.text.clone());
+        }
+        let words = texts
+            .iter()
+            .filter(|t| t.chars().any(is_latin))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            words,
+            vec!["Hello", "world", "It's", "U.S.A", "iPhone15", "3.14"]
+        );
+        assert_eq!(&texts[..2], ["東京", "で"]);
+
+        let tokenizer =
+            test_tokenizer().recognizer(LatinRecognizer::new().lowercase(true).fold_width(true));
+        let text = "ｉＰｈｏｎｅ15Proを買った";
+        let mut stream = tokenizer.token_stream(text);
+        let token = stream.next().unwrap();
+        assert_eq!(token.text, "iphone15pro");
+        assert_eq!(
+            &text[token.offset_from..token.offset_to],
+            "ｉＰｈｏｎｅ15Pro"
+        );
+    }
+}
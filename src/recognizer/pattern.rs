@@ -0,0 +1,128 @@
+use regex::Regex;
+
+use crate::recognizer::{Recognized, Recognizer};
+use crate::tokenizer::Result;
+
+/// ASCII and fullwidth digits.
+const DIGIT: &str = "[0-9０-９]";
+
+/// Hyphens and the other dashes used as separators in Japanese texts.
+const HYPHEN: &str = "[-－‐−ー]";
+
+/// Recognizes the matches of a regular expression, e.g. phone numbers or product model
+/// numbers, and emits them verbatim instead of splitting them into numbers and symbols.
+///
+/// Matches adjacent to an ASCII letter or a digit are ignored, so that a pattern does not
+/// match a part of a longer code. The recognizers for common Japanese patterns are provided:
+/// [`phone_number`](Self::phone_number), [`postal_code`](Self::postal_code) and
+/// [`model_number`](Self::model_number).
+#[derive(Debug, Clone)]
+pub struct PatternRecognizer {
+    regex: Regex,
+    feature: String,
+}
+
+impl PatternRecognizer {
+    /// Create a recognizer of the matches of `pattern`, emitted with `feature`.
+    ///
+    /// Returns an error if `pattern` is not a valid regular expression of the `regex` crate.
+    pub fn new<F: Into<String>>(pattern: &str, feature: F) -> Result<PatternRecognizer> {
+        Ok(PatternRecognizer {
+            regex: Regex::new(pattern)?,
+            feature: feature.into(),
+        })
+    }
+
+    fn preset(pattern: String, feature: &str) -> PatternRecognizer {
+        PatternRecognizer::new(&pattern, feature).expect("preset patterns are valid")
+    }
+
+    /// Japanese phone numbers, e.g. `03-1234-5678`, `03(1234)5678`, `090-1234-5678`,
+    /// `+81-3-1234-5678` or `0312345678`.
+    pub fn phone_number() -> PatternRecognizer {
+        let (d, h, z) = (DIGIT, HYPHEN, "[0０]");
+        Self::preset(
+            format!(
+                "(?:\\+81{h}?{d}{{1,4}}|{z}{d}{{1,4}}){h}{d}{{1,4}}{h}{d}{{4}}\
+                 |{z}{d}{{1,4}}[(（]{d}{{1,4}}[)）]{d}{{4}}\
+                 |{z}{d}{{9,10}}"
+            ),
+            "名詞,固有名詞,電話番号",
+        )
+    }
+
+    /// Postal codes marked with `〒`, e.g. `〒100-0001` or `〒 1000001`.
+    pub fn postal_code() -> PatternRecognizer {
+        let (d, h) = (DIGIT, HYPHEN);
+        Self::preset(
+            format!("〒[ 　]?{d}{{3}}{h}?{d}{{4}}"),
+            "名詞,固有名詞,郵便番号",
+        )
+    }
+
+    /// Product model numbers mixing ASCII letters and digits, optionally separated by
+    /// hyphens, e.g. `KJ-55X9500H` or `iPhone15`.
+    pub fn model_number() -> PatternRecognizer {
+        Self::preset(
+            "(?:[A-Za-z]+-?[0-9]+|[0-9]+-?[A-Za-z]+)[A-Za-z0-9]*(?:-[A-Za-z0-9]+)*".to_string(),
+            "名詞,固有名詞,型番",
+        )
+    }
+}
+
+fn is_code_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ('０'..='９').contains(&c)
+}
+
+impl Recognizer for PatternRecognizer {
+    fn recognize(&self, text: &str) -> Vec<Recognized> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| {
+                !m.is_empty()
+                    && !text[..m.start()]
+                        .chars()
+                        .next_back()
+                        .is_some_and(is_code_char)
+                    && !text[m.end()..].chars().next().is_some_and(is_code_char)
+            })
+            .map(|m| Recognized::new(m.range(), self.feature.as_str()))
+            .collect()
+    }
+
+    fn config(&self) -> String {
+        format!("pattern={} feature={}", self.regex.as_str(), self.feature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans<'a>(recognizer: &PatternRecognizer, text: &'a str) -> Vec<&'a str> {
+        recognizer
+            .recognize(text)
+            .into_iter()
+            .map(|s| &text[s.range])
+            .collect()
+    }
+
+    #[test]
+    fn presets() {
+        let text = "〒100-0001 東京都、電話03-1234-5678か０９０１２３４５６７８、型番KJ-55X9500H";
+        assert_eq!(
+            spans(&PatternRecognizer::phone_number(), text),
+            vec!["03-1234-5678", "０９０１２３４５６７８"]
+        );
+        assert_eq!(
+            spans(&PatternRecognizer::postal_code(), text),
+            vec!["〒100-0001"]
+        );
+        assert_eq!(
+            spans(&PatternRecognizer::model_number(), text),
+            vec!["KJ-55X9500H"]
+        );
+        assert!(spans(&PatternRecognizer::phone_number(), "1203-1234-5678").is_empty());
+        assert!(PatternRecognizer::new("(", "記号").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use std::ops::Range;
+
+use crate::char_filter::find_end_tag;
+use crate::chars::is_kanji;
+use crate::features::DictionaryKind;
+use crate::recognizer::{Recognized, Recognizer};
+use crate::sort_key::to_katakana;
+
+/// Recognizes ruby annotations (furigana) and emits the annotated text as one token whose
+/// reading is the annotation, e.g. `東京` read as `トウキョウ`, so that the markup is not
+/// analyzed and the annotation reaches the fields indexing readings.
+///
+/// Both HTML ruby, `<ruby>東京<rt>とうきょう</rt></ruby>` (with optional `<rb>` and `<rp>`
+/// tags and several annotated parts), and the Aozora Bunko notation, `｜東京《とうきょう》` or
+/// `東京《とうきょう》` for a run of kanji, are recognized. The token spans the whole markup,
+/// but its term text is the annotated text and its positions only cover the annotated text.
+///
+/// The reading is converted to katakana and stored in the feature string of a common noun,
+/// with the layout of IPADIC by default. Set the layout of the dictionary in use with
+/// [`dictionary_kind`](Self::dictionary_kind). When stripping HTML with
+/// [`MarkupCharFilter`](crate::char_filter::MarkupCharFilter), keep the ruby tags with
+/// [`keep_ruby`](crate::char_filter::MarkupCharFilter::keep_ruby).
+#[derive(Debug, Clone)]
+pub struct RubyRecognizer {
+    kind: DictionaryKind,
+}
+
+impl RubyRecognizer {
+    /// Create a recognizer of ruby annotations.
+    pub fn new() -> RubyRecognizer {
+        RubyRecognizer {
+            kind: DictionaryKind::Ipadic,
+        }
+    }
+
+    /// Layout of the feature strings of the emitted tokens. The default is IPADIC.
+    pub fn dictionary_kind(mut self, kind: DictionaryKind) -> RubyRecognizer {
+        self.kind = kind;
+        self
+    }
+
+    fn feature(&self, base: &str, reading: &str) -> String {
+        match self.kind {
+            DictionaryKind::Ipadic => format!("名詞,一般,*,*,*,*,{base},{reading},{reading}"),
+            DictionaryKind::Unidic => format!(
+                "名詞,普通名詞,一般,*,*,*,{reading},{base},{base},{reading},{base},{reading}"
+            ),
+        }
+    }
+
+    fn annotation(&self, range: Range<usize>, base: &str, reading: &str) -> Option<Recognized> {
+        let reading = reading.trim();
+        if base.is_empty() || reading.is_empty() || base.contains(',') || reading.contains(',') {
+            return None;
+        }
+        let feature = self.feature(base, &to_katakana(reading));
+        Some(Recognized::new(range, feature).text(base))
+    }
+}
+
+impl Default for RubyRecognizer {
+    fn default() -> RubyRecognizer {
+        RubyRecognizer::new()
+    }
+}
+
+/// Parses the `<ruby>` element at the start of `text`, returning its length, the annotated
+/// text and the annotation.
+fn html_ruby(text: &str) -> Option<(usize, String, String)> {
+    let (close, _) = find_end_tag(text, &["ruby"])?;
+    let end = close + text[close..].find('>')? + 1;
+    let content = &text["<ruby>".len()..close];
+    let (mut base, mut reading) = (String::new(), String::new());
+    // Name of the element whose text is being read: none, `rt` or `rp`.
+    let mut inside = "";
+    let mut rest = content;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            let close = rest.find('>')?;
+            let name = rest[1..close].trim_start_matches('/').to_ascii_lowercase();
+            let name = name.split_whitespace().next().unwrap_or("");
+            inside = match (name, rest.as_bytes()[1] == b'/') {
+                ("rt", false) => "rt",
+                ("rp", false) => "rp",
+                ("rt" | "rp", true) | ("rb", _) => "",
+                _ => return None,
+            };
+            rest = &rest[close + 1..];
+            continue;
+        }
+        match inside {
+            "rt" => reading.push(c),
+            "rp" => {}
+            _ => base.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    Some((end, base.trim().to_string(), reading))
+}
+
+impl Recognizer for RubyRecognizer {
+    fn recognize(&self, text: &str) -> Vec<Recognized> {
+        let mut spans = vec![];
+        let mut i = 0;
+        // Start of the run of kanji ending at `i`, annotated by a following `《`.
+        let mut kanji_start = None;
+        // Offset of the last `｜` marking the start of the annotated text.
+        let mut bar = None;
+        while i < text.len() {
+            let rest = &text[i..];
+            let c = rest.chars().next().unwrap();
+            let head = rest.as_bytes().get(..6);
+            if head.is_some_and(|b| b.eq_ignore_ascii_case(b"<ruby>")) {
+                if let Some((len, base, reading)) = html_ruby(rest) {
+                    spans.extend(self.annotation(i..i + len, &base, &reading));
+                    i += len;
+                    kanji_start = None;
+                    continue;
+                }
+            }
+            match c {
+                '｜' | '|' => bar = Some(i),
+                '《' => {
+                    let start = bar.or(kanji_start);
+                    let close = rest.find('》');
+                    if let (Some(start), Some(close)) = (start, close) {
+                        let base = text[start..i].trim_start_matches(['｜', '|']);
+                        let reading = &rest['《'.len_utf8()..close];
+                        let end = i + close + '》'.len_utf8();
+                        if let Some(span) = self.annotation(start..end, base, reading) {
+                            spans.push(span);
+                            i = end;
+                            bar = None;
+                            kanji_start = None;
+                            continue;
+                        }
+                    }
+                }
+                '\n' => bar = None,
+                _ => {}
+            }
+            if is_kanji(c) {
+                kanji_start.get_or_insert(i);
+            } else {
+                kanji_start = None;
+            }
+            i += c.len_utf8();
+        }
+        spans
+    }
+
+    fn config(&self) -> String {
+        format!("kind={:?}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    fn spans(text: &str) -> Vec<(&str, String, String)> {
+        RubyRecognizer::new()
+            .recognize(text)
+            .into_iter()
+            .map(|s| (&text[s.range], s.text.unwrap(), s.feature))
+            .collect()
+    }
+
+    #[test]
+    fn html() {
+        let text = "<ruby>東京<rp>(</rp><rt>とうきょう</rt><rp>)</rp></ruby>と<RUBY>大<rt>だい</rt>阪<rt>さか</rt></RUBY>";
+        let found = spans(text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].0,
+            &text[..text.find("</ruby>").unwrap() + "</ruby>".len()]
+        );
+        assert_eq!(found[0].1, "東京");
+        assert_eq!(found[0].2, "名詞,一般,*,*,*,*,東京,トウキョウ,トウキョウ");
+        assert_eq!(found[1].1, "大阪");
+        assert!(found[1].2.ends_with("ダイサカ,ダイサカ"));
+        assert!(spans("<ruby>東京</ruby>").is_empty());
+        assert!(spans("iPhoneを東京で買った").is_empty());
+        assert!(spans("a<東京>").is_empty());
+    }
+
+    #[test]
+    fn aozora() {
+        let found = spans("その数多《あまた》の｜東京タワー《とうきょうたわー》");
+        let found = found
+            .iter()
+            .map(|(span, base, _)| (*span, base.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("数多《あまた》", "数多"),
+                ("｜東京タワー《とうきょうたわー》", "東京タワー"),
+            ]
+        );
+        assert!(spans("《あまた》").is_empty());
+        assert!(spans("｜心《こゝろ》")[0].2.ends_with("コヽロ,コヽロ"));
+    }
+
+    #[test]
+    fn tokenize() {
+        use tantivy::tokenizer::Tokenizer;
+
+        let tokenizer = test_tokenizer()
+            .recognizer(RubyRecognizer::new())
+            .position_mode(crate::PositionMode::CharOffset);
+        let text = "<ruby>東京<rt>とうきょう</rt></ruby>に行く";
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.offset_from, token.position));
+        }
+        let ruby_end = text.find('に').unwrap();
+        assert_eq!(tokens[0], ("東京".to_string(), 0, 0));
+        assert_eq!(tokens[1], ("に".to_string(), ruby_end, 2));
+
+        let morphemes = tokenizer.char_filtered_morphemes(text);
+        assert_eq!(morphemes[0].features().reading(), Some("トウキョウ"));
+    }
+}
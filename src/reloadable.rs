@@ -0,0 +1,141 @@
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::path;
+use std::sync::{Arc, RwLock};
+
+use tantivy::tokenizer::{BoxTokenStream, Tokenizer as TTokenizer};
+
+use crate::tokenizer::Result;
+use crate::VibratoTokenizer;
+
+/// A [`VibratoTokenizer`] whose dictionary can be replaced while it is registered in tantivy,
+/// e.g. to pick up an updated user lexicon in a long-running search service.
+///
+/// The clones, including the one registered in the `TokenizerManager`, share the current
+/// tokenizer. A reload builds the new tokenizer first, then swaps it atomically: texts being
+/// tokenized keep the old dictionary, which is freed when the last of them finishes.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy::schema::Schema;
+/// use tantivy::Index;
+/// use tantivy_vibrato::{ReloadableVibratoTokenizer, VibratoTokenizer};
+///
+/// let index = Index::create_in_ram(Schema::builder().build());
+/// let tokenizer = ReloadableVibratoTokenizer::new(VibratoTokenizer::with_user_lexicon(
+///     "/path/to/dictionary",
+///     "user.csv",
+/// )?);
+/// index.tokenizers().register("lang_ja", tokenizer.clone());
+///
+/// // Later, after user.csv is updated.
+/// tokenizer.reload_user_lexicon("user.csv")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ReloadableVibratoTokenizer {
+    current: Arc<RwLock<VibratoTokenizer>>,
+}
+
+impl ReloadableVibratoTokenizer {
+    /// Create a reloadable tokenizer starting with `tokenizer`.
+    pub fn new(tokenizer: VibratoTokenizer) -> ReloadableVibratoTokenizer {
+        ReloadableVibratoTokenizer {
+            current: Arc::new(RwLock::new(tokenizer)),
+        }
+    }
+
+    /// Returns the current tokenizer.
+    pub fn current(&self) -> VibratoTokenizer {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the current tokenizer with `tokenizer`.
+    pub fn set(&self, tokenizer: VibratoTokenizer) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = tokenizer;
+    }
+
+    /// Replace the system dictionary with the Vibrato dictionary file at `dict_path`, keeping
+    /// the options, the user lexicon and the filters of the current tokenizer. The entities of
+    /// the gazetteers are tuned again with the new dictionary. Requires the `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn reload<P: AsRef<path::Path>>(&self, dict_path: P) -> Result<()> {
+        let file = fs::File::open(dict_path)?;
+        self.reload_from_reader(file)
+    }
+
+    /// Replace the system dictionary with a reader of a Vibrato dictionary file, see
+    /// [`reload`](Self::reload).
+    pub fn reload_from_reader<R: Read>(&self, reader: R) -> Result<()> {
+        let tokenizer = self.current().with_system_dictionary(reader)?;
+        self.set(tokenizer);
+        Ok(())
+    }
+
+    /// Replace the user lexicon with the one at `user_lexicon_path`, in MeCab CSV format, see
+    /// [`VibratoTokenizer::user_lexicon`]. The gazetteers are kept. Requires the `fs` feature.
+    #[cfg(feature = "fs")]
+    pub fn reload_user_lexicon<P: AsRef<path::Path>>(&self, user_lexicon_path: P) -> Result<()> {
+        self.reload_user_lexicon_from_reader(fs::File::open(user_lexicon_path)?)
+    }
+
+    /// Replace the user lexicon with a reader of a user lexicon, see
+    /// [`reload_user_lexicon`](Self::reload_user_lexicon).
+    pub fn reload_user_lexicon_from_reader<R: Read>(&self, mut reader: R) -> Result<()> {
+        let mut entries = String::new();
+        reader.read_to_string(&mut entries)?;
+        let tokenizer = self.current().with_user_lexicon_entries(entries)?;
+        self.set(tokenizer);
+        Ok(())
+    }
+}
+
+impl TTokenizer for ReloadableVibratoTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        self.current().token_stream(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn reload() {
+        let texts = |tokenizer: &ReloadableVibratoTokenizer| {
+            let mut stream = tokenizer.token_stream("東京大学");
+            let mut texts = vec![];
+            while let Some(token) = stream.next() {
+                texts.push(token.text.clone());
+            }
+            texts
+        };
+        let tokenizer = ReloadableVibratoTokenizer::new(test_tokenizer());
+        let registered = tokenizer.clone();
+        let fingerprint = tokenizer.current().fingerprint();
+        assert_eq!(texts(&registered), vec!["東京", "大学"]);
+
+        let path = std::env::temp_dir().join("tantivy-vibrato-reload.csv");
+        fs::write(&path, "東京大学,1,1,-5000,名詞,固有名詞,組織,*,*,*,東京大学,トウキョウダイガク,トーキョーダイガク\n").unwrap();
+        let mut in_flight = registered.token_stream("東京大学");
+        tokenizer.reload_user_lexicon(&path).unwrap();
+        assert_eq!(texts(&registered), vec!["東京大学"]);
+        assert!(in_flight.advance());
+        assert_eq!(in_flight.token().text, "東京");
+
+        fs::write(&path, "").unwrap();
+        tokenizer.reload_user_lexicon(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(texts(&registered), vec!["東京", "大学"]);
+
+        tokenizer.reload("./system.dic").unwrap();
+        assert_eq!(tokenizer.current().fingerprint(), fingerprint);
+    }
+}
@@ -0,0 +1,284 @@
+use std::ops::Range;
+
+use tantivy::tokenizer::{BoxTokenStream, Token as TToken, Tokenizer as TTokenizer};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::tokenizer::VibratoTokenStream;
+use crate::VibratoTokenizer;
+
+/// How the spans of a script are tokenized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    /// Kana and kanji, analyzed by Vibrato.
+    Japanese,
+    /// Hangul, Bopomofo and Yi, split into character bigrams. The digits following them are
+    /// split into words.
+    Bigrams,
+    /// The other alphabetic scripts, split on Unicode word boundaries.
+    Words,
+}
+
+fn route(c: char) -> Option<Route> {
+    match c {
+        '\u{3040}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{FF66}'..='\u{FF9F}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3FFFF}'
+        | '々'
+        | '〆' => Some(Route::Japanese),
+        '\u{1100}'..='\u{11FF}'
+        | '\u{3100}'..='\u{312F}'
+        | '\u{3130}'..='\u{318F}'
+        | '\u{31A0}'..='\u{31BF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{A960}'..='\u{A97F}'
+        | '\u{AC00}'..='\u{D7FF}' => Some(Route::Bigrams),
+        _ if c.is_alphabetic() => Some(Route::Words),
+        _ => None,
+    }
+}
+
+/// Splits `text` into spans of the same route. Digits, spaces and symbols belong to the span
+/// they follow, so that Vibrato sees the numbers and punctuation of Japanese text.
+fn spans(text: &str) -> Vec<(Range<usize>, Route)> {
+    let mut spans: Vec<(Range<usize>, Route)> = vec![];
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        match (route(c), spans.last_mut()) {
+            (Some(r), Some((range, last))) if *last == r => range.end = end,
+            (Some(r), Some((range, _))) => {
+                let start = range.end;
+                spans.push((start..end, r));
+            }
+            (Some(r), None) => spans.push((0..end, r)),
+            (None, Some((range, _))) => range.end = end,
+            (None, None) => {}
+        }
+    }
+    if spans.is_empty() && !text.is_empty() {
+        spans.push((0..text.len(), Route::Japanese));
+    }
+    spans
+}
+
+/// A tokenizer for multilingual fields, routing the spans of each script to a suitable
+/// tokenizer: Japanese to Vibrato, Hangul (and the other CJK scripts without kanji) to
+/// character bigrams, and the other alphabetic scripts, such as Latin or Cyrillic, to a word
+/// splitter on Unicode word boundaries.
+///
+/// Offsets are relative to the whole text. Positions follow the
+/// [`PositionMode`](crate::PositionMode) of the Japanese tokenizer: consecutive over the
+/// tokens of all spans by default, or character offsets. The char
+/// filters, recognizers and filters of the Japanese tokenizer only apply to Japanese spans.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use tantivy_vibrato::{ScriptRouter, VibratoTokenizer};
+///
+/// let tokenizer = ScriptRouter::new(VibratoTokenizer::new("/path/to/dictionary")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScriptRouter {
+    japanese: VibratoTokenizer,
+}
+
+impl ScriptRouter {
+    /// Create a router analyzing Japanese spans with `japanese`.
+    pub fn new(japanese: VibratoTokenizer) -> ScriptRouter {
+        ScriptRouter { japanese }
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<TToken> {
+        let mut emitter = Emitter::new(self.japanese.has_sequential_positions());
+        let mut char_offset = 0;
+        for (range, route) in spans(text) {
+            let span = &text[range.clone()];
+            match route {
+                Route::Japanese => {
+                    emitter.extend(self.japanese.analyze(span, None), &range, char_offset)
+                }
+                Route::Bigrams => emitter.bigrams(span, range.start, char_offset),
+                Route::Words => emitter.words(span, range.start, char_offset),
+            }
+            char_offset += span.chars().count();
+        }
+        emitter.tokens
+    }
+}
+
+/// Collects the tokens of the spans of a text, shifting offsets and positions.
+pub(crate) struct Emitter {
+    pub(crate) tokens: Vec<TToken>,
+    sequential: bool,
+    next_position: usize,
+}
+
+impl Emitter {
+    /// Create an emitter of consecutive positions if `sequential`, of character offsets
+    /// otherwise.
+    pub(crate) fn new(sequential: bool) -> Emitter {
+        Emitter {
+            tokens: vec![],
+            sequential,
+            next_position: 0,
+        }
+    }
+
+    fn push(&mut self, text: &str, offset: usize, char_offset: usize) {
+        let (position, position_length) = if self.sequential {
+            self.next_position += 1;
+            (self.next_position - 1, 1)
+        } else {
+            (char_offset, text.chars().count())
+        };
+        self.tokens.push(TToken {
+            offset_from: offset,
+            offset_to: offset + text.len(),
+            position,
+            position_length,
+            text: text.to_string(),
+        });
+    }
+
+    /// Append the tokens of a span analyzed on its own.
+    pub(crate) fn extend(&mut self, tokens: Vec<TToken>, range: &Range<usize>, char_offset: usize) {
+        let base = if self.sequential {
+            self.next_position
+        } else {
+            char_offset
+        };
+        for mut token in tokens {
+            token.offset_from += range.start;
+            token.offset_to += range.start;
+            token.position += base;
+            self.next_position = self
+                .next_position
+                .max(token.position + token.position_length);
+            self.tokens.push(token);
+        }
+    }
+
+    fn bigrams(&mut self, span: &str, offset: usize, char_offset: usize) {
+        let chars = span.char_indices().collect::<Vec<_>>();
+        let mut i = 0;
+        while i < chars.len() {
+            let start = i;
+            while i < chars.len() && route(chars[i].1) == Some(Route::Bigrams) {
+                i += 1;
+            }
+            let run = &chars[start..i];
+            let end_of = |j: usize| chars[j].0 + chars[j].1.len_utf8();
+            if run.len() == 1 {
+                self.push(
+                    &span[run[0].0..end_of(start)],
+                    offset + run[0].0,
+                    char_offset + start,
+                );
+            }
+            for j in start..i.saturating_sub(1) {
+                let bigram = &span[chars[j].0..end_of(j + 1)];
+                self.push(bigram, offset + chars[j].0, char_offset + j);
+            }
+            // The digits and symbols following the run are split into words.
+            let start = i;
+            while i < chars.len() && route(chars[i].1) != Some(Route::Bigrams) {
+                i += 1;
+            }
+            if i > start {
+                let end = chars.get(i).map_or(span.len(), |c| c.0);
+                self.words(
+                    &span[chars[start].0..end],
+                    offset + chars[start].0,
+                    char_offset + start,
+                );
+            }
+        }
+    }
+
+    fn words(&mut self, span: &str, offset: usize, char_offset: usize) {
+        let mut chars = 0;
+        let mut last = 0;
+        for (start, word) in span.split_word_bound_indices() {
+            chars += span[last..start].chars().count();
+            last = start;
+            if word.chars().any(char::is_alphanumeric) {
+                self.push(word, offset + start, char_offset + chars);
+            }
+        }
+    }
+}
+
+impl TTokenizer for ScriptRouter {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(VibratoTokenStream::new(self.tokenize(text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::PositionMode;
+
+    fn tokens(router: &ScriptRouter, text: &str) -> Vec<(String, usize, usize)> {
+        let mut stream = router.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((token.text.clone(), token.offset_from, token.position));
+        }
+        tokens
+    }
+
+    #[test]
+    fn routes() {
+        let tokenizer = test_tokenizer().position_mode(PositionMode::CharOffset);
+        let text = "東京でHello worldと안녕하세요を";
+        let expected = [
+            ("東京", 0),
+            ("で", 2),
+            ("Hello", 3),
+            ("world", 9),
+            ("と", 14),
+            ("안녕", 15),
+            ("녕하", 16),
+            ("하세", 17),
+            ("세요", 18),
+            ("を", 20),
+        ];
+
+        let router = ScriptRouter::new(tokenizer.clone());
+        let emitted = tokens(&router, text);
+        let texts = emitted
+            .iter()
+            .map(|(t, offset, _)| (t.as_str(), &text[*offset..*offset + t.len()]))
+            .collect::<Vec<_>>();
+        assert!(texts.iter().all(|(t, original)| t == original));
+        let positions = emitted
+            .iter()
+            .map(|(t, _, position)| (t.as_str(), *position))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, expected);
+
+        let router = ScriptRouter::new(tokenizer.clone().kuromoji_compatible());
+        let positions = tokens(&router, "東京大学Hello world")
+            .into_iter()
+            .map(|(_, _, position)| position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![0, 1, 2, 3]);
+
+        let router = ScriptRouter::new(tokenizer);
+        assert_eq!(
+            tokens(&router, "서울 2024년"),
+            vec![
+                ("서울".to_string(), 0, 0),
+                ("2024".to_string(), 7, 3),
+                ("년".to_string(), 11, 7),
+            ]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Decomposition of compound nouns for search, like Kuromoji's search mode.
+//!
+//! Vibrato only returns the best path, so the decomposition of each long noun of the best path
+//! is searched on top of it: every split of the noun into dictionary words is ranked by the sum
+//! of the path costs of its parts analyzed on their own, plus a penalty growing with the length
+//! of each part, and the noun itself competes with its penalty too.
+use vibrato::dictionary::LexType;
+use vibrato::tokenizer::worker::Worker;
+
+use crate::chars::is_kanji;
+use crate::features::DictionaryKind;
+use crate::filter::katakana_stem::is_katakana;
+use crate::token::MorphToken;
+use crate::worker_pool::WorkerPool;
+
+/// Maximum length in characters of the parts of a compound.
+const MAX_PART_LEN: usize = 24;
+
+/// Maximum length in characters of the nouns to decompose.
+const MAX_COMPOUND_LEN: usize = 64;
+
+/// Length in characters of the kanji words without penalty, and penalty per extra character.
+const KANJI_PENALTY: (usize, i64) = (2, 3000);
+
+/// Length in characters of the other words without penalty, and penalty per extra character.
+const OTHER_PENALTY: (usize, i64) = (7, 1700);
+
+/// Search mode of [`VibratoTokenizer::search_mode`](crate::VibratoTokenizer::search_mode),
+/// splitting long compound nouns into their parts, e.g. `関西国際空港` into `関西`, `国際` and
+/// `空港`.
+///
+/// Like Kuromoji, words of more than 2 kanji or of more than 7 other characters are penalized
+/// by their length, so that a compound is split when its parts are dictionary words whose
+/// costs, with their own penalty, are lower than the one of the compound. The parts are
+/// dictionary words, and unknown words are only split when they are katakana, e.g.
+/// `シニアソフトウェアエンジニア` into `シニア`, `ソフトウェア` and `エンジニア`. Words of more than 64
+/// characters are kept as they are.
+#[derive(Debug, Clone, Default)]
+pub struct SearchMode {
+    keep_compound: bool,
+}
+
+impl SearchMode {
+    /// Create a search mode replacing the compounds with their parts.
+    pub fn new() -> SearchMode {
+        SearchMode::default()
+    }
+
+    /// Whether to also emit the compound, as an overlay spanning its parts, so that it keeps
+    /// matching as a whole, e.g. in phrase queries. Disabled by default.
+    pub fn keep_compound(mut self, keep_compound: bool) -> SearchMode {
+        self.keep_compound = keep_compound;
+        self
+    }
+
+    /// Replace the long nouns of `tokens`, the morphemes of `text`, with their parts.
+    pub(crate) fn decompose(
+        &self,
+        workers: &WorkerPool,
+        kind: DictionaryKind,
+        text: &str,
+        tokens: Vec<MorphToken>,
+    ) -> Vec<MorphToken> {
+        let mut worker = workers.get();
+        let mut decomposed = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token.overlay
+                || (token.lex_type == LexType::Unknown && !token.surface.chars().all(is_katakana))
+                || token.surface.chars().count() > MAX_COMPOUND_LEN
+                || penalty(&token.surface) == 0
+                || !is_noun(&token)
+            {
+                decomposed.push(token);
+                continue;
+            }
+            let parts = split(&mut worker, kind, text, &token);
+            if parts.len() < 2 {
+                decomposed.push(token);
+                continue;
+            }
+            let mut parts = parts.into_iter();
+            decomposed.extend(parts.next());
+            if self.keep_compound {
+                decomposed.push(MorphToken {
+                    overlay: true,
+                    ..token
+                });
+            }
+            decomposed.extend(parts);
+        }
+        decomposed
+    }
+}
+
+fn is_noun(token: &MorphToken) -> bool {
+    token.features().pos().first() == Some(&"名詞")
+}
+
+fn penalty(surface: &str) -> i64 {
+    let len = surface.chars().count();
+    let (free, cost) = if surface.chars().all(is_kanji) {
+        KANJI_PENALTY
+    } else {
+        OTHER_PENALTY
+    };
+    len.saturating_sub(free) as i64 * cost
+}
+
+/// Returns the parts of the decomposition of `token` of the lowest cost, or `token` alone.
+fn split(
+    worker: &mut Worker<'_>,
+    kind: DictionaryKind,
+    text: &str,
+    token: &MorphToken,
+) -> Vec<MorphToken> {
+    let surface = &text[token.offset_from..token.offset_to];
+    let bounds = surface
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([surface.len()])
+        .collect::<Vec<_>>();
+    let n = bounds.len() - 1;
+    // Lowest cost of the decompositions of the first `j` chars, the start of their last part
+    // and the last part.
+    let mut best: Vec<Option<(i64, usize, MorphToken)>> = vec![None; n + 1];
+    best[0] = Some((0, 0, token.clone()));
+    for j in 1..=n {
+        for i in j.saturating_sub(MAX_PART_LEN)..j {
+            let Some(&(cost, _, _)) = best[i].as_ref() else {
+                continue;
+            };
+            let Some(part) = analyze(worker, kind, &surface[bounds[i]..bounds[j]]) else {
+                continue;
+            };
+            if part.lex_type == LexType::Unknown {
+                continue;
+            }
+            let cost = cost + i64::from(part.total_cost) + penalty(&part.surface);
+            if best[j].as_ref().is_none_or(|(c, _, _)| cost < *c) {
+                best[j] = Some((cost, i, part));
+            }
+        }
+    }
+    if best[n].is_none() {
+        return vec![];
+    }
+
+    let mut parts = vec![];
+    let mut j = n;
+    while j > 0 {
+        let (_, i, mut part) = best[j].take().unwrap();
+        part.offset_from += token.offset_from;
+        part.offset_to += token.offset_from;
+        part.char_from = token.char_from + i;
+        part.char_to = token.char_from + j;
+        part.negated = token.negated;
+        parts.push(part);
+        j = i;
+    }
+    parts.reverse();
+    parts
+}
+
+/// Analyze `part` on its own and return its morpheme if it is a single word.
+fn analyze(worker: &mut Worker<'_>, kind: DictionaryKind, part: &str) -> Option<MorphToken> {
+    worker.reset_sentence(part);
+    worker.tokenize();
+    (worker.num_tokens() == 1).then(|| MorphToken::from_vibrato(&worker.token(0), kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+    use crate::{PositionMode, VibratoTokenizer};
+
+    #[test]
+    fn decompose() {
+        let entry = "東京大学,1,1,3000,名詞,固有名詞,組織,*,*,*,東京大学,トウキョウダイガク,トーキョーダイガク\n";
+        let tokenizer = test_tokenizer()
+            .user_lexicon(entry.as_bytes())
+            .unwrap()
+            .position_mode(PositionMode::CharOffset);
+        let texts = |tokenizer: &VibratoTokenizer| {
+            tokenizer
+                .analyze("東京大学に行きました", None)
+                .into_iter()
+                .map(|t| (t.text, t.position, t.position_length))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&tokenizer)[0], ("東京大学".to_string(), 0, 4));
+
+        let search = tokenizer.clone().search_mode(SearchMode::new());
+        assert_eq!(
+            texts(&search)[..2],
+            [("東京".to_string(), 0, 2), ("大学".to_string(), 2, 2)]
+        );
+        let long = "a".repeat(4000);
+        assert_eq!(
+            search.analyze(&long, None).len(),
+            tokenizer.analyze(&long, None).len()
+        );
+
+        let compound = tokenizer
+            .search_mode(SearchMode::new().keep_compound(true))
+            .position_mode(PositionMode::Sequential);
+        assert_eq!(
+            texts(&compound)[..3],
+            [
+                ("東京".to_string(), 0, 1),
+                ("東京大学".to_string(), 0, 2),
+                ("大学".to_string(), 1, 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Splitting of documents into the chunks given to Vibrato.
+
+/// Maximum length in bytes of a chunk without a sentence boundary, split at the last `、` or
+/// space before it, or at a character boundary.
+const MAX_CHUNK_LEN: usize = 4096;
+
+const TERMINATORS: &[char] = &['。', '！', '？', '!', '?', '\n'];
+
+/// Closing brackets and quotes kept with the sentence they end, e.g. `」` after `。`.
+const CLOSING: &[char] = &[
+    '」', '』', '）', ')', '】', '〉', '》', '〕', '］', ']', '”', '’',
+];
+
+/// A chunk of a text, analyzed on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Chunk<'a> {
+    pub(crate) text: &'a str,
+    /// Byte offset of the chunk in the text.
+    pub(crate) offset: usize,
+    /// Char offset of the chunk in the text.
+    pub(crate) char_offset: usize,
+}
+
+/// Returns the sentences of `text`, ending after `。！？` or newlines and the closing
+/// brackets following them, and splits the sentences longer than [`MAX_CHUNK_LEN`].
+pub(crate) fn chunks(text: &str) -> Chunks<'_> {
+    Chunks {
+        text,
+        offset: 0,
+        char_offset: 0,
+    }
+}
+
+pub(crate) struct Chunks<'a> {
+    text: &'a str,
+    offset: usize,
+    char_offset: usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = Chunk<'a>;
+
+    fn next(&mut self) -> Option<Chunk<'a>> {
+        let rest = &self.text[self.offset..];
+        if rest.is_empty() {
+            return None;
+        }
+        let len = sentence_len(rest);
+        let chunk = Chunk {
+            text: &rest[..len],
+            offset: self.offset,
+            char_offset: self.char_offset,
+        };
+        self.offset += len;
+        self.char_offset += chunk.text.chars().count();
+        Some(chunk)
+    }
+}
+
+/// Returns the length in bytes of the first chunk of `text`.
+fn sentence_len(text: &str) -> usize {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if i + c.len_utf8() > MAX_CHUNK_LEN {
+            return fallback_len(text);
+        }
+        if TERMINATORS.contains(&c) {
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, c)) = chars.peek() {
+                if !TERMINATORS.contains(&c) && !CLOSING.contains(&c) {
+                    break;
+                }
+                end = j + c.len_utf8();
+                chars.next();
+            }
+            return end;
+        }
+    }
+    text.len()
+}
+
+/// Returns the length of the first chunk of a sentence longer than [`MAX_CHUNK_LEN`].
+fn fallback_len(text: &str) -> usize {
+    let mut end = MAX_CHUNK_LEN;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end]
+        .char_indices()
+        .rfind(|(_, c)| *c == '、' || c.is_whitespace())
+        .map(|(i, c)| i + c.len_utf8())
+        .filter(|&i| i > end / 2)
+        .unwrap_or(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let texts = |text: &'static str| chunks(text).map(|c| c.text).collect::<Vec<_>>();
+        assert_eq!(
+            texts("「東京に行きます。」と言った！本当？\n\nはい"),
+            vec!["「東京に行きます。」", "と言った！", "本当？\n\n", "はい"]
+        );
+        assert!(texts("").is_empty());
+
+        let long = "すもも".repeat(1000) + "、もも";
+        let chunks = chunks(&long).collect::<Vec<_>>();
+        assert!(chunks.iter().all(|c| c.text.len() <= MAX_CHUNK_LEN));
+        assert_eq!(chunks.iter().map(|c| c.text).collect::<String>(), long);
+        assert_eq!(chunks[1].offset, chunks[0].text.len());
+        assert_eq!(chunks[1].char_offset, chunks[0].text.chars().count());
+    }
+}
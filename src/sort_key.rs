@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+
+use crate::filter::width::fold_width;
+use crate::token::MorphToken;
+
+/// Katakana in the order of Japanese dictionaries (gojūon).
+const GOJUON: &str = "アイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワヰヱヲン";
+
+const VOICED: &str = "ガギグゲゴザジズゼゾダヂヅデドバビブベボパピプペポヴ";
+const UNVOICED: &str = "カキクケコサシスセソタチツテトハヒフヘホハヒフヘホウ";
+const SMALL: &str = "ァィゥェォッャュョヮヵヶ";
+const LARGE: &str = "アイウエオツヤユヨワカケ";
+
+const VOWELS: [(char, &str); 5] = [
+    ('ア', "アカサタナハマヤラワ"),
+    ('イ', "イキシチニヒミリヰ"),
+    ('ウ', "ウクスツヌフムユル"),
+    ('エ', "エケセテネヘメレヱ"),
+    ('オ', "オコソトノホモヨロヲ"),
+];
+
+/// Number of characters packed by [`SortKey::as_u64`].
+const PACKED_LEN: usize = 10;
+
+/// Collation key of a text in Japanese dictionary order, returned by
+/// [`VibratoTokenizer::sort_key`](crate::VibratoTokenizer::sort_key).
+///
+/// Texts are compared by their reading in katakana: first ignoring voiced sound marks, the size
+/// of small kana and replacing the prolonged sound mark with the vowel it extends (`カード` is
+/// sorted as `カアト`), then by the reading itself to break ties, like paper dictionaries do.
+/// Other characters are compared by code point: Latin letters and digits are sorted before
+/// kana, and kanji without a reading after them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SortKey {
+    reading: String,
+    primary: String,
+}
+
+impl SortKey {
+    pub(crate) fn from_morphemes(morphemes: &[MorphToken]) -> SortKey {
+        let mut reading = String::new();
+        for token in morphemes {
+            match token.features().reading() {
+                Some(r) => reading.push_str(r),
+                None => reading.push_str(&token.surface),
+            }
+        }
+        let reading = to_katakana(&fold_width(&reading).to_lowercase());
+        let primary = primary(&reading);
+        SortKey { reading, primary }
+    }
+
+    /// Normalized reading in katakana. Words without a reading are kept as they are.
+    pub fn reading(&self) -> &str {
+        &self.reading
+    }
+
+    /// A string whose byte order is the order of the keys, for a `STRING` field.
+    pub fn as_string(&self) -> String {
+        format!("{}\u{0}{}", self.primary, self.reading)
+    }
+
+    /// The first 10 characters of the key packed in a `u64`, for a tantivy fast field, so that
+    /// results can be sorted with `order_by_u64_field`. Texts sharing their first 10 characters
+    /// are not ordered among themselves, and neither are the characters other than kana.
+    pub fn as_u64(&self) -> u64 {
+        let mut packed = 0u64;
+        let mut chars = self.primary.chars();
+        for _ in 0..PACKED_LEN {
+            let rank = match chars.next() {
+                None => 0,
+                Some(c) => match GOJUON.chars().position(|g| g == c) {
+                    Some(i) => i as u64 + 2,
+                    None if c < 'ア' => 1,
+                    None => 63,
+                },
+            };
+            packed = packed << 6 | rank;
+        }
+        packed
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &SortKey) -> Ordering {
+        self.primary
+            .cmp(&other.primary)
+            .then_with(|| self.reading.cmp(&other.reading))
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &SortKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub(crate) fn to_katakana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3041}'..='\u{3096}' | 'ゝ' | 'ゞ' => char::from_u32(c as u32 + 0x60).unwrap(),
+            _ => c,
+        })
+        .collect()
+}
+
+fn translate(c: char, from: &str, to: &str) -> char {
+    from.chars()
+        .position(|f| f == c)
+        .map_or(c, |i| to.chars().nth(i).unwrap())
+}
+
+fn primary(reading: &str) -> String {
+    let mut primary = String::with_capacity(reading.len());
+    for c in reading.chars() {
+        let c = translate(translate(c, VOICED, UNVOICED), SMALL, LARGE);
+        if c == 'ー' {
+            let vowel = primary.chars().last().and_then(|last| {
+                VOWELS
+                    .iter()
+                    .find(|(_, row)| row.contains(last))
+                    .map(|(vowel, _)| *vowel)
+            });
+            if let Some(vowel) = vowel {
+                primary.push(vowel);
+                continue;
+            }
+        }
+        primary.push(c);
+    }
+    primary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn order() {
+        let tokenizer = test_tokenizer();
+        let key = tokenizer.sort_key("東京");
+        assert_eq!(key.reading(), "トウキョウ");
+        assert_eq!(primary("カード"), "カアト");
+        assert_eq!(primary("ジャズ"), "シヤス");
+
+        let mut texts = vec!["東京", "コンピューター", "すもも", "もも", "ＡＢＣ"];
+        texts.sort_by_key(|t| tokenizer.sort_key(t));
+        assert_eq!(
+            texts,
+            vec!["ＡＢＣ", "コンピューター", "すもも", "東京", "もも"]
+        );
+
+        let mut by_u64 = texts.clone();
+        by_u64.sort_by_key(|t| tokenizer.sort_key(t).as_u64());
+        assert_eq!(by_u64, texts);
+        let mut by_string = texts.clone();
+        by_string.sort_by_key(|t| tokenizer.sort_key(t).as_string());
+        assert_eq!(by_string, texts);
+
+        // Voiced sound marks only break ties.
+        assert!(tokenizer.sort_key("ハス") < tokenizer.sort_key("バス"));
+        assert!(tokenizer.sort_key("バス") < tokenizer.sort_key("ハソ"));
+    }
+}
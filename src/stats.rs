@@ -0,0 +1,212 @@
+//! Term statistics of a corpus, collected with the analyzer used for indexing.
+//!
+//! Relevance tuning, such as choosing stop words or the terms to boost, is easier with the
+//! statistics of the actual terms of the index. [`TermStatsCollector`] analyzes the documents
+//! of a corpus one at a time and counts the frequency and the document frequency of each term,
+//! and optionally how often two terms occur close to each other:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use tantivy::tokenizer::TextAnalyzer;
+//! use tantivy_vibrato::stats::TermStatsCollector;
+//! use tantivy_vibrato::VibratoTokenizer;
+//!
+//! let analyzer = TextAnalyzer::from(VibratoTokenizer::new("/path/to/dictionary")?);
+//! let mut collector = TermStatsCollector::new(analyzer).cooccurrence_window(5);
+//! for doc in ["東京大学に行く", "東京に行く"] {
+//!     collector.add_document(doc);
+//! }
+//! let stop_word_candidates = collector.terms().into_iter().take(100);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The collector implements [`TermStatistics`], so that it can weight the keywords of a
+//! [`KeywordExtractor`](crate::keywords::KeywordExtractor).
+use std::collections::{HashMap, HashSet};
+
+use tantivy::tokenizer::TextAnalyzer;
+
+use crate::keywords::TermStatistics;
+
+/// Frequencies of a term over a corpus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermFrequency {
+    /// Number of occurrences of the term.
+    pub term_freq: u64,
+    /// Number of documents containing the term.
+    pub doc_freq: u64,
+}
+
+/// Collects the term statistics of a corpus. See the [module documentation](self).
+#[derive(Clone)]
+pub struct TermStatsCollector {
+    analyzer: TextAnalyzer,
+    window: usize,
+    num_docs: u64,
+    num_tokens: u64,
+    terms: HashMap<String, TermFrequency>,
+    cooccurrences: HashMap<(String, String), u64>,
+}
+
+impl TermStatsCollector {
+    /// Create a collector analyzing the documents with `analyzer`.
+    pub fn new(analyzer: TextAnalyzer) -> TermStatsCollector {
+        TermStatsCollector {
+            analyzer,
+            window: 0,
+            num_docs: 0,
+            num_tokens: 0,
+            terms: HashMap::new(),
+            cooccurrences: HashMap::new(),
+        }
+    }
+
+    /// Count the pairs of different terms at most `window` tokens apart. The default, 0,
+    /// disables co-occurrence counting.
+    pub fn cooccurrence_window(mut self, window: usize) -> TermStatsCollector {
+        self.window = window;
+        self
+    }
+
+    /// Analyze `text` and add its terms to the statistics.
+    pub fn add_document(&mut self, text: &str) {
+        let mut stream = self.analyzer.token_stream(text);
+        let mut terms = vec![];
+        while let Some(token) = stream.next() {
+            terms.push(token.text.clone());
+        }
+
+        self.num_docs += 1;
+        self.num_tokens += terms.len() as u64;
+        let mut seen = HashSet::new();
+        for term in &terms {
+            let freq = self.terms.entry(term.clone()).or_default();
+            freq.term_freq += 1;
+            if seen.insert(term.as_str()) {
+                freq.doc_freq += 1;
+            }
+        }
+        for (i, a) in terms.iter().enumerate() {
+            for b in terms.iter().skip(i + 1).take(self.window) {
+                if a == b {
+                    continue;
+                }
+                let pair = if a < b { (a, b) } else { (b, a) };
+                *self
+                    .cooccurrences
+                    .entry((pair.0.clone(), pair.1.clone()))
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Add the statistics of another collector, e.g. of another shard of the corpus.
+    pub fn merge(&mut self, other: &TermStatsCollector) {
+        self.num_docs += other.num_docs;
+        self.num_tokens += other.num_tokens;
+        for (term, freq) in &other.terms {
+            let merged = self.terms.entry(term.clone()).or_default();
+            merged.term_freq += freq.term_freq;
+            merged.doc_freq += freq.doc_freq;
+        }
+        for (pair, count) in &other.cooccurrences {
+            *self.cooccurrences.entry(pair.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Number of documents added.
+    pub fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+
+    /// Number of tokens of the documents added.
+    pub fn num_tokens(&self) -> u64 {
+        self.num_tokens
+    }
+
+    /// Returns the frequencies of `term`.
+    pub fn term(&self, term: &str) -> TermFrequency {
+        self.terms.get(term).copied().unwrap_or_default()
+    }
+
+    /// Returns the terms with their frequencies, by decreasing document frequency, then term
+    /// frequency.
+    pub fn terms(&self) -> Vec<(String, TermFrequency)> {
+        let mut terms = self
+            .terms
+            .iter()
+            .map(|(term, freq)| (term.clone(), *freq))
+            .collect::<Vec<_>>();
+        terms.sort_by(|a, b| {
+            (b.1.doc_freq, b.1.term_freq, &a.0).cmp(&(a.1.doc_freq, a.1.term_freq, &b.0))
+        });
+        terms
+    }
+
+    /// Returns the number of times `a` and `b` occur within the co-occurrence window.
+    pub fn cooccurrence(&self, a: &str, b: &str) -> u64 {
+        let pair = if a < b { (a, b) } else { (b, a) };
+        self.cooccurrences
+            .get(&(pair.0.to_string(), pair.1.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the pairs of terms occurring within the co-occurrence window, most frequent
+    /// first. The terms of a pair are sorted.
+    pub fn cooccurrences(&self) -> Vec<((String, String), u64)> {
+        let mut pairs = self
+            .cooccurrences
+            .iter()
+            .map(|(pair, count)| (pair.clone(), *count))
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        pairs
+    }
+}
+
+impl TermStatistics for TermStatsCollector {
+    fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+
+    fn doc_freq(&self, term: &str) -> u64 {
+        self.term(term).doc_freq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tokenizer;
+
+    #[test]
+    fn collect() {
+        let tokenizer = test_tokenizer();
+        let mut collector =
+            TermStatsCollector::new(TextAnalyzer::from(tokenizer)).cooccurrence_window(1);
+        collector.add_document("東京の東京");
+        let mut other = collector.clone();
+        other.add_document("大学の東京");
+        collector.merge(&other);
+
+        assert_eq!(collector.num_docs(), 3);
+        assert_eq!(collector.num_tokens(), 9);
+        assert_eq!(
+            collector.term("東京"),
+            TermFrequency {
+                term_freq: 5,
+                doc_freq: 3
+            }
+        );
+        assert_eq!(collector.terms()[0].0, "東京");
+        assert_eq!(collector.cooccurrence("の", "東京"), 5);
+        assert_eq!(collector.cooccurrence("東京", "大学"), 0);
+        assert_eq!(
+            collector.cooccurrences()[0],
+            (("の".to_string(), "東京".to_string()), 5)
+        );
+        assert_eq!(collector.doc_freq("大学"), 1);
+    }
+}
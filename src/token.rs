@@ -0,0 +1,73 @@
+use vibrato::dictionary::LexType;
+
+use crate::features::{DictionaryKind, Features};
+
+/// A morpheme produced by Vibrato, carrying the dictionary information that tantivy's
+/// `Token` cannot hold.
+///
+/// Offsets are relative to the text given to Vibrato, i.e. after char filters are applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MorphToken {
+    /// Term text. Starts as the surface form and may be rewritten by filters.
+    pub text: String,
+    /// Surface form as it appears in the analyzed text.
+    pub surface: String,
+    /// Byte offset of the first character.
+    pub offset_from: usize,
+    /// Byte offset of the last character + 1.
+    pub offset_to: usize,
+    /// Character offset of the first character.
+    pub char_from: usize,
+    /// Character offset of the last character + 1.
+    pub char_to: usize,
+    /// Comma separated feature string of the dictionary entry.
+    pub feature: String,
+    /// Layout of `feature`.
+    pub kind: DictionaryKind,
+    /// Lexicon the entry comes from.
+    pub lex_type: LexType,
+    /// Word cost of the entry.
+    pub word_cost: i16,
+    /// Accumulated cost from the beginning of the sentence to this token.
+    pub total_cost: i32,
+    /// `true` if the token is not on the best segmentation but overlaid on it, e.g. a word of
+    /// an alternative segmentation. Overlays share the position of the words they cover.
+    pub overlay: bool,
+    /// `true` if the token is a verb or an adjective negated by the auxiliaries following it,
+    /// e.g. `高く` in `高くない`. Double negations cancel out.
+    pub negated: bool,
+}
+
+impl MorphToken {
+    pub(crate) fn from_vibrato(
+        token: &vibrato::token::Token<'_, '_>,
+        kind: DictionaryKind,
+    ) -> MorphToken {
+        let surface = token.surface().to_string();
+        MorphToken {
+            text: surface.clone(),
+            surface,
+            offset_from: token.range_byte().start,
+            offset_to: token.range_byte().end,
+            char_from: token.range_char().start,
+            char_to: token.range_char().end,
+            feature: token.feature().to_string(),
+            kind,
+            lex_type: token.lex_type(),
+            word_cost: token.word_cost(),
+            total_cost: token.total_cost(),
+            overlay: false,
+            negated: false,
+        }
+    }
+
+    /// Returns the parsed feature string.
+    pub fn features(&self) -> Features<'_> {
+        Features::parse(&self.feature, self.kind)
+    }
+
+    /// Returns the feature fields split by commas.
+    pub fn feature_fields(&self) -> Vec<&str> {
+        self.feature.split(',').collect()
+    }
+}
@@ -1,112 +1,1118 @@
-use log::error;
+use std::borrow::Cow;
+#[cfg(feature = "fs")]
 use std::fs;
 use std::io;
-use std::io::{BufReader, Read};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "fs")]
 use std::path;
 use std::sync::Arc;
 use thiserror::Error;
 
 use tantivy::tokenizer::{BoxTokenStream, Token as TToken, TokenStream, Tokenizer as TTokenizer};
+use vibrato::dictionary::LexType;
 use vibrato::{Dictionary, Tokenizer};
 
+use crate::builder::VibratoTokenizerBuilder;
+use crate::char_filter::{self, CharFilter, OffsetMap};
+use crate::entity_field::{self, EntityTokens};
+use crate::error_policy::{self, ErrorPolicy};
+use crate::explain::{CharFilterStep, Explanation, FilterStep};
+use crate::features::DictionaryKind;
+use crate::filter::{predicate, MorphFilter};
+use crate::fingerprint::{Fingerprint, Hasher, HashingReader};
+use crate::gazetteer::Gazetteer;
+use crate::lattice::Lattice;
+use crate::log_context::{self, LogContext};
+use crate::lookup::{self, DictionaryEntry};
+use crate::nbest;
+use crate::recognizer::{self, Recognizer};
+use crate::search_mode::SearchMode;
+use crate::sentence;
+use crate::sort_key::SortKey;
+use crate::token::MorphToken;
+use crate::utf16::{Utf16Offsets, Utf16Token};
+use crate::worker_pool::{WorkerPool, DEFAULT_POOL_SIZE};
+
 #[derive(Error, Debug)]
 pub enum TantivyVibratoError {
     #[error("IO error {0:?}")]
     IOError(#[from] io::Error),
     #[error("vibrate error {0:?}")]
     VibratoError(#[from] vibrato::errors::VibratoError),
+    #[error("invalid pattern {0:?}")]
+    PatternError(#[from] regex::Error),
+}
+
+/// Magic number of the zstd frames, at the start of the `.dic.zst` files distributed by Vibrato.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+pub(crate) type Result<T> = std::result::Result<T, TantivyVibratoError>;
+
+/// How token positions are assigned, set with
+/// [`VibratoTokenizer::position_mode`](crate::VibratoTokenizer::position_mode).
+///
+/// Positions decide what phrase queries and their slop match, so both modes index different
+/// positions for the same text and an index must be queried with the mode it was built with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PositionMode {
+    /// The position is the character offset of the token, e.g. 0, 2, 3 for `東京の空`, and
+    /// the position length is its number of characters. A phrase query then needs a slop to
+    /// match words apart from each other by more than one character.
+    CharOffset,
+    /// Positions are consecutive over the morphemes, e.g. 0, 1, 2 for `東京の空`, like Lucene
+    /// and the other tantivy tokenizers. Removed morphemes leave gaps. The default.
+    #[default]
+    Sequential,
 }
 
-type Result<T> = std::result::Result<T, TantivyVibratoError>;
+/// Options of Vibrato's tokenizer, set with a
+/// [`VibratoTokenizerBuilder`](crate::VibratoTokenizerBuilder).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct TokenizerOptions {
+    pub(crate) ignore_space: bool,
+    pub(crate) max_grouping_len: usize,
+}
 
+impl TokenizerOptions {
+    fn tokenizer(&self, dict: Dictionary) -> Result<Tokenizer> {
+        let tokenizer = Tokenizer::new(dict)
+            .ignore_space(self.ignore_space)?
+            .max_grouping_len(self.max_grouping_len);
+        Ok(tokenizer)
+    }
+}
+
+/// A tantivy tokenizer analyzing Japanese text with Vibrato.
+///
+/// # Reproducibility
+///
+/// The analysis is deterministic: the costs are integers and no step depends on hashing, the
+/// platform or the thread, so the same text analyzed with the same dictionary file and the
+/// same configuration gives the same tokens, bit for bit, at index time and at query time.
+/// Compare the [`fingerprint`](Self::fingerprint) of both sides to check that they match.
+///
+/// When several segmentations have the same lowest cost, Vibrato keeps, at each word boundary,
+/// the last candidate of equal cost in the order of the lattice: the previous word starting
+/// the latest, i.e. the shortest one, and among the entries spanning the same characters, the
+/// unknown words over the system lexicon over the user lexicon, then the entry appearing
+/// last in the lexicon. The order of the entries is the only part of this rule which differs
+/// between builds of a dictionary: sort the lexicon CSV files before building it to make the
+/// ties reproducible across builds. The rule is fixed by Vibrato, which does not expose its
+/// connection costs to resolve ties differently.
+///
+/// The alternatives of [`nbest`](Self::nbest) with the same cost are ordered by the position
+/// of the boundary forced inside the word, from the left.
 #[derive(Clone)]
 pub struct VibratoTokenizer {
     tokenizer: Arc<Tokenizer>,
+    workers: Arc<WorkerPool>,
+    dictionary_hash: u64,
+    /// The user lexicon entries added as is.
+    user_lexicon: Arc<String>,
+    /// The gazetteers, whose entries are tuned again when the system dictionary changes.
+    gazetteers: Vec<Arc<Gazetteer>>,
+    /// The user lexicon entries of the gazetteers, tuned with the current dictionary.
+    gazetteer_lexicon: Arc<String>,
+    options: TokenizerOptions,
+    kind: DictionaryKind,
+    char_filters: Vec<Arc<dyn CharFilter>>,
+    filters: Vec<Arc<dyn MorphFilter>>,
+    recognizers: Vec<Arc<dyn Recognizer>>,
+    position_mode: PositionMode,
+    nbest: usize,
+    search_mode: Option<SearchMode>,
+    pre_segmented: bool,
+    error_policy: ErrorPolicy,
+    log_context: LogContext,
 }
 
 impl VibratoTokenizer {
     /// Create a new `VibratoTokenizer`.
     ///
-    /// - `dict_path` is the path to the Vibrato dictionary file.
+    /// - `dict_path` is the path to the Vibrato dictionary file. With the `zstd` feature, it can
+    ///   be compressed with zstd, like the `.dic.zst` files distributed by Vibrato; it is then
+    ///   decompressed while it is read.
+    ///
+    /// Requires the `fs` feature, enabled by default. Without a filesystem, e.g. on
+    /// WebAssembly, use [`from_bytes`](Self::from_bytes) or [`from_reader`](Self::from_reader).
+    #[cfg(feature = "fs")]
     pub fn new<P: AsRef<path::Path>>(dict_path: P) -> Result<VibratoTokenizer> {
         let file = fs::File::open(&dict_path)?;
         Self::from_reader(file)
     }
 
+    /// Create a new `VibratoTokenizer` with the Vibrato dictionary file at `dict_path`, mapped
+    /// into memory instead of read, and without checking its consistency, which makes large
+    /// dictionaries load faster, e.g. at the startup of indexer processes.
+    ///
+    /// The dictionary is still decoded into the heap, as Vibrato has no zero-copy format: the
+    /// processes share the page cache of the file but not the decoded dictionary. Compressed
+    /// dictionaries are decompressed and checked like with [`new`](Self::new).
+    ///
+    /// # Safety
+    ///
+    /// The file must be a dictionary written by Vibrato (with the same version of Vibrato), and
+    /// must not be modified while it is loaded. Otherwise, tokenizing may panic or read out of
+    /// bounds.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn new_mmap<P: AsRef<path::Path>>(dict_path: P) -> Result<VibratoTokenizer> {
+        let file = fs::File::open(&dict_path)?;
+        let mmap = memmap2::Mmap::map(&file)?;
+        if mmap.starts_with(&ZSTD_MAGIC) {
+            return Self::from_bytes(&mmap);
+        }
+        let dict = Dictionary::read_unchecked(&mmap[..]);
+        #[cfg(feature = "metrics")]
+        if dict.is_err() {
+            crate::metrics::record_error("dictionary");
+        }
+        let mut hasher = Hasher::new();
+        hasher.write(&mmap);
+        Self::with_dictionary(dict?, hasher.finish(), TokenizerOptions::default())
+    }
+
+    /// Create a new `VibratoTokenizer` with the dictionary of `preset`, downloaded into the
+    /// default cache directory if it is not there yet, see
+    /// [`DictionaryDownloader`](crate::download::DictionaryDownloader).
+    #[cfg(feature = "download")]
+    pub fn from_preset(preset: &crate::download::Preset) -> Result<VibratoTokenizer> {
+        Self::new(crate::download::DictionaryDownloader::new().fetch(preset)?)
+    }
+
     /// Create a new `VibratoTokenizer`.
     ///
-    /// - `reader` is a reader of the Vibrato dictionary file.
+    /// - `reader` is a reader of the Vibrato dictionary file, optionally compressed with zstd.
+    ///   With the `zstd` feature, it is decompressed while it is read.
     pub fn from_reader<R: Read>(reader: R) -> Result<VibratoTokenizer> {
-        let dict = Dictionary::read(BufReader::new(reader))?;
-        let tokenizer = Arc::new(Tokenizer::new(dict));
+        Self::from_reader_with_options(reader, TokenizerOptions::default())
+    }
+
+    /// Returns a builder setting the options of Vibrato's tokenizer.
+    pub fn builder() -> VibratoTokenizerBuilder {
+        VibratoTokenizerBuilder::new()
+    }
+
+    pub(crate) fn from_reader_with_options<R: Read>(
+        reader: R,
+        options: TokenizerOptions,
+    ) -> Result<VibratoTokenizer> {
+        let mut reader = BufReader::new(reader);
+        if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+            #[cfg(feature = "zstd")]
+            return Self::read_dictionary(
+                BufReader::new(zstd::Decoder::with_buffer(reader)?),
+                options,
+            );
+            #[cfg(not(feature = "zstd"))]
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the dictionary is compressed with zstd: enable the zstd feature to read it",
+            )
+            .into());
+        }
+        Self::read_dictionary(reader, options)
+    }
+
+    /// Create a new `VibratoTokenizer` from the bytes of the Vibrato dictionary file, e.g.
+    /// embedded with `include_bytes!`, optionally compressed with zstd, see
+    /// [`from_reader`](Self::from_reader). This is the way to load a dictionary on WebAssembly.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VibratoTokenizer> {
+        Self::from_reader(bytes)
+    }
+
+    /// Create a new `VibratoTokenizer` from a loaded Vibrato dictionary, e.g. one built or
+    /// edited with Vibrato's API.
+    ///
+    /// The dictionary is serialized once to compute the [`fingerprint`](Self::fingerprint),
+    /// which is the same as when the dictionary is read from its file.
+    pub fn from_dictionary(dict: Dictionary) -> Result<VibratoTokenizer> {
+        let mut hasher = Hasher::new();
+        dict.write(&mut hasher)?;
+        Self::with_dictionary(dict, hasher.finish(), TokenizerOptions::default())
+    }
+
+    fn read_dictionary<R: Read>(reader: R, options: TokenizerOptions) -> Result<VibratoTokenizer> {
+        let mut reader = HashingReader::new(reader);
+        let dict = Dictionary::read(&mut reader);
+        #[cfg(feature = "metrics")]
+        if dict.is_err() {
+            crate::metrics::record_error("dictionary");
+        }
+        Self::with_dictionary(dict?, reader.finish(), options)
+    }
+
+    fn with_dictionary(
+        dict: Dictionary,
+        dictionary_hash: u64,
+        options: TokenizerOptions,
+    ) -> Result<VibratoTokenizer> {
+        let tokenizer = Arc::new(options.tokenizer(dict)?);
+        let kind = detect_kind(&tokenizer);
+
+        Ok(VibratoTokenizer {
+            workers: Arc::new(WorkerPool::new(tokenizer.clone(), DEFAULT_POOL_SIZE)),
+            tokenizer,
+            dictionary_hash,
+            user_lexicon: Arc::new(String::new()),
+            gazetteers: vec![],
+            gazetteer_lexicon: Arc::new(String::new()),
+            options,
+            kind,
+            char_filters: vec![],
+            filters: vec![],
+            recognizers: vec![],
+            position_mode: PositionMode::default(),
+            nbest: 1,
+            search_mode: None,
+            pre_segmented: false,
+            error_policy: ErrorPolicy::Strict,
+            log_context: LogContext::new(),
+        })
+    }
+
+    /// Layout of the feature strings of the dictionary, detected when it is loaded.
+    pub fn dictionary_kind(&self) -> DictionaryKind {
+        self.kind
+    }
+
+    /// Fingerprint of the analyzer: the dictionary content, the configuration including the
+    /// filters and their parameters, and the analysis behavior of this crate.
+    ///
+    /// Store it with the index and compare it at query time to detect analyzer drift.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Hasher::new();
+        hasher.write(&self.dictionary_hash.to_le_bytes());
+        hasher.write_str(&self.user_lexicon);
+        hasher.write_str(&self.gazetteer_lexicon);
+        hasher.write_str(&format!("{:?}", self.options));
+        hasher.write_str(&format!("{:?}", self.kind));
+        hasher.write_str(&format!("{:?}", self.position_mode));
+        hasher.write_str(&self.nbest.to_string());
+        hasher.write_str(&self.pre_segmented.to_string());
+        hasher.write_str(&format!("{:?}", self.error_policy));
+        if let Some(search_mode) = &self.search_mode {
+            hasher.write_str(&format!("{search_mode:?}"));
+        }
+        for filter in &self.char_filters {
+            hasher.write_str("char_filter");
+            hasher.write_str(filter.name());
+            hasher.write_str(&filter.config());
+        }
+        for filter in &self.filters {
+            hasher.write_str("filter");
+            hasher.write_str(filter.name());
+            hasher.write_str(&filter.config());
+        }
+        for recognizer in &self.recognizers {
+            hasher.write_str("recognizer");
+            hasher.write_str(recognizer.name());
+            hasher.write_str(&recognizer.config());
+        }
+        hasher.fingerprint()
+    }
+
+    /// Returns the dictionary entry of `surface` with the lowest cost in isolation.
+    ///
+    /// Vibrato does not expose its lexicon, so the other entries of the same surface cannot be
+    /// listed: `surface` is analyzed on its own, and the entry is returned if it is analyzed as
+    /// a single word. `None` means `surface` is split into several words.
+    ///
+    /// The entry, and so its reading, may differ from the one selected when `surface` appears
+    /// in a text. Use [`morphemes`](Self::morphemes) to get the entries selected in context.
+    pub fn best_entry(&self, surface: &str) -> Option<DictionaryEntry> {
+        let mut worker = self.workers.get();
+        lookup::probe(&mut worker, self.kind, surface)
+    }
+
+    /// Build the lattice of `text` for debugging its segmentation.
+    ///
+    /// `text` is analyzed as is, without applying char filters. See [`Lattice`] for what is
+    /// included. Use [`Lattice::to_dot`] to render it with GraphViz.
+    pub fn lattice(&self, text: &str) -> Lattice {
+        Lattice::build(&self.tokenizer, self.kind, text)
+    }
+
+    /// Create a new `VibratoTokenizer` with a user dictionary.
+    ///
+    /// - `dict_path` is the path to the Vibrato dictionary file.
+    /// - `user_lexicon_path` is the path to the user lexicon, see
+    ///   [`user_lexicon`](Self::user_lexicon).
+    #[cfg(feature = "fs")]
+    pub fn with_user_lexicon<P: AsRef<path::Path>, Q: AsRef<path::Path>>(
+        dict_path: P,
+        user_lexicon_path: Q,
+    ) -> Result<VibratoTokenizer> {
+        let file = fs::File::open(user_lexicon_path)?;
+        Self::new(dict_path)?.user_lexicon(file)
+    }
+
+    /// Add the entries of a user lexicon in MeCab CSV format, e.g. product names or jargon
+    /// the system dictionary does not know.
+    ///
+    /// Each line is `surface,left_id,right_id,cost,feature...`, where the connection ids are
+    /// those of the `left-id.def` and `right-id.def` files of the system dictionary. The entries
+    /// take part in the segmentation like the system ones. Use a [`Gazetteer`] to add words
+    /// without knowing the connection ids. Vibrato does not allow to edit a loaded dictionary,
+    /// so it is copied once in memory.
+    pub fn user_lexicon<R: Read>(mut self, mut reader: R) -> Result<VibratoTokenizer> {
+        let mut entries = String::new();
+        reader.read_to_string(&mut entries)?;
+        let mut user_lexicon = self.user_lexicon.as_str().to_string();
+        user_lexicon.push_str(&entries);
+        if !user_lexicon.is_empty() && !user_lexicon.ends_with('\n') {
+            user_lexicon.push('\n');
+        }
+        self.reset_user_lexicon(user_lexicon)?;
+        Ok(self)
+    }
+
+    /// Add the entities of `gazetteer` to the dictionary, so that they are always segmented as
+    /// single tokens.
+    ///
+    /// The entities are added as user lexicon entries whose costs are tuned with the current
+    /// dictionary, see [`Gazetteer`], and tuned again when the system dictionary is replaced
+    /// with [`ReloadableVibratoTokenizer::reload`](crate::ReloadableVibratoTokenizer::reload).
+    /// Vibrato does not allow to edit a loaded dictionary, so it is copied once in memory.
+    pub fn gazetteer(mut self, gazetteer: &Gazetteer) -> Result<VibratoTokenizer> {
+        let mut gazetteer_lexicon = self.gazetteer_lexicon.as_str().to_string();
+        gazetteer_lexicon.push_str(&gazetteer.to_user_lexicon(&self.tokenizer, self.kind));
+        self.load_user_lexicon(&format!("{}{gazetteer_lexicon}", self.user_lexicon))?;
+        self.gazetteers.push(Arc::new(gazetteer.clone()));
+        self.gazetteer_lexicon = Arc::new(gazetteer_lexicon);
+        Ok(self)
+    }
 
-        Ok(VibratoTokenizer { tokenizer })
+    /// Returns a copy of this tokenizer with the user lexicon replaced with `entries`, keeping
+    /// the gazetteers.
+    pub(crate) fn with_user_lexicon_entries(
+        &self,
+        mut entries: String,
+    ) -> Result<VibratoTokenizer> {
+        if !entries.is_empty() && !entries.ends_with('\n') {
+            entries.push('\n');
+        }
+        let mut tokenizer = self.clone();
+        tokenizer.reset_user_lexicon(entries)?;
+        Ok(tokenizer)
+    }
+
+    /// Returns a copy of this tokenizer with the system dictionary read from `reader`, keeping
+    /// the options, the user lexicon and the filters. The entries of the gazetteers are tuned
+    /// again with the new dictionary, since their connection ids and costs depend on it.
+    pub(crate) fn with_system_dictionary<R: Read>(&self, reader: R) -> Result<VibratoTokenizer> {
+        let loaded = Self::from_reader_with_options(reader, self.options)?;
+        let mut tokenizer = self.clone();
+        tokenizer.workers = Arc::new(WorkerPool::new(
+            loaded.tokenizer.clone(),
+            self.workers.size(),
+        ));
+        tokenizer.tokenizer = loaded.tokenizer;
+        tokenizer.dictionary_hash = loaded.dictionary_hash;
+        tokenizer.kind = loaded.kind;
+        if !self.user_lexicon.is_empty() || !self.gazetteers.is_empty() {
+            tokenizer.reset_user_lexicon(self.user_lexicon.as_str().to_string())?;
+        }
+        Ok(tokenizer)
+    }
+
+    /// Replace the user lexicon entries added as is with `user_lexicon`, then add the entries
+    /// of the gazetteers, each tuned with the dictionary including the previous ones.
+    fn reset_user_lexicon(&mut self, user_lexicon: String) -> Result<()> {
+        self.load_user_lexicon(&user_lexicon)?;
+        let mut gazetteer_lexicon = String::new();
+        for gazetteer in self.gazetteers.clone() {
+            gazetteer_lexicon.push_str(&gazetteer.to_user_lexicon(&self.tokenizer, self.kind));
+            self.load_user_lexicon(&format!("{user_lexicon}{gazetteer_lexicon}"))?;
+        }
+        self.user_lexicon = Arc::new(user_lexicon);
+        self.gazetteer_lexicon = Arc::new(gazetteer_lexicon);
+        Ok(())
+    }
+
+    /// Rebuild the tokenizer with the dictionary whose user lexicon is `lexicon`.
+    fn load_user_lexicon(&mut self, lexicon: &str) -> Result<()> {
+        let mut buf = vec![];
+        self.tokenizer.dictionary().write(&mut buf)?;
+        let dict = Dictionary::read(buf.as_slice())?
+            .reset_user_lexicon_from_reader((!lexicon.is_empty()).then_some(lexicon.as_bytes()))?;
+        self.tokenizer = Arc::new(self.options.tokenizer(dict)?);
+        self.workers = Arc::new(WorkerPool::new(self.tokenizer.clone(), self.workers.size()));
+        Ok(())
+    }
+
+    /// Set the maximum number of idle Vibrato workers kept for reuse. The default is 16.
+    ///
+    /// A worker holds the buffers of the lattice of a text; reusing them halves the time spent
+    /// analyzing short documents. The pool is shared by the clones of the tokenizer, so it
+    /// only needs to be as large as the number of threads tokenizing at the same time; 0
+    /// disables the reuse.
+    pub fn worker_pool_size(mut self, size: usize) -> VibratoTokenizer {
+        self.workers = Arc::new(WorkerPool::new(self.tokenizer.clone(), size));
+        self
+    }
+
+    /// Append a char filter applied to the text before morphological analysis.
+    pub fn char_filter<F: CharFilter>(mut self, filter: F) -> VibratoTokenizer {
+        self.char_filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Append a filter applied to the morphemes before they are emitted.
+    pub fn filter<F: MorphFilter>(mut self, filter: F) -> VibratoTokenizer {
+        self.filters.push(Arc::new(filter));
+        self
+    }
+
+    /// Append a recognizer of spans emitted as single tokens instead of being analyzed, e.g.
+    /// [`HashtagRecognizer`](crate::recognizer::HashtagRecognizer). See
+    /// [`recognizer`](crate::recognizer).
+    pub fn recognizer<R: Recognizer>(mut self, recognizer: R) -> VibratoTokenizer {
+        self.recognizers.push(Arc::new(recognizer));
+        self
+    }
+
+    /// Reproduce the default analysis of Lucene's Kuromoji (Elasticsearch's `kuromoji`
+    /// analyzer) as closely as possible, so that search results do not shift when migrating.
+    ///
+    /// This enables Kuromoji's default search mode, keeping the compounds like Elasticsearch's
+    /// default `discard_compound_token: false` (see [`search_mode`](Self::search_mode)),
+    /// appends the equivalents of the `kuromoji_baseform`, `kuromoji_part_of_speech`,
+    /// `cjk_width`, `ja_stop`, `kuromoji_stemmer` and `lowercase` filters with their default
+    /// settings, and makes positions consecutive over the morphemes like Lucene does.
+    pub fn kuromoji_compatible(self) -> VibratoTokenizer {
+        crate::kuromoji::configure(self)
+    }
+
+    /// Configure the recommended analysis of Japanese text: NFKC normalization, base forms,
+    /// the part-of-speech stop tags of the dictionary, Lucene's Japanese stop words and
+    /// lowercasing. See also [`register_japanese_analyzer`](crate::register_japanese_analyzer).
+    ///
+    /// The filters are appended to the ones already attached.
+    pub fn recommended(self) -> VibratoTokenizer {
+        crate::analyzer::configure(self)
+    }
+
+    /// Set how token positions are assigned. The default is [`PositionMode::Sequential`].
+    ///
+    /// Indexes built with earlier versions of this crate have the positions of
+    /// [`PositionMode::CharOffset`]: keep it for them, or reindex, as phrase queries with other
+    /// positions than the indexed ones miss. The mode is part of the
+    /// [`fingerprint`](Self::fingerprint).
+    pub fn position_mode(mut self, position_mode: PositionMode) -> VibratoTokenizer {
+        self.position_mode = position_mode;
+        self
+    }
+
+    /// Emit the words of up to `k - 1` alternative segmentations of each word besides the best
+    /// segmentation, overlaid on the positions of the words they cover. This improves the
+    /// recall of ambiguous strings such as person names at the cost of a larger index.
+    ///
+    /// Vibrato only returns the best path, so the alternatives of a word are found by forcing
+    /// a boundary inside it and analyzing both sides on their own, ranked by the sum of their
+    /// costs. Adjacent words which also form a single dictionary word are emitted as well.
+    /// The alternatives are given to the filters with [`MorphToken::overlay`] set.
+    ///
+    /// `k = 1`, the default, emits the best segmentation only.
+    pub fn nbest(mut self, k: usize) -> VibratoTokenizer {
+        self.nbest = k.max(1);
+        self
+    }
+
+    /// Split long compound nouns into their parts, like Kuromoji's search mode, to improve the
+    /// recall of compound-heavy text, optionally also emitting the compound. See
+    /// [`SearchMode`].
+    pub fn search_mode(mut self, search_mode: SearchMode) -> VibratoTokenizer {
+        self.search_mode = Some(search_mode);
+        self
+    }
+
+    /// Treat the text as already segmented into words delimited by whitespace, e.g. the
+    /// `-Owakati` output of MeCab, and emit the words as they are instead of analyzing them.
+    ///
+    /// Char filters and filters are still applied, but the words have no dictionary features,
+    /// so filters depending on them, such as the part-of-speech filters, see unknown words.
+    /// The delimiters are not counted in the character offsets, so that positions match the
+    /// ones of the same words analyzed from the unsegmented text.
+    pub fn pre_segmented(mut self, pre_segmented: bool) -> VibratoTokenizer {
+        self.pre_segmented = pre_segmented;
+        self
+    }
+
+    /// Set what to emit when the analysis of a text fails. See [`ErrorPolicy`].
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> VibratoTokenizer {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Attach `context` to the log records of every text analyzed by this tokenizer.
+    ///
+    /// See [`log_context`](crate::log_context) for a context scoped to the current thread.
+    pub fn log_context(mut self, context: LogContext) -> VibratoTokenizer {
+        self.log_context = context;
+        self
+    }
+
+    /// Returns the key sorting `text` in Japanese dictionary order by its reading, e.g. to
+    /// populate a fast field. Char filters are applied, but not filters.
+    pub fn sort_key(&self, text: &str) -> SortKey {
+        SortKey::from_morphemes(&self.char_filtered_morphemes(text))
+    }
+
+    /// Analyze `text` like the token stream does and return the tokens with their offsets in
+    /// UTF-16 code units besides their byte offsets, for frontends working in UTF-16 such as
+    /// JavaScript.
+    pub fn utf16_tokens(&self, text: &str) -> Vec<Utf16Token> {
+        let offsets = Utf16Offsets::new(text);
+        self.analyze(text, None)
+            .into_iter()
+            .map(|token| Utf16Token::new(token, &offsets))
+            .collect()
+    }
+
+    /// Analyze `text` once and return its tokens for the main field and for a field of its
+    /// proper nouns. See [`EntityTokens`].
+    ///
+    /// Proper nouns are the morphemes tagged `名詞,固有名詞` after the filters, including the
+    /// entities of a [`Gazetteer`] and the spans of the recognizers tagged so.
+    pub fn entity_tokens(&self, text: &str) -> EntityTokens {
+        EntityTokens::new(
+            text,
+            self.analyze_tagged(text, None, entity_field::is_proper_noun),
+        )
+    }
+
+    /// Analyze `text` and return a trace of every stage of the analysis:
+    /// the char filter rewrites, the raw morphemes and the effect of each filter.
+    pub fn explain(&self, text: &str) -> Explanation {
+        let mut explanation = Explanation {
+            text: text.to_string(),
+            char_filters: vec![],
+            morphemes: vec![],
+            filters: vec![],
+            tokens: vec![],
+        };
+        explanation.tokens = self.analyze(text, Some(&mut explanation));
+        explanation
+    }
+
+    /// Apply the char filters to `text`, returning the filtered text and the offset maps.
+    fn apply_char_filters<'a>(
+        &self,
+        text: &'a str,
+        mut trace: Option<&mut Explanation>,
+    ) -> (Cow<'a, str>, Vec<OffsetMap>) {
+        let mut analyzed = Cow::Borrowed(text);
+        let mut offset_maps: Vec<OffsetMap> = Vec::with_capacity(self.char_filters.len());
+        for filter in &self.char_filters {
+            let (filtered, offsets) = filter.filter(&analyzed);
+            if offsets.original_len() != analyzed.len() || offsets.filtered_len() != filtered.len()
+            {
+                log_context::log(
+                    log::Level::Warn,
+                    &self.log_context,
+                    format_args!(
+                        "char filter {} returned an offset map of {} -> {} bytes for {} -> {} bytes, \
+                         highlighting offsets may be wrong",
+                        filter.name(),
+                        offsets.original_len(),
+                        offsets.filtered_len(),
+                        analyzed.len(),
+                        filtered.len()
+                    ),
+                );
+            }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.char_filters.push(CharFilterStep {
+                    name: filter.name(),
+                    text: filtered.clone(),
+                    offsets: offsets.clone(),
+                });
+            }
+            analyzed = Cow::Owned(filtered);
+            offset_maps.push(offsets);
+        }
+        (analyzed, offset_maps)
+    }
+
+    /// Analyze `text` after applying the char filters. Offsets are relative to the filtered
+    /// text.
+    pub(crate) fn char_filtered_morphemes(&self, text: &str) -> Vec<MorphToken> {
+        let (analyzed, _) = self.apply_char_filters(text, None);
+        self.segment(&analyzed, 1)
+    }
+
+    /// Analyze `text` after applying the char filters, leaving out the spans of the
+    /// recognizers. Offsets are relative to the filtered text.
+    pub(crate) fn unrecognized_morphemes(&self, text: &str) -> Vec<MorphToken> {
+        let (analyzed, _) = self.apply_char_filters(text, None);
+        let spans =
+            recognizer::recognize_all(self.recognizers.iter().map(|r| r.as_ref()), &analyzed);
+        let mut tokens = self.segment(&analyzed, 1);
+        tokens.retain(|t| {
+            !spans
+                .iter()
+                .any(|s| s.range.start <= t.offset_from && t.offset_to <= s.range.end)
+        });
+        tokens
+    }
+
+    /// Analyze `text` with the recognizers and Vibrato, adding the words of up to `nbest - 1`
+    /// alternative segmentations as overlays.
+    fn segment(&self, text: &str, nbest: usize) -> Vec<MorphToken> {
+        if self.pre_segmented {
+            return self.split_words(text);
+        }
+        let spans = recognizer::recognize_all(self.recognizers.iter().map(|r| r.as_ref()), text);
+        let mut tokens = vec![];
+        // Byte and char offsets of the end of the last span.
+        let mut cursor = (0, 0);
+        for span in spans.iter().map(Some).chain([None]) {
+            let end = span.map_or(text.len(), |s| s.range.start);
+            cursor = self.analyze_range(text, cursor, end, nbest, false, &mut tokens);
+            let Some(span) = span else {
+                break;
+            };
+            let surface = span
+                .text
+                .clone()
+                .unwrap_or_else(|| text[span.range.clone()].to_string());
+            let char_to = cursor.1 + surface.chars().count();
+            if span.emit {
+                tokens.push(MorphToken {
+                    text: surface.clone(),
+                    surface,
+                    offset_from: span.range.start,
+                    offset_to: span.range.end,
+                    char_from: cursor.1,
+                    char_to,
+                    feature: span.feature.clone(),
+                    kind: self.kind,
+                    lex_type: LexType::Unknown,
+                    word_cost: 0,
+                    total_cost: 0,
+                    overlay: false,
+                    negated: false,
+                });
+                if let Some(inner) = &span.inner {
+                    let from = (
+                        inner.start,
+                        cursor.1 + text[cursor.0..inner.start].chars().count(),
+                    );
+                    self.analyze_range(text, from, inner.end, nbest, true, &mut tokens);
+                }
+            }
+            cursor = (span.range.end, char_to);
+        }
+        if nbest > 1 || spans.iter().any(|s| s.inner.is_some()) {
+            tokens.sort_by_key(|t| (t.char_from, t.overlay));
+        }
+        tokens
+    }
+
+    /// Returns the words of the pre-segmented `text`. Char offsets skip the delimiters.
+    fn split_words(&self, text: &str) -> Vec<MorphToken> {
+        let mut tokens = vec![];
+        let mut chars = 0;
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(i),
+                (true, Some(from)) => {
+                    let word = &text[from..i];
+                    let len = word.chars().count();
+                    tokens.push(MorphToken {
+                        text: word.to_string(),
+                        surface: word.to_string(),
+                        offset_from: from,
+                        offset_to: i,
+                        char_from: chars,
+                        char_to: chars + len,
+                        feature: "*".to_string(),
+                        kind: self.kind,
+                        lex_type: LexType::Unknown,
+                        word_cost: 0,
+                        total_cost: 0,
+                        overlay: false,
+                        negated: false,
+                    });
+                    chars += len;
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        tokens
+    }
+
+    /// Analyze `text[from.0..to]` on its own, where `from` is the byte and char offsets of its
+    /// start, and append the morphemes with offsets relative to `text` to `tokens`. Returns the
+    /// offsets of `to`.
+    fn analyze_range(
+        &self,
+        text: &str,
+        from: (usize, usize),
+        to: usize,
+        nbest: usize,
+        overlay: bool,
+        tokens: &mut Vec<MorphToken>,
+    ) -> (usize, usize) {
+        let range = &text[from.0..to];
+        if range.is_empty() {
+            return from;
+        }
+        let mut morphemes = self.morphemes(range);
+        if let Some(search_mode) = &self.search_mode {
+            morphemes = search_mode.decompose(&self.workers, self.kind, range, morphemes);
+        }
+        if nbest > 1 {
+            let overlays =
+                nbest::alternatives(&self.workers, self.kind, range, &morphemes, nbest);
+            morphemes.extend(overlays);
+        }
+        for mut token in morphemes {
+            token.offset_from += from.0;
+            token.offset_to += from.0;
+            token.char_from += from.1;
+            token.char_to += from.1;
+            token.overlay |= overlay;
+            tokens.push(token);
+        }
+        (to, from.1 + range.chars().count())
+    }
+
+    /// Returns `true` if positions are consecutive over the morphemes rather than character
+    /// offsets.
+    pub(crate) fn has_sequential_positions(&self) -> bool {
+        self.position_mode == PositionMode::Sequential
+    }
+
+    pub(crate) fn analyze(&self, text: &str, trace: Option<&mut Explanation>) -> Vec<TToken> {
+        self.analyze_tagged(text, trace, |_| ())
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    /// Analyze `text` like [`analyze`](Self::analyze), tagging each token with `tag` applied
+    /// to the filtered morpheme it comes from.
+    pub(crate) fn analyze_tagged<T, F: Fn(&MorphToken) -> T>(
+        &self,
+        text: &str,
+        mut trace: Option<&mut Explanation>,
+        tag: F,
+    ) -> Vec<(TToken, T)> {
+        let (analyzed, offset_maps) = self.apply_char_filters(text, trace.as_deref_mut());
+
+        let mut tokens = self.segment(&analyzed, self.nbest);
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.morphemes = tokens.clone();
+        }
+        let mut starts = match self.position_mode {
+            PositionMode::CharOffset => vec![],
+            PositionMode::Sequential => tokens
+                .iter()
+                .filter(|t| !t.overlay)
+                .map(|t| t.char_from)
+                .collect(),
+        };
+
+        for filter in &self.filters {
+            match trace.as_deref_mut() {
+                Some(trace) => {
+                    let before = tokens.clone();
+                    filter.filter(&mut tokens);
+                    trace
+                        .filters
+                        .push(FilterStep::new(filter.as_ref(), &before, &tokens));
+                }
+                None => filter.filter(&mut tokens),
+            }
+        }
+
+        log_context::log(
+            log::Level::Trace,
+            &self.log_context,
+            format_args!("analyzed {} bytes into {} tokens", text.len(), tokens.len()),
+        );
+
+        if self.position_mode == PositionMode::Sequential {
+            starts.extend(tokens.iter().filter(|t| !t.overlay).map(|t| t.char_from));
+            starts.sort_unstable();
+            starts.dedup();
+        }
+
+        tokens
+            .into_iter()
+            .map(|t| {
+                let offsets = char_filter::original_range(&offset_maps, t.offset_from..t.offset_to);
+                let (position, position_length) = match self.position_mode {
+                    PositionMode::CharOffset => (t.char_from, t.char_to - t.char_from),
+                    PositionMode::Sequential => {
+                        // Overlays take the position of the word they start in.
+                        let position = starts
+                            .partition_point(|&s| s <= t.char_from)
+                            .saturating_sub(1);
+                        let end = starts.partition_point(|&s| s < t.char_to);
+                        (position, (end - position).max(1))
+                    }
+                };
+                let tag = tag(&t);
+                let token = TToken {
+                    offset_from: offsets.start,
+                    offset_to: offsets.end,
+                    position,
+                    position_length,
+                    text: t.text,
+                };
+                (token, tag)
+            })
+            .collect()
+    }
+
+    /// Analyze `text` like the token stream and return each token with the morpheme it comes
+    /// from, after the filters, whose [`features`](MorphToken::features) give its part of
+    /// speech, conjugation, lemma and reading, e.g. for analytics or processing downstream.
+    ///
+    /// The tokens have the offsets in `text` and the positions of the token stream; the
+    /// offsets of the morphemes are relative to the text after the char filters.
+    pub fn analyze_with_features(&self, text: &str) -> Vec<(TToken, MorphToken)> {
+        self.analyze_tagged(text, None, MorphToken::clone)
+    }
+
+    /// Analyze `text` with Vibrato and return the raw morphemes.
+    ///
+    /// Char filters and filters are not applied. The negated predicates are flagged with
+    /// [`MorphToken::negated`]. Vibrato analyzes each sentence of `text` on its own, so that
+    /// long documents don't build a single lattice.
+    pub fn morphemes(&self, text: &str) -> Vec<MorphToken> {
+        let mut worker = self.workers.get();
+        let mut tokens = vec![];
+        for chunk in sentence::chunks(text) {
+            worker.reset_sentence(chunk.text);
+            worker.tokenize();
+            tokens.extend(worker.token_iter().map(|t| {
+                let mut token = MorphToken::from_vibrato(&t, self.kind);
+                token.offset_from += chunk.offset;
+                token.offset_to += chunk.offset;
+                token.char_from += chunk.char_offset;
+                token.char_to += chunk.char_offset;
+                token
+            }));
+        }
+        predicate::tag_negation(&mut tokens);
+        tokens
     }
 }
 
+fn detect_kind(tokenizer: &Tokenizer) -> DictionaryKind {
+    let mut worker = tokenizer.new_worker();
+    worker.reset_sentence("の");
+    worker.tokenize();
+    worker
+        .token_iter()
+        .find(|t| t.lex_type() == vibrato::dictionary::LexType::System)
+        .map(|t| DictionaryKind::detect(t.feature()))
+        .unwrap_or(DictionaryKind::Ipadic)
+}
+
 impl TTokenizer for VibratoTokenizer {
     fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
-        let mut worker = self.tokenizer.new_worker();
-        worker.reset_sentence(text);
-        worker.tokenize();
-
-        let tokens = worker
-            .token_iter()
-            .map(|t| TToken {
-                offset_from: t.range_byte().start,
-                offset_to: t.range_byte().end,
-                position: t.range_char().start,
-                position_length: t.range_char().end - t.range_char().start,
-                text: t.surface().to_string(),
-            })
-            .collect();
+        if self.error_policy == ErrorPolicy::Strict {
+            return self.stream(text);
+        }
+        let payload = match panic::catch_unwind(AssertUnwindSafe(|| self.stream(text))) {
+            Ok(stream) => return stream,
+            Err(payload) => payload,
+        };
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown error");
+        log_context::log(
+            log::Level::Error,
+            &self.log_context,
+            format_args!(
+                "failed to analyze {} bytes ({message}), {}",
+                text.len(),
+                match self.error_policy {
+                    ErrorPolicy::FallbackNgram => "indexing character bigrams",
+                    _ => "skipping the text",
+                }
+            ),
+        );
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_error("analysis");
 
-        let stream = VibratoTokenStream {
-            tokens,
-            index: None,
+        let tokens = match self.error_policy {
+            ErrorPolicy::FallbackNgram => {
+                error_policy::bigrams(text, self.has_sequential_positions())
+            }
+            _ => vec![],
         };
+        BoxTokenStream::from(VibratoTokenStream::new(tokens))
+    }
+}
 
-        BoxTokenStream::from(stream)
+impl VibratoTokenizer {
+    fn stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        if self.is_plain() {
+            let stream = WordStream::new(&self.workers, text, self.position_mode);
+            log_context::log(
+                log::Level::Trace,
+                &self.log_context,
+                format_args!(
+                    "analyzed {} bytes into {} tokens",
+                    text.len(),
+                    stream.words.len()
+                ),
+            );
+
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_tokenization(stream.words.len(), started.elapsed());
+
+            return BoxTokenStream::from(stream);
+        }
+
+        let tokens = self.analyze(text, None);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_tokenization(tokens.len(), started.elapsed());
+
+        BoxTokenStream::from(VibratoTokenStream::new(tokens))
     }
+
+    /// Returns `true` if the tokens are the words of Vibrato as is, without char filters,
+    /// filters, recognizers or overlays, so that the token stream can skip the conversion into
+    /// morphemes.
+    fn is_plain(&self) -> bool {
+        self.char_filters.is_empty()
+            && self.filters.is_empty()
+            && self.recognizers.is_empty()
+            && self.nbest == 1
+            && self.search_mode.is_none()
+            && !self.pre_segmented
+    }
+}
+
+pub(crate) struct VibratoTokenStream {
+    tokens: std::vec::IntoIter<TToken>,
+    token: TToken,
 }
 
-struct VibratoTokenStream {
-    tokens: Vec<TToken>,
-    index: Option<usize>,
+impl VibratoTokenStream {
+    pub(crate) fn new(tokens: Vec<TToken>) -> VibratoTokenStream {
+        VibratoTokenStream {
+            tokens: tokens.into_iter(),
+            token: TToken::default(),
+        }
+    }
 }
 
 impl TokenStream for VibratoTokenStream {
     fn advance(&mut self) -> bool {
-        let next_index = self.index.map(|i| i + 1).unwrap_or(0);
-        if next_index < self.tokens.len() {
-            self.index = Some(next_index);
-            true
-        } else {
-            false
+        match self.tokens.next() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
         }
     }
 
     fn token(&self) -> &TToken {
-        &self.tokens[self.index.unwrap()]
+        &self.token
     }
 
     fn token_mut(&mut self) -> &mut TToken {
-        &mut self.tokens[self.index.unwrap()]
+        &mut self.token
+    }
+}
+
+/// Token stream of the words of Vibrato as is. The words are kept as offsets, and the text of
+/// each token is copied into the same buffer when the stream advances.
+///
+/// The stream stays eager: all the chunks of the text are analyzed when it is created, since
+/// the worker borrows the tokenizer and cannot be kept by a stream outliving the call.
+struct WordStream<'a> {
+    text: &'a str,
+    /// Byte and char ranges of the words.
+    words: Vec<(Range<usize>, Range<usize>)>,
+    position_mode: PositionMode,
+    index: usize,
+    token: TToken,
+}
+
+impl<'a> WordStream<'a> {
+    fn new(workers: &WorkerPool, text: &'a str, position_mode: PositionMode) -> WordStream<'a> {
+        let mut worker = workers.get();
+        let mut words = vec![];
+        for chunk in sentence::chunks(text) {
+            worker.reset_sentence(chunk.text);
+            worker.tokenize();
+            words.extend(worker.token_iter().map(|t| {
+                let (bytes, chars) = (t.range_byte(), t.range_char());
+                (
+                    bytes.start + chunk.offset..bytes.end + chunk.offset,
+                    chars.start + chunk.char_offset..chars.end + chunk.char_offset,
+                )
+            }));
+        }
+        WordStream {
+            text,
+            words,
+            position_mode,
+            index: 0,
+            token: TToken::default(),
+        }
+    }
+}
+
+impl TokenStream for WordStream<'_> {
+    fn advance(&mut self) -> bool {
+        let Some((bytes, chars)) = self.words.get(self.index) else {
+            return false;
+        };
+        let (position, position_length) = match self.position_mode {
+            PositionMode::CharOffset => (chars.start, chars.len()),
+            PositionMode::Sequential => (self.index, 1),
+        };
+        self.token.offset_from = bytes.start;
+        self.token.offset_to = bytes.end;
+        self.token.position = position;
+        self.token.position_length = position_length;
+        self.token.text.clear();
+        self.token.text.push_str(&self.text[bytes.clone()]);
+        self.index += 1;
+        true
+    }
+
+    fn token(&self) -> &TToken {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut TToken {
+        &mut self.token
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    fn tokenizer() -> VibratoTokenizer {
-        VibratoTokenizer::new("./system.dic")
-            .expect("system.dic is required in the project root directory")
-    }
+    use crate::char_filter::OffsetMapBuilder;
+    use crate::explain::TokenEffect;
+    use crate::test_tokenizer;
 
     #[test]
     fn test1() {
-        let tokenizer = tokenizer();
+        let tokenizer = test_tokenizer();
         let mut stream = tokenizer.token_stream("すもももももももものうち");
         let mut tokens = vec![];
         while let Some(token) = stream.next() {
@@ -126,48 +1132,48 @@ mod tests {
             assert_eq!(token.text, "も");
             assert_eq!(token.offset_from, 9);
             assert_eq!(token.offset_to, 12);
-            assert_eq!(token.position, 3);
+            assert_eq!(token.position, 1);
         }
         {
             let token = &tokens[2];
             assert_eq!(token.text, "もも");
             assert_eq!(token.offset_from, 12);
             assert_eq!(token.offset_to, 18);
-            assert_eq!(token.position, 4);
+            assert_eq!(token.position, 2);
         }
         {
             let token = &tokens[3];
             assert_eq!(token.text, "も");
             assert_eq!(token.offset_from, 18);
             assert_eq!(token.offset_to, 21);
-            assert_eq!(token.position, 6);
+            assert_eq!(token.position, 3);
         }
         {
             let token = &tokens[4];
             assert_eq!(token.text, "もも");
             assert_eq!(token.offset_from, 21);
             assert_eq!(token.offset_to, 27);
-            assert_eq!(token.position, 7);
+            assert_eq!(token.position, 4);
         }
         {
             let token = &tokens[5];
             assert_eq!(token.text, "の");
             assert_eq!(token.offset_from, 27);
             assert_eq!(token.offset_to, 30);
-            assert_eq!(token.position, 9);
+            assert_eq!(token.position, 5);
         }
         {
             let token = &tokens[6];
             assert_eq!(token.text, "うち");
             assert_eq!(token.offset_from, 30);
             assert_eq!(token.offset_to, 36);
-            assert_eq!(token.position, 10);
+            assert_eq!(token.position, 6);
         }
     }
 
     #[test]
     fn empty() {
-        let tokenizer = tokenizer();
+        let tokenizer = test_tokenizer();
         let mut stream = tokenizer.token_stream("");
         let mut tokens = vec![];
         while let Some(token) = stream.next() {
@@ -176,4 +1182,532 @@ mod tests {
 
         assert_eq!(tokens.len(), 0);
     }
+
+    struct RemoveAsterisk;
+
+    impl CharFilter for RemoveAsterisk {
+        fn filter(&self, text: &str) -> (String, OffsetMap) {
+            let mut filtered = String::new();
+            let mut offsets = OffsetMapBuilder::new();
+            for c in text.chars() {
+                if c == '*' {
+                    offsets.replace(1, 0);
+                } else {
+                    filtered.push(c);
+                    offsets.copy(c.len_utf8());
+                }
+            }
+            (filtered, offsets.build())
+        }
+    }
+
+    struct RemoveParticles;
+
+    impl MorphFilter for RemoveParticles {
+        fn filter(&self, tokens: &mut Vec<MorphToken>) {
+            tokens.retain(|t| !t.feature.starts_with("助詞"));
+        }
+
+        fn reason(&self, token: &MorphToken) -> Option<String> {
+            Some(format!("{} is a particle", token.surface))
+        }
+    }
+
+    #[test]
+    fn filters() {
+        let tokenizer = test_tokenizer()
+            .char_filter(RemoveAsterisk)
+            .filter(RemoveParticles);
+        let mut stream = tokenizer.token_stream("*すもも*もももももものうち");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.clone());
+        }
+
+        let texts = tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["すもも", "もも", "もも", "うち"]);
+        assert_eq!(tokens[0].offset_from, 1);
+        assert_eq!(tokens[0].offset_to, 10);
+        assert_eq!(tokens[1].offset_from, 14);
+        assert_eq!(tokens[1].offset_to, 20);
+    }
+
+    #[test]
+    fn explain() {
+        let tokenizer = test_tokenizer()
+            .char_filter(RemoveAsterisk)
+            .filter(RemoveParticles);
+        let explanation = tokenizer.explain("*すもも*もももももものうち");
+
+        assert_eq!(explanation.char_filters.len(), 1);
+        let step = &explanation.char_filters[0];
+        assert_eq!(step.name, "RemoveAsterisk");
+        assert_eq!(step.text, "すもももももももものうち");
+        assert_eq!(step.offsets.replacements().count(), 2);
+
+        assert_eq!(explanation.morphemes.len(), 7);
+
+        assert_eq!(explanation.filters.len(), 1);
+        let step = &explanation.filters[0];
+        assert_eq!(step.name, "RemoveParticles");
+        let dropped = step
+            .effects
+            .iter()
+            .filter_map(|e| match e {
+                TokenEffect::Dropped { token, reason } => {
+                    Some((token.surface.as_str(), reason.as_deref()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dropped,
+            vec![
+                ("も", Some("も is a particle")),
+                ("も", Some("も is a particle")),
+                ("の", Some("の is a particle")),
+            ]
+        );
+        assert_eq!(explanation.tokens.len(), 4);
+    }
+
+    #[test]
+    fn best_entry() {
+        let tokenizer = test_tokenizer();
+        assert_eq!(tokenizer.dictionary_kind(), DictionaryKind::Ipadic);
+
+        let entry = tokenizer.best_entry("すもも").unwrap();
+        assert_eq!(entry.surface, "すもも");
+        assert_eq!(entry.lex_type, vibrato::dictionary::LexType::System);
+        assert_eq!(entry.features().pos_tag(), "名詞-一般");
+        assert_eq!(entry.features().reading(), Some("スモモ"));
+
+        assert!(tokenizer.best_entry("すもももも").is_none());
+        assert!(tokenizer.best_entry("").is_none());
+    }
+
+    #[test]
+    fn lattice() {
+        let tokenizer = test_tokenizer();
+        let lattice = tokenizer.lattice("すもももも");
+
+        let path = lattice
+            .best_path()
+            .map(|n| n.surface.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(path, vec!["すもも", "もも"]);
+        assert_eq!(lattice.nodes.first().unwrap().surface, "BOS");
+        assert_eq!(lattice.nodes.last().unwrap().surface, "EOS");
+        assert!(lattice
+            .nodes
+            .iter()
+            .any(|n| n.surface == "も" && n.char_from == 3 && !n.on_best_path));
+
+        let path_edges = lattice
+            .edges
+            .iter()
+            .filter(|e| e.on_best_path)
+            .collect::<Vec<_>>();
+        assert_eq!(path_edges.len(), 3);
+        assert!(path_edges[..2].iter().all(|e| e.cost.is_some()));
+        assert!(lattice
+            .edges
+            .iter()
+            .filter(|e| !e.on_best_path)
+            .all(|e| e.cost.is_none()));
+
+        let dot = lattice.to_dot();
+        assert!(dot.starts_with("digraph lattice {"));
+        assert!(dot.contains("すもも"));
+    }
+
+    #[test]
+    fn kuromoji_compatible() {
+        let tokenizer = test_tokenizer().kuromoji_compatible();
+        let mut stream = tokenizer.token_stream("私は東京に行きました");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.clone());
+        }
+
+        let tokens = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.position, t.offset_from, t.offset_to))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![("私", 0, 0, 3), ("東京", 2, 6, 12), ("行く", 4, 15, 21)]
+        );
+    }
+
+    #[test]
+    fn fingerprint() {
+        let fingerprint = test_tokenizer().fingerprint();
+        assert_eq!(test_tokenizer().fingerprint(), fingerprint);
+        assert_eq!(test_tokenizer().clone().fingerprint(), fingerprint);
+
+        let kuromoji = test_tokenizer().kuromoji_compatible().fingerprint();
+        assert_ne!(kuromoji, fingerprint);
+        assert_ne!(
+            test_tokenizer().filter(RemoveParticles).fingerprint(),
+            fingerprint
+        );
+        assert_eq!(kuromoji.to_string().parse(), Ok(kuromoji));
+
+        let skip = test_tokenizer().error_policy(ErrorPolicy::Skip).fingerprint();
+        assert_ne!(skip, fingerprint);
+        assert_ne!(
+            test_tokenizer()
+                .error_policy(ErrorPolicy::FallbackNgram)
+                .fingerprint(),
+            skip
+        );
+    }
+
+    #[test]
+    fn nbest() {
+        let tokenizer = test_tokenizer().nbest(2).position_mode(PositionMode::CharOffset);
+        let mut stream = tokenizer.token_stream("すもももも");
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push(token.clone());
+        }
+
+        let tokens = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.position, t.position_length))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                ("すもも", 0, 3),
+                ("すも", 0, 2),
+                ("も", 2, 1),
+                ("もも", 3, 2),
+                ("も", 3, 1),
+                ("も", 4, 1),
+            ]
+        );
+
+        let tokenizer = tokenizer.position_mode(PositionMode::Sequential);
+        let mut stream = tokenizer.token_stream("すもももも");
+        let mut positions = vec![];
+        while let Some(token) = stream.next() {
+            positions.push(token.position);
+        }
+        assert_eq!(positions, vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn context_reading() {
+        let tokenizer = test_tokenizer();
+        let reading = |text: &str| {
+            let morphemes = tokenizer.morphemes(text);
+            let token = morphemes.iter().find(|t| t.surface == "人").unwrap();
+            token.features().reading().map(str::to_string)
+        };
+        assert_eq!(reading("人").as_deref(), Some("ヒト"));
+        assert_eq!(reading("東京人").as_deref(), Some("ジン"));
+
+        // The lattice keeps both entries.
+        let lattice = tokenizer.lattice("東京人");
+        let readings = lattice
+            .nodes
+            .iter()
+            .filter(|n| n.surface == "人")
+            .map(|n| (n.feature.split(',').nth(7).unwrap(), n.on_best_path))
+            .collect::<Vec<_>>();
+        assert_eq!(readings, vec![("ジン", true), ("ヒト", false)]);
+    }
+
+    #[test]
+    fn gazetteer() {
+        let texts = |tokenizer: &VibratoTokenizer| {
+            tokenizer
+                .morphemes("新東京大学に")
+                .into_iter()
+                .map(|t| t.surface)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&test_tokenizer()), vec!["新", "東京", "大学", "に"]);
+
+        let gazetteer = Gazetteer::from_reader("# entities\n東京大学\n".as_bytes()).unwrap();
+        assert_eq!(gazetteer.len(), 1);
+        let with_gazetteer = test_tokenizer().gazetteer(&gazetteer).unwrap();
+        assert_eq!(texts(&with_gazetteer), vec!["新", "東京大学", "に"]);
+        let entity = &with_gazetteer.morphemes("東京大学")[0];
+        assert_eq!(entity.lex_type, vibrato::dictionary::LexType::User);
+        assert_eq!(entity.features().pos_tag(), "名詞-固有名詞-一般");
+        assert_ne!(with_gazetteer.fingerprint(), test_tokenizer().fingerprint());
+
+        let overrides =
+            Gazetteer::from_reader("もも\t\t-20000\nうち\t\t30000\n".as_bytes()).unwrap();
+        let with_overrides = test_tokenizer().gazetteer(&overrides).unwrap();
+        let morphemes = with_overrides.morphemes("もものうち");
+        let texts = morphemes
+            .iter()
+            .map(|t| t.surface.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["もも", "の", "うち"]);
+        assert_eq!(morphemes[0].word_cost, -20000);
+        assert_eq!(morphemes[2].word_cost, 2000);
+        assert_eq!(morphemes[0].feature, test_tokenizer().best_entry("もも").unwrap().feature);
+    }
+
+    #[test]
+    fn word_stream() {
+        let text = "すもももももももものうち、東京 ChatGPT";
+        for tokenizer in [
+            test_tokenizer(),
+            test_tokenizer().position_mode(PositionMode::CharOffset),
+        ] {
+            assert!(tokenizer.is_plain());
+            let mut stream = tokenizer.token_stream(text);
+            let mut tokens = vec![];
+            while let Some(token) = stream.next() {
+                tokens.push(token.clone());
+            }
+            assert_eq!(tokens, tokenizer.analyze(text, None));
+        }
+    }
+
+    #[test]
+    fn in_memory_dictionary() {
+        let bytes = fs::read("./system.dic").unwrap();
+        let from_bytes = VibratoTokenizer::from_bytes(&bytes).unwrap();
+        assert_eq!(from_bytes.fingerprint(), test_tokenizer().fingerprint());
+        let dict = Dictionary::read(bytes.as_slice()).unwrap();
+        let from_dictionary = VibratoTokenizer::from_dictionary(dict).unwrap();
+        assert_eq!(from_dictionary.fingerprint(), test_tokenizer().fingerprint());
+        assert_eq!(from_dictionary.morphemes("東京大学").len(), 2);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_dictionary() {
+        let tokenizer = test_tokenizer();
+        let mapped = unsafe { VibratoTokenizer::new_mmap("./system.dic") }.unwrap();
+        assert_eq!(mapped.fingerprint(), tokenizer.fingerprint());
+        assert_eq!(
+            mapped.morphemes("東京大学"),
+            tokenizer.morphemes("東京大学")
+        );
+    }
+
+    #[test]
+    fn zstd_dictionary() {
+        let compressed = [0x28, 0xb5, 0x2f, 0xfd, 0, 0, 0, 0];
+        let result = VibratoTokenizer::from_reader(compressed.as_slice());
+        #[cfg(not(feature = "zstd"))]
+        assert!(result.err().unwrap().to_string().contains("zstd feature"));
+        #[cfg(feature = "zstd")]
+        {
+            assert!(result.is_err());
+            let dictionary = fs::read("./system.dic").unwrap();
+            let compressed = zstd::encode_all(dictionary.as_slice(), 3).unwrap();
+            let decompressed = VibratoTokenizer::from_reader(compressed.as_slice()).unwrap();
+            assert_eq!(decompressed.fingerprint(), test_tokenizer().fingerprint());
+        }
+    }
+
+    #[test]
+    fn user_lexicon() {
+        let lexicon = "京都,1,1,1000,名詞,固有名詞,地域,一般,*,*,京都,キョウト,キョート";
+        let tokenizer = test_tokenizer().user_lexicon(lexicon.as_bytes()).unwrap();
+        let morphemes = tokenizer.morphemes("京都に");
+        assert_eq!(morphemes[0].surface, "京都");
+        assert_eq!(morphemes[0].lex_type, vibrato::dictionary::LexType::User);
+        assert_eq!(morphemes[0].features().reading(), Some("キョウト"));
+
+        let gazetteer = Gazetteer::new().entity("東京大学");
+        let tokenizer = tokenizer.gazetteer(&gazetteer).unwrap();
+        assert_eq!(
+            tokenizer.morphemes("京都")[0].lex_type,
+            vibrato::dictionary::LexType::User
+        );
+        assert_eq!(tokenizer.morphemes("東京大学").len(), 1);
+
+        // With a new system dictionary, the entries of the gazetteer are tuned again while the
+        // other entries are kept as is.
+        let reloaded = tokenizer
+            .with_system_dictionary(std::fs::File::open("./system.dic").unwrap())
+            .unwrap();
+        assert_eq!(reloaded.user_lexicon.as_str(), format!("{lexicon}\n"));
+        assert_eq!(reloaded.gazetteer_lexicon, tokenizer.gazetteer_lexicon);
+        assert_eq!(reloaded.morphemes("東京大学").len(), 1);
+        assert_eq!(reloaded.fingerprint(), tokenizer.fingerprint());
+        assert!(test_tokenizer()
+            .user_lexicon("京都,1,1".as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn negation() {
+        let tokenizer = test_tokenizer();
+        let negated = |text: &str| {
+            tokenizer
+                .morphemes(text)
+                .into_iter()
+                .filter(|t| t.negated)
+                .map(|t| t.surface)
+                .collect::<Vec<_>>()
+        };
+        assert!(negated("高い").is_empty());
+        assert_eq!(negated("高くない"), vec!["高く"]);
+        assert_eq!(negated("東京に行かなかった"), vec!["行か"]);
+        assert!(negated("行かなければならない").is_empty());
+    }
+
+    #[test]
+    fn recognizer() {
+        use crate::recognizer::{HashtagRecognizer, KaomojiRecognizer};
+
+        let texts = |tokenizer: &VibratoTokenizer, text: &str| {
+            let mut stream = tokenizer.token_stream(text);
+            let mut tokens = vec![];
+            while let Some(token) = stream.next() {
+                tokens.push((token.text.clone(), token.offset_from, token.position));
+            }
+            tokens
+        };
+        let tokenizer = test_tokenizer()
+            .recognizer(HashtagRecognizer::new())
+            .position_mode(PositionMode::CharOffset);
+        assert_eq!(
+            texts(&tokenizer, "#東京大学 に@user"),
+            vec![
+                ("#東京大学".to_string(), 0, 0),
+                (" ".to_string(), 13, 5),
+                ("に".to_string(), 14, 6),
+                ("@user".to_string(), 17, 7),
+            ]
+        );
+
+        let tokenizer = tokenizer.recognizer(HashtagRecognizer::new().analyze_body(true));
+        let with_body = texts(
+            &self::test_tokenizer()
+                .recognizer(HashtagRecognizer::new().analyze_body(true))
+                .position_mode(PositionMode::CharOffset),
+            "#東京大学",
+        );
+        assert_eq!(
+            with_body,
+            vec![
+                ("#東京大学".to_string(), 0, 0),
+                ("東京".to_string(), 1, 1),
+                ("大学".to_string(), 7, 3),
+            ]
+        );
+        assert_ne!(tokenizer.fingerprint(), self::test_tokenizer().fingerprint());
+
+        let tokenizer = self::test_tokenizer().recognizer(KaomojiRecognizer::new().remove(true));
+        assert_eq!(
+            texts(&tokenizer, "すもも(^o^)"),
+            vec![("すもも".to_string(), 0, 0)]
+        );
+    }
+
+    #[test]
+    fn analyze_with_features() {
+        let tokenizer = test_tokenizer().filter(RemoveParticles);
+        let analyzed = tokenizer.analyze_with_features("東京に行きました");
+        let texts = analyzed
+            .iter()
+            .map(|(token, morpheme)| {
+                (
+                    token.text.as_str(),
+                    morpheme.features().pos_tag(),
+                    morpheme.features().base_form().map(str::to_string),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts[0],
+            (
+                "東京",
+                "名詞-固有名詞-地域-一般".to_string(),
+                Some("東京".to_string())
+            )
+        );
+        assert_eq!(texts[1].0, "行き");
+        assert_eq!(texts[1].2.as_deref(), Some("行く"));
+        assert_eq!(
+            analyzed.iter().map(|(t, _)| t.clone()).collect::<Vec<_>>(),
+            tokenizer.analyze("東京に行きました", None)
+        );
+    }
+
+    struct Panic;
+
+    impl MorphFilter for Panic {
+        fn filter(&self, _tokens: &mut Vec<MorphToken>) {
+            panic!("broken filter");
+        }
+    }
+
+    #[test]
+    fn error_policy() {
+        let texts = |policy: ErrorPolicy| {
+            let tokenizer = test_tokenizer().filter(Panic).error_policy(policy);
+            let mut stream = tokenizer.token_stream("東京都");
+            let mut texts = vec![];
+            while let Some(token) = stream.next() {
+                texts.push(token.text.clone());
+            }
+            texts
+        };
+        assert_eq!(texts(ErrorPolicy::FallbackNgram), vec!["東京", "京都"]);
+        assert!(texts(ErrorPolicy::Skip).is_empty());
+        let strict = panic::catch_unwind(|| texts(ErrorPolicy::Strict));
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    fn pre_segmented() {
+        let tokenizer = test_tokenizer()
+            .pre_segmented(true)
+            .position_mode(PositionMode::CharOffset);
+        let text = "すもも も\u{3000}もも  の うち\n";
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = vec![];
+        while let Some(token) = stream.next() {
+            tokens.push((
+                &text[token.offset_from..token.offset_to],
+                token.text.clone(),
+                token.position,
+            ));
+        }
+        let expected = [
+            ("すもも", 0),
+            ("も", 3),
+            ("もも", 4),
+            ("の", 6),
+            ("うち", 7),
+        ];
+        assert_eq!(tokens.len(), expected.len());
+        for ((original, text, position), (word, expected_position)) in tokens.iter().zip(expected) {
+            assert_eq!(
+                (*original, text.as_str(), *position),
+                (word, word, expected_position)
+            );
+        }
+        assert_ne!(tokenizer.fingerprint(), self::test_tokenizer().fingerprint());
+    }
+
+    #[test]
+    fn utf16_tokens() {
+        let tokens = test_tokenizer().utf16_tokens("𠮷野家で東京");
+        let last = tokens.last().unwrap();
+        assert_eq!(last.token.text, "東京");
+        assert_eq!(last.token.offset_from, 13);
+        assert_eq!((last.utf16_offset_from, last.utf16_offset_to), (5, 7));
+    }
+
+    #[test]
+    fn reproducible() {
+        let text = "すもももももももものうち東京大学";
+        let other = VibratoTokenizer::from_reader(fs::File::open("./system.dic").unwrap()).unwrap();
+        assert_eq!(other.fingerprint(), test_tokenizer().fingerprint());
+        assert_eq!(other.analyze(text, None), test_tokenizer().analyze(text, None));
+    }
 }
@@ -0,0 +1,83 @@
+use std::ops::Range;
+
+use tantivy::tokenizer::Token as TToken;
+
+/// Converts the byte offsets of a text into offsets in UTF-16 code units, the unit of the
+/// string indices of JavaScript, e.g. to place highlights in a web frontend.
+///
+/// This works with the tokens of any tokenizer; see
+/// [`VibratoTokenizer::utf16_tokens`](crate::VibratoTokenizer::utf16_tokens) for the
+/// tokens of a `VibratoTokenizer`.
+#[derive(Debug, Clone)]
+pub struct Utf16Offsets {
+    /// Byte and UTF-16 offsets of the start of each character and of the end of the text.
+    boundaries: Vec<(usize, usize)>,
+}
+
+impl Utf16Offsets {
+    /// Index the character boundaries of `text`.
+    pub fn new(text: &str) -> Utf16Offsets {
+        let mut boundaries = Vec::with_capacity(text.len() + 1);
+        let mut utf16 = 0;
+        for (i, c) in text.char_indices() {
+            boundaries.push((i, utf16));
+            utf16 += c.len_utf16();
+        }
+        boundaries.push((text.len(), utf16));
+        Utf16Offsets { boundaries }
+    }
+
+    /// Returns the UTF-16 offset of the byte offset `offset`, which must be a character
+    /// boundary of the text. Offsets inside a character are rounded down to its start.
+    pub fn offset(&self, offset: usize) -> usize {
+        let i = self.boundaries.partition_point(|&(b, _)| b <= offset);
+        self.boundaries[i.saturating_sub(1)].1
+    }
+
+    /// Returns the UTF-16 range of the byte range `range`.
+    pub fn range(&self, range: Range<usize>) -> Range<usize> {
+        self.offset(range.start)..self.offset(range.end)
+    }
+}
+
+/// A token with its offsets in UTF-16 code units besides its byte offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utf16Token {
+    /// The token as emitted to tantivy, with byte offsets.
+    pub token: TToken,
+    /// UTF-16 offset of the first character.
+    pub utf16_offset_from: usize,
+    /// UTF-16 offset of the last character + 1.
+    pub utf16_offset_to: usize,
+}
+
+impl Utf16Token {
+    pub(crate) fn new(token: TToken, offsets: &Utf16Offsets) -> Utf16Token {
+        let range = offsets.range(token.offset_from..token.offset_to);
+        Utf16Token {
+            token,
+            utf16_offset_from: range.start,
+            utf16_offset_to: range.end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets() {
+        let text = "a東京𠮷野家b";
+        let offsets = Utf16Offsets::new(text);
+        let utf16 = |s: &str| {
+            let start = text.find(s).unwrap();
+            offsets.range(start..start + s.len())
+        };
+        assert_eq!(utf16("a"), 0..1);
+        assert_eq!(utf16("東京"), 1..3);
+        assert_eq!(utf16("𠮷"), 3..5);
+        assert_eq!(utf16("野家b"), 5..8);
+        assert_eq!(offsets.offset(text.len() + 1), 8);
+    }
+}
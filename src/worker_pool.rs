@@ -0,0 +1,115 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use crossbeam_queue::ArrayQueue;
+use vibrato::tokenizer::worker::Worker;
+use vibrato::Tokenizer;
+
+/// Default maximum number of idle workers kept by a [`WorkerPool`].
+pub(crate) const DEFAULT_POOL_SIZE: usize = 16;
+
+/// A pool of Vibrato workers of a tokenizer, reused across texts to avoid reallocating their
+/// lattice buffers. The idle workers are kept in a lock-free queue, so that indexing threads
+/// do not contend on a lock for each document.
+pub(crate) struct WorkerPool {
+    // Declared before `tokenizer` so that the workers are dropped before it. `None` if the
+    // reuse is disabled.
+    workers: Option<ArrayQueue<Worker<'static>>>,
+    tokenizer: Arc<Tokenizer>,
+    size: usize,
+}
+
+impl WorkerPool {
+    /// Create a pool keeping up to `size` idle workers of `tokenizer`. 0 disables the reuse.
+    pub(crate) fn new(tokenizer: Arc<Tokenizer>, size: usize) -> WorkerPool {
+        WorkerPool {
+            workers: (size > 0).then(|| ArrayQueue::new(size)),
+            tokenizer,
+            size,
+        }
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns an idle worker, or a new one if there is none. The worker goes back to the pool
+    /// when it is dropped.
+    pub(crate) fn get(&self) -> PooledWorker<'_> {
+        let worker = self.workers.as_ref().and_then(ArrayQueue::pop);
+        PooledWorker {
+            pool: self,
+            worker: Some(worker.unwrap_or_else(|| self.tokenizer.new_worker())),
+        }
+    }
+}
+
+/// A worker borrowed from a [`WorkerPool`].
+pub(crate) struct PooledWorker<'p> {
+    pool: &'p WorkerPool,
+    worker: Option<Worker<'p>>,
+}
+
+impl<'p> Deref for PooledWorker<'p> {
+    type Target = Worker<'p>;
+
+    fn deref(&self) -> &Worker<'p> {
+        self.worker.as_ref().unwrap()
+    }
+}
+
+impl<'p> DerefMut for PooledWorker<'p> {
+    fn deref_mut(&mut self) -> &mut Worker<'p> {
+        self.worker.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledWorker<'_> {
+    fn drop(&mut self) {
+        let (Some(worker), Some(workers)) = (self.worker.take(), &self.pool.workers) else {
+            return;
+        };
+        // SAFETY: the worker borrows the tokenizer of the pool, either because the pool
+        // created it or because it was taken from the pool. The tokenizer is owned by the pool
+        // through an `Arc` and outlives the workers the pool stores, which are dropped first
+        // and are only handed out with the lifetime of a borrow of the pool.
+        let worker = unsafe { std::mem::transmute::<Worker<'_>, Worker<'static>>(worker) };
+        // The worker is dropped if the pool is full.
+        let _ = workers.push(worker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vibrato::Dictionary;
+
+    use super::*;
+
+    #[test]
+    fn reuse() {
+        let file = std::fs::File::open("./system.dic")
+            .expect("system.dic is required in the project root directory");
+        let tokenizer = Arc::new(Tokenizer::new(Dictionary::read(file).unwrap()));
+        let surfaces = |pool: &WorkerPool, text: &str| {
+            let mut worker = pool.get();
+            worker.reset_sentence(text);
+            worker.tokenize();
+            worker
+                .token_iter()
+                .map(|t| t.surface().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let pool = WorkerPool::new(tokenizer.clone(), 1);
+        for text in ["東京大学", "すもももももももものうち"] {
+            let _other = pool.get();
+            surfaces(&pool, text);
+        }
+        assert_eq!(pool.workers.as_ref().unwrap().len(), 1);
+        assert_eq!(surfaces(&pool, "東京大学"), vec!["東京", "大学"]);
+
+        let unpooled = WorkerPool::new(tokenizer, 0);
+        surfaces(&unpooled, "東京");
+        assert!(unpooled.workers.is_none());
+    }
+}
@@ -0,0 +1,27 @@
+//! Compares `VibratoTokenizer::kuromoji_compatible` with the terms produced by the `kuromoji`
+//! analyzer of Elasticsearch (IPADIC) for the corpus in `tests/data/kuromoji_parity.tsv`.
+//!
+//! The test dictionary only has a few words, so the IPADIC words of the corpus it lacks are
+//! added from `tests/data/kuromoji_parity.csv`.
+use tantivy::tokenizer::Tokenizer;
+use tantivy_vibrato::VibratoTokenizer;
+
+#[test]
+fn kuromoji_parity() {
+    let tokenizer = VibratoTokenizer::new("./system.dic")
+        .expect("system.dic is required in the project root directory")
+        .user_lexicon(include_str!("data/kuromoji_parity.csv").as_bytes())
+        .unwrap()
+        .kuromoji_compatible();
+
+    let corpus = include_str!("data/kuromoji_parity.tsv");
+    for line in corpus.lines() {
+        let (text, expected) = line.split_once('\t').unwrap();
+        let mut stream = tokenizer.token_stream(text);
+        let mut terms = vec![];
+        while let Some(token) = stream.next() {
+            terms.push(token.text.clone());
+        }
+        assert_eq!(terms.join(" "), expected, "{text}");
+    }
+}